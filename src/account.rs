@@ -1,126 +1,302 @@
-use sha2::{Sha512, Digest};
-use std::convert::TryInto;
-use rand::rngs::OsRng;
-use std::fmt;
-use ed25519_dalek::Keypair;
-use crate::positive_f64::PositiveF64;
+use rand::{rngs::OsRng, RngCore};
+use std::{convert::TryFrom, fmt, error, hash};
+use ed25519_dalek::{Keypair, PublicKey, SecretKey};
+use crate::{
+    amount::Amount,
+    transaction::TxError,
+    hasher::{Hasher, Sha512Hasher},
+};
+use argon2::{Argon2, Params, PasswordHash, PasswordHasher, PasswordVerifier, password_hash::{Output, SaltString}};
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+/// The default maximum length of the first name and of the last name of an `Account`, in characters.
+pub const MAX_NAME_LEN: usize = 64;
 
 /// A structure to handle accounts for the currency.
 /// 
-/// Every account has a first name, a last name, a balance (set to 0.0) and a password,
-/// which is used to validate the transactions; the password is saved using the SHA-512 hashing algorithm.
+/// Every account has a first name, a last name, a balance (set to 0) and a password,
+/// which is used to validate the transactions; the password is saved as its Argon2id hash, with a random salt.
 /// Also, every account has a `Keypair` which is used to validate the signature of the transaction,
 /// using the `ed25519_dalek` crate.
-#[derive(Debug, Clone, PartialEq)]
+/// 
+/// The balance is an `Amount`, i.e. a number of the smallest units of the currency.
+/// 
+/// When an account is serialized, only its names, its balance and its public key are written:
+/// the secret key and the hash of the password are never serialized, so a deserialized account
+/// can't make transactions until its secret is imported with `import_secret()`,
+/// from the encrypted secret returned by `export_secret()`.
+/// 
+/// Two accounts are equal, and have the same hash, if they have the same public key, which is their identity:
+/// the names and the balance aren't compared, so an account is still equal to itself after its balance changes.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "PublicAccount", from = "PublicAccount"))]
 pub struct Account {
     first_name: String,
     last_name: String,
-    balance: PositiveF64,
+    balance: Amount,
     keypair: [u8; 64],
-    hash_password: [u8; 64],
+    hash_password: String,
+}
+
+/// The fields of an `Account` that are serialized.
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct PublicAccount {
+    first_name: String,
+    last_name: String,
+    balance: Amount,
+    #[serde(with = "crate::serde_hex::key")]
+    public_key: [u8; 32],
+}
+
+/// The rules the first name and the last name of an `Account` must follow, see `Account::new_with_rules()`:
+/// - a name can't be empty, or made only of whitespace
+/// - a name can't be longer than `max_len` characters
+/// - every character of a name must be allowed by `allowed`
+/// 
+/// By default, the names can be `MAX_NAME_LEN` characters long, and every character but the control characters
+/// is allowed, so the names can have any Unicode letter.
+#[derive(Debug, Clone, Copy)]
+pub struct NameRules {
+    pub max_len: usize,
+    pub allowed: fn(char) -> bool,
+}
+
+impl NameRules {
+    /// Checks if a name follows the rules.
+    /// 
+    /// The method returns a `Result<(), AccountError>`:
+    /// - if the name is empty, an `AccountError::EmptyName` error is returned
+    /// - if the name is too long, an `AccountError::NameTooLong` error is returned
+    /// - if a character isn't allowed, an `AccountError::InvalidCharacter` error is returned
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::account::{NameRules, AccountError};
+    /// let rules = NameRules::default();
+    /// 
+    /// assert_eq!(rules.check("Zoë"), Ok(()));
+    /// assert_eq!(rules.check("  "), Err(AccountError::EmptyName));
+    /// assert_eq!(rules.check("Zo\u{7}ë"), Err(AccountError::InvalidCharacter));
+    /// ```
+    pub fn check(&self, name: &str) -> Result<(), AccountError> {
+        if name.trim().is_empty() {
+            Err(AccountError::EmptyName)
+        } else if name.chars().count() > self.max_len {
+            Err(AccountError::NameTooLong)
+        } else if !name.chars().all(self.allowed) {
+            Err(AccountError::InvalidCharacter)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Default for NameRules {
+    fn default() -> Self {
+        Self {
+            max_len: MAX_NAME_LEN,
+            allowed: |c| !c.is_control(),
+        }
+    }
 }
 
 impl Account {
     /// Generates a new `Account`.
     /// 
+    /// The function returns a `Result<Account, AccountError>`, because the first name and the last name
+    /// must follow the default `NameRules`, see `new_with_rules()`.
+    /// 
     /// # Example
     /// ```
-    /// # use blockchain::account::Account;
-    /// let ferris = Account::new("Ferris", "Rusty", "I_Love_Ferris_123!#"); // make sure your password is safe enough!
+    /// # use blockchain::account::{Account, AccountError};
+    /// let ferris = Account::new("Ferris", "Rusty", "I_Love_Ferris_123!#").unwrap(); // make sure your password is safe enough!
     /// 
-    /// assert_eq!(ferris.balance(), 0.0); // your balance is 0.0 when the account is created
+    /// assert_eq!(ferris.balance(), 0); // your balance is 0 when the account is created
+    /// assert!(matches!(Account::new("", "Rusty", "I_Love_Ferris_123!#"), Err(AccountError::EmptyName)));
     /// ```
-    pub fn new(first_name: &str, last_name: &str, password: &str) -> Self {
+    pub fn new(first_name: &str, last_name: &str, password: &str) -> Result<Self, AccountError> {
+        Account::new_with_rules(first_name, last_name, password, &NameRules::default())
+    }
+
+    /// Generates a new `Account` like `new()`, checking the first name and the last name with the given `NameRules`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::account::{Account, AccountError, NameRules};
+    /// let rules = NameRules { max_len: 8, allowed: char::is_alphabetic };
+    /// 
+    /// assert!(Account::new_with_rules("Ada", "Byron", "4d4_byr0n", &rules).is_ok());
+    /// assert!(matches!(Account::new_with_rules("Ada", "Lovelace-Byron", "4d4_byr0n", &rules), Err(AccountError::NameTooLong)));
+    /// assert!(matches!(Account::new_with_rules("Ada", "B.", "4d4_byr0n", &rules), Err(AccountError::InvalidCharacter)));
+    /// ```
+    pub fn new_with_rules(first_name: &str, last_name: &str, password: &str, rules: &NameRules) -> Result<Self, AccountError> {
+        rules.check(first_name)?;
+        rules.check(last_name)?;
+
         let mut csprng = OsRng;
         let keypair: Keypair = Keypair::generate(&mut csprng);
 
-        let mut hasher = Sha512::new();
+        Ok(Account::with_keypair(first_name, last_name, password, keypair))
+    }
+
+    /// Generates a new `Account` whose `Keypair` is derived from the given seed instead of being random,
+    /// so that the same seed always generates the same keys; this is useful to write reproducible tests.
+    /// 
+    /// Like `new()`, the names must follow the default `NameRules`.
+    /// 
+    /// Note: anyone knowing the seed knows the secret key of the account.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::account::Account;
+    /// let gale = Account::from_seed("Gale", "Hunt", "g4l3_hunt", [7; 32]).unwrap();
+    /// let twin = Account::from_seed("Gale", "Hunt", "g4l3_hunt", [7; 32]).unwrap();
+    /// 
+    /// assert_eq!(gale.public_key(), twin.public_key());
+    /// assert_eq!(gale.address(), twin.address());
+    /// ```
+    pub fn from_seed(first_name: &str, last_name: &str, password: &str, seed: [u8; 32]) -> Result<Self, AccountError> {
+        let rules = NameRules::default();
+
+        rules.check(first_name)?;
+        rules.check(last_name)?;
+
+        let secret = SecretKey::from_bytes(&seed).expect("Error generating the secret key from the seed.");
+        let public = PublicKey::from(&secret);
+
+        Ok(Account::with_keypair(first_name, last_name, password, Keypair { secret, public }))
+    }
+
+    /// Generates an `Account` knowing only its public key, without its secret key and its password.
+    #[cfg(feature = "serde")]
+    fn from_public_key(first_name: String, last_name: String, balance: Amount, public_key: [u8; 32]) -> Self {
+        let mut keypair = [0; 64];
+        keypair[32..].copy_from_slice(&public_key);
 
-        hasher.update(password.as_bytes());
+        Self {
+            first_name,
+            last_name,
+            balance,
+            keypair,
+            hash_password: String::new(),
+        }
+    }
 
-        let hash_password = hasher
-            .finalize()[..]
-            .try_into()
-            .expect("Error generating the SHA-512 hash of the password.");
+    /// Generates a new `Account` with the given `Keypair`.
+    fn with_keypair(first_name: &str, last_name: &str, password: &str, keypair: Keypair) -> Self {
+        let hash_password = Account::hash(password);
 
         Self {
             first_name: String::from(first_name),
             last_name: String::from(last_name),
-            balance: PositiveF64::new(0.0).unwrap(),
+            balance: 0,
             keypair: keypair.to_bytes(),
             hash_password,
         }
     }
 
-    /// A method to add money to your balance; the amount can't be `0.0`, and can't be negative.
+    /// A method to add money to your balance; the amount can't be `0`, and the balance can't overflow.
     /// 
     /// # Example
     /// ```
     /// # use blockchain::account::Account;
-    /// let mut allen = Account::new("Allen", "Johnson", "AllenJ500321#");
-    /// allen.add_money(100.0);
+    /// let mut allen = Account::new("Allen", "Johnson", "AllenJ500321#").unwrap();
+    /// allen.add_money(100);
     /// 
-    /// assert_eq!(allen.balance(), 100.0);
+    /// assert_eq!(allen.balance(), 100);
     /// ```
     #[allow(dead_code)]
-    pub fn add_money(&mut self, amount: f64) {
-        if amount == 0.0 {
+    pub fn add_money(&mut self, amount: Amount) {
+        if amount == 0 {
             eprintln!("Can't add a zero-value amount to the balance.")
         } else {
-            match PositiveF64::new(amount) {
-                Ok(a) => self.balance += a,
-                Err(e) => eprintln!("{} Details: can't add a negative amount to the balance.", e),
+            match self.balance.checked_add(amount) {
+                Some(balance) => self.balance = balance,
+                None => eprintln!("Can't add an amount that would overflow the balance."),
             }
         }
     }
 
 
-    /// A method to subtract money to your balance; the amount to subtract can't be `0.0`, can't be negative,
+    /// A method to subtract money to your balance; the amount to subtract can't be `0`,
     /// and can't be more than the amount in your balance.
     /// 
     /// # Example
     /// ```
     /// # use blockchain::account::Account;
-    /// let mut branda = Account::new("Branda", "Pickle", "brandA;picklE;+1992");
-    /// branda.add_money(50.0); // you must have more than 0.0 in your balance
+    /// let mut branda = Account::new("Branda", "Pickle", "brandA;picklE;+1992").unwrap();
+    /// branda.add_money(50); // you must have more than 0 in your balance
     /// 
-    /// branda.sub_money(20.0);
+    /// branda.sub_money(20);
     /// 
-    /// assert_eq!(branda.balance(), 30.0); // 50.0 - 30.0 = 20.0
+    /// assert_eq!(branda.balance(), 30); // 50 - 30 = 20
     /// ```
     #[allow(dead_code)]
-    pub fn sub_money(&mut self, amount: f64) {
-        if amount == 0.0 {
+    pub fn sub_money(&mut self, amount: Amount) {
+        if amount == 0 {
             eprintln!("Can't subtract a zero-value amount to the balance.")
         } else {
-            match PositiveF64::new(amount) {
-                Ok(a) => {
-                    if PositiveF64::new(self.balance.value() - a.value()).is_ok() { // if the difference is >= 0.0
-                        self.balance -= a
-                    } else {
-                        eprintln!("Can't subtract an amount that is more than the amount in your balance.")
-                    }
-                },
-                Err(e) => eprintln!("{} Details: can't subtract a negative amount to the balance.", e)
+            match self.balance.checked_sub(amount) {
+                Some(balance) => self.balance = balance,
+                None => eprintln!("Can't subtract an amount that is more than the amount in your balance."),
             }
         }
     }
 
+    /// A method to spend money from your balance, which is deducted only if the funds suffice.
+    /// 
+    /// The method returns a `Result<(), TxError>`:
+    /// - if the amount is zero, a `TxError::InvalidAmount` error is returned
+    /// - if the amount is more than the balance, a `TxError::InsufficientFunds` error is returned
+    /// 
+    /// In both cases the balance is left untouched.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::account::Account;
+    /// # use blockchain::transaction::TxError;
+    /// let mut elba = Account::new("Elba", "Frye", "3lb4_fry3").unwrap();
+    /// elba.add_money(30);
+    /// 
+    /// assert_eq!(elba.spend(31), Err(TxError::InsufficientFunds));
+    /// assert_eq!(elba.spend(0), Err(TxError::InvalidAmount));
+    /// assert_eq!(elba.spend(30), Ok(())); // the whole balance can be spent
+    /// 
+    /// assert_eq!(elba.balance(), 0);
+    /// ```
+    pub fn spend(&mut self, amount: Amount) -> Result<(), TxError> {
+        if amount == 0 {
+            return Err(TxError::InvalidAmount);
+        }
+
+        match self.balance.checked_sub(amount) {
+            Some(balance) => {
+                self.balance = balance;
+
+                Ok(())
+            },
+            None => Err(TxError::InsufficientFunds),
+        }
+    }
+
     /// This method returns the balance of the account, since the `balance` field isn't `pub`.
     /// 
     /// # Example
     /// ```
     /// # use blockchain::account::Account;
-    /// let mut walter = Account::new("Walter", "Clifton", "SuperWalter2000?");
+    /// let mut walter = Account::new("Walter", "Clifton", "SuperWalter2000?").unwrap();
     /// 
-    /// assert_eq!(walter.balance(), 0.0); // your balance is 0.0 when the account is created
+    /// assert_eq!(walter.balance(), 0); // your balance is 0 when the account is created
     /// 
-    /// walter.add_money(50.0);
+    /// walter.add_money(50);
     /// 
-    /// assert_eq!(walter.balance(), 50.0);
+    /// assert_eq!(walter.balance(), 50);
     /// ```
-    pub fn balance(&self) -> f64 {
-        self.balance.value()
+    pub fn balance(&self) -> Amount {
+        self.balance
     }
 
     /// This method returns the keypair of the account, since the `keypair` field isn't `pub`.
@@ -128,7 +304,7 @@ impl Account {
     /// # Example
     /// ```
     /// # use blockchain::account::Account;
-    /// let cecilia = Account::new("Cecilia", "Lacey", "Cecilia_Is_Be@utiful49");
+    /// let cecilia = Account::new("Cecilia", "Lacey", "Cecilia_Is_Be@utiful49").unwrap();
     /// 
     /// let cecilia_keypair = cecilia.keypair(); // this variable now contains cecilia's keypair
     /// ```
@@ -136,87 +312,331 @@ impl Account {
         self.keypair
     }
 
+    /// This method returns the public key of the account, taken from its keypair,
+    /// which identifies the account regardless of its balance.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::account::Account;
+    /// let mut farah = Account::new("Farah", "Nolan", "Farah_N0lan!!").unwrap();
+    /// let public_key = farah.public_key();
+    /// 
+    /// farah.add_money(10);
+    /// 
+    /// assert_eq!(farah.public_key(), public_key); // the public key doesn't depend on the balance
+    /// ```
+    pub fn public_key(&self) -> [u8; 32] {
+        let mut public_key = [0; 32];
+
+        public_key.copy_from_slice(&self.keypair[32..]);
+
+        public_key
+    }
+
+    /// This method returns the address of the account, a short identifier that can be shared:
+    /// it's the hex encoding of the first 20 bytes of the SHA-512 hash of the public key.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::account::Account;
+    /// let hana = Account::new("Hana", "Sato", "h4n4_s4t0").unwrap();
+    /// let ivan = Account::new("Ivan", "Horvat", "ivan.horvat.77").unwrap();
+    /// 
+    /// assert_eq!(hana.address(), hana.address());
+    /// assert_ne!(hana.address(), ivan.address());
+    /// assert_eq!(hana.address().len(), 40);
+    /// ```
+    pub fn address(&self) -> String {
+        Account::address_of_key(&self.public_key())
+    }
+
+    /// This function returns the address of the account with the given public key, see `address()`,
+    /// e.g. to get the address of the producer of a block, see `Block::producer()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::account::Account;
+    /// let jade = Account::new("Jade", "Kim", "j4d3_k1m").unwrap();
+    /// 
+    /// assert_eq!(Account::address_of_key(&jade.public_key()), jade.address());
+    /// ```
+    pub fn address_of_key(public_key: &[u8; 32]) -> String {
+        Sha512Hasher::hash(public_key)[..20]
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
     /// This method returns the hash of the password of the account, since the `hash_password` field isn't `pub`.
     /// 
+    /// The password is hashed with Argon2id and a random salt, and the hash is a PHC string
+    /// with the parameters and the salt it was made with; an account without a password has an empty hash.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::account::Account;
+    /// let denzel = Account::new("Denzel", "Pratt", "My_Secret_Password@@@__789").unwrap();
+    /// let ezra = Account::new("Ezra", "Pratt", "My_Secret_Password@@@__789").unwrap();
+    /// 
+    /// assert!(denzel.hash_password().starts_with("$argon2id$v=19$"));
+    /// assert_ne!(denzel.hash_password(), ezra.hash_password()); // the salts differ
+    /// ```
+    pub fn hash_password(&self) -> &str {
+        &self.hash_password
+    }
+
+    /// This method checks if the given password is the password of the account,
+    /// by hashing it with the salt of the `hash_password` of the account; the password itself is never stored.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::account::Account;
+    /// let greta = Account::new("Greta", "Lind", "gr3t4_L1nd!").unwrap();
+    /// 
+    /// assert!(greta.verify_password("gr3t4_L1nd!"));
+    /// assert!(!greta.verify_password("greta_lind"));
+    /// ```
+    pub fn verify_password(&self, attempt: &str) -> bool {
+        // an account without a password has an empty hash, which isn't a valid PHC string
+        PasswordHash::new(&self.hash_password)
+            .is_ok_and(|hash| Account::argon2().verify_password(attempt.as_bytes(), &hash).is_ok())
+    }
+
+    /// This method returns the secret key of the account encrypted with its password, to be stored
+    /// since the secret key isn't serialized with the account; the secret is imported back with `import_secret()`.
+    /// 
+    /// The secret key is encrypted with 32 bytes derived from the password with Argon2id, like the hash of the password,
+    /// and a new random salt; the secret is returned as a PHC string with the parameters and the salt of the derivation,
+    /// whose hash is the encrypted secret key, so that it can be decrypted even if the parameters of the crate change.
+    /// 
+    /// If the password isn't the password of the account, a `TxError::Unauthorized` error is returned.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::account::Account;
+    /// # use blockchain::transaction::TxError;
+    /// let kira = Account::new("Kira", "Vogt", "k1r4_v0gt").unwrap();
+    /// 
+    /// assert!(kira.export_secret("k1r4_v0gt").unwrap().starts_with("$argon2id$v=19$m=19456,t=2,p=1$"));
+    /// assert_eq!(kira.export_secret("kira_vogt"), Err(TxError::Unauthorized));
+    /// ```
+    pub fn export_secret(&self, password: &str) -> Result<String, TxError> {
+        if !self.verify_password(password) {
+            return Err(TxError::Unauthorized);
+        }
+
+        let salt = Account::salt();
+        let mut derived = Account::argon2()
+            .hash_password(password.as_bytes(), &salt)
+            .expect("The password can always be hashed with valid parameters.");
+
+        let key = derived.hash.expect("The hash of the password is always calculated.");
+        let mut secret = [0; 32];
+
+        for (i, byte) in secret.iter_mut().enumerate() {
+            *byte = self.keypair[i] ^ key.as_bytes()[i];
+        }
+
+        derived.hash = Some(Output::new(&secret).expect("An output of 32 bytes is always valid."));
+
+        Ok(derived.to_string())
+    }
+
+    /// This method imports the secret key exported with `export_secret()`, e.g. into an account
+    /// which was deserialized, so that the account can make transactions again with the same password.
+    /// 
+    /// The secret is decrypted with the parameters and the salt recorded in it, see `export_secret()`.
+    /// 
+    /// If the secret isn't a valid PHC string of Argon2id, if it can't be decrypted with the password,
+    /// or if it isn't the secret key of the account, a `TxError::Unauthorized` error is returned and the account is left untouched.
+    /// 
     /// # Example
     /// ```
     /// # use blockchain::account::Account;
-    /// # use hex_literal::hex;
-    /// let denzel = Account::new("Denzel", "Pratt", "My_Secret_Password@@@__789");
+    /// # use blockchain::transaction::TxError;
+    /// # #[cfg(feature = "serde")] {
+    /// let lars = Account::new("Lars", "Brun", "l4rs_brun").unwrap();
+    /// let exported = lars.export_secret("l4rs_brun").unwrap();
+    /// 
+    /// let json = serde_json::to_string(&lars).unwrap();
+    /// let mut restored: Account = serde_json::from_str(&json).unwrap();
     /// 
-    /// assert_eq!(denzel.hash_password(), hex!("de4b5227910fb4c8fa8a7702dc25807a4c6d50090615b0cd5a52446438b461071d3be479d2710ae65d48cb9fc30a3a7775f5b97a6d5b4692d17c73ab6dfd461f"));
+    /// assert!(!restored.verify_password("l4rs_brun")); // the password isn't serialized
+    /// assert_eq!(restored.import_secret(&exported, "wrong"), Err(TxError::Unauthorized));
+    /// 
+    /// restored.import_secret(&exported, "l4rs_brun").unwrap();
+    /// 
+    /// assert_eq!(restored.keypair(), lars.keypair());
+    /// assert!(restored.verify_password("l4rs_brun"));
+    /// # }
     /// ```
-    pub fn hash_password(&self) -> [u8; 64] {
-        self.hash_password
+    pub fn import_secret(&mut self, exported: &str, password: &str) -> Result<(), TxError> {
+        let exported = PasswordHash::new(exported).map_err(|_| TxError::Unauthorized)?;
+        let encrypted = exported.hash.filter(|hash| hash.len() == 32).ok_or(TxError::Unauthorized)?;
+        let salt = exported.salt.ok_or(TxError::Unauthorized)?;
+
+        if exported.algorithm != argon2::Algorithm::Argon2id.ident() {
+            return Err(TxError::Unauthorized);
+        }
+
+        let params = Params::try_from(&exported).map_err(|_| TxError::Unauthorized)?;
+        let key = Argon2::default()
+            .hash_password_customized(password.as_bytes(), Some(exported.algorithm), exported.version, params, salt)
+            .ok()
+            .and_then(|derived| derived.hash)
+            .ok_or(TxError::Unauthorized)?;
+
+        let mut secret = [0; 32];
+
+        for (i, byte) in secret.iter_mut().enumerate() {
+            *byte = encrypted.as_bytes()[i] ^ key.as_bytes()[i];
+        }
+
+        // a wrong password decrypts a secret key which doesn't match the public key
+        let secret = SecretKey::from_bytes(&secret).map_err(|_| TxError::Unauthorized)?;
+
+        if PublicKey::from(&secret).to_bytes() != self.public_key() {
+            return Err(TxError::Unauthorized);
+        }
+
+        self.keypair[..32].copy_from_slice(secret.as_bytes());
+        self.hash_password = Account::hash(password);
+
+        Ok(())
+    }
+
+    /// Calculates the Argon2id hash of a password, with a new random salt.
+    pub(crate) fn hash(password: &str) -> String {
+        let salt = Account::salt();
+
+        Account::argon2()
+            .hash_password(password.as_bytes(), &salt)
+            .expect("The password can always be hashed with valid parameters.")
+            .to_string()
+    }
+
+    /// Generates a new random salt of 16 bytes for `argon2()`.
+    fn salt() -> SaltString {
+        let mut salt = [0; 16];
+        OsRng.fill_bytes(&mut salt);
+
+        SaltString::encode_b64(&salt).expect("A salt of 16 bytes is always valid.")
+    }
+
+    /// The Argon2id hasher of the passwords and of the exported secrets, with the default parameters (19 MiB of memory and 2 passes);
+    /// the tests use the smallest memory and a single pass, since they hash a lot of passwords.
+    fn argon2() -> Argon2<'static> {
+        #[cfg(not(test))]
+        let params = Params::DEFAULT;
+
+        #[cfg(test)]
+        let params = Params::new(Params::MIN_M_COST, 1, 1, None).expect("The minimum parameters are valid.");
+
+        Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
     }
 
     /// Adds money to an account without checking the input.
     /// 
     /// # Safety
-    /// A method to add money to your balance, without checking if the amount is non-zero and positive.
+    /// A method to add money to your balance, without checking if the amount is non-zero, or if the balance overflows.
     /// 
     /// # Examples
     /// ```
     /// # use blockchain::account::Account;
     /// unsafe {
-    ///     let mut mary = Account::new("Mary", "Shelley", "marymaryMoo123#");
+    ///     let mut mary = Account::new("Mary", "Shelley", "marymaryMoo123#").unwrap();
     /// 
-    ///     mary.add_money_unchecked(10.0);
+    ///     mary.add_money_unchecked(10);
     /// 
-    ///     assert_eq!(mary.balance(), 10.0);
+    ///     assert_eq!(mary.balance(), 10);
     /// }
     /// ```
     /// 
     /// # Panics
     /// The invalid amount could lead to uncertain behaviour with calculations.
     /// 
-    /// ```
+    /// ```no_run
     /// # use blockchain::account::Account;
     /// unsafe {
-    ///     let mut john = Account::new("John", "Keats", "my_password2021!");
+    ///     let mut john = Account::new("John", "Keats", "my_password2021!").unwrap();
+    ///     john.add_money(4);
     /// 
-    ///     john.add_money_unchecked(-4.0);
+    ///     john.add_money_unchecked(u64::MAX);
     /// 
     ///     // The above expression could make the program panic!
     /// }
     /// ```
-    pub unsafe fn add_money_unchecked(&mut self, amount: f64) {
-        self.balance += PositiveF64::new_unchecked(amount)
+    pub unsafe fn add_money_unchecked(&mut self, amount: Amount) {
+        self.balance += amount
     }
 
     /// Subtracts money from an account without checking the input.
     /// 
     /// # Safety
-    /// A method to subtract money to your balance, without checking if the amount is non-zero and positive.
+    /// A method to subtract money to your balance, without checking if the amount is non-zero,
+    /// or if it's more than the amount in your balance.
     /// 
     /// # Examples
     /// ```
     /// # use blockchain::account::Account;
     /// unsafe {
-    ///     let mut mary = Account::new("Mary", "Shelley", "marymaryMoo123#");
-    ///     mary.add_money(10.0); // you must have more than 0.0 in your balance
+    ///     let mut mary = Account::new("Mary", "Shelley", "marymaryMoo123#").unwrap();
+    ///     mary.add_money(10); // you must have more than 0 in your balance
     /// 
-    ///     mary.sub_money_unchecked(8.0);
+    ///     mary.sub_money_unchecked(8);
     /// 
-    ///     assert_eq!(mary.balance(), 2.0); // 10.0 - 8.0 = 2.0
+    ///     assert_eq!(mary.balance(), 2); // 10 - 8 = 2
     /// }
     /// ```
     /// 
     /// # Panics
     /// The invalid amount could lead to uncertain behaviour with calculations.
     /// 
-    /// ```
+    /// ```no_run
     /// # use blockchain::account::Account;
     /// unsafe {
-    ///     let mut john = Account::new("John", "Keats", "my_password2021!");
+    ///     let mut john = Account::new("John", "Keats", "my_password2021!").unwrap();
     ///     
-    ///     john.sub_money_unchecked(-7.0);
+    ///     john.sub_money_unchecked(7);
     /// 
     ///     // The above expression could make the program panic!
     /// }
     /// ```
-    pub unsafe fn sub_money_unchecked(&mut self, amount: f64) {
-        self.balance -= PositiveF64::new_unchecked(amount)
+    pub unsafe fn sub_money_unchecked(&mut self, amount: Amount) {
+        self.balance -= amount
+    }
+}
+
+impl PartialEq for Account {
+    fn eq(&self, other: &Self) -> bool {
+        self.public_key() == other.public_key()
+    }
+}
+
+impl Eq for Account {}
+
+impl hash::Hash for Account {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.public_key().hash(state);
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<Account> for PublicAccount {
+    fn from(account: Account) -> Self {
+        Self {
+            public_key: account.public_key(),
+            first_name: account.first_name,
+            last_name: account.last_name,
+            balance: account.balance,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<PublicAccount> for Account {
+    fn from(account: PublicAccount) -> Self {
+        Account::from_public_key(account.first_name, account.last_name, account.balance, account.public_key)
     }
 }
 
@@ -225,3 +645,100 @@ impl fmt::Display for Account {
         write!(f, "({} {}: {})", self.first_name, self.last_name, self.balance)
     }
 }
+
+/// An enum to handle the errors generated while creating an `Account` with invalid names, see `NameRules`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountError {
+    EmptyName,
+    NameTooLong,
+    InvalidCharacter,
+}
+
+impl fmt::Display for AccountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::EmptyName => write!(f, "Empty name."),
+            Self::NameTooLong => write!(f, "Name too long."),
+            Self::InvalidCharacter => write!(f, "Invalid character in the name."),
+        }
+    }
+}
+
+impl error::Error for AccountError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_and_long_names_are_rejected() {
+        let long_name = "a".repeat(MAX_NAME_LEN + 1);
+
+        assert!(matches!(Account::new("", "Test", "password"), Err(AccountError::EmptyName)));
+        assert!(matches!(Account::new("Test", "\t", "password"), Err(AccountError::EmptyName)));
+        assert!(matches!(Account::new(&long_name, "Test", "password"), Err(AccountError::NameTooLong)));
+        assert!(matches!(Account::new("Test\n", "Test", "password"), Err(AccountError::InvalidCharacter)));
+
+        // the length is counted in characters, not in bytes
+        assert!(Account::new(&"é".repeat(MAX_NAME_LEN), "Łukasz", "password").is_ok());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialized_account_has_no_secret() {
+        let account = Account::from_seed("Nell", "Park", "n3ll_p4rk", [9; 32]).unwrap();
+
+        let json = serde_json::to_string(&account).unwrap();
+        let secret: String = account.keypair()[..32].iter().map(|byte| format!("{:02x}", byte)).collect();
+
+        assert!(!json.contains(&secret));
+        assert!(!json.contains(account.hash_password()));
+        assert!(!json.contains("n3ll_p4rk"));
+
+        let restored: Account = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored, account);
+        assert_eq!(restored.keypair()[..32], [0; 32]);
+    }
+
+    #[test]
+    fn accounts_are_compared_by_public_key() {
+        let account = Account::new("Account", "Test", "account_password").unwrap();
+        let other = Account::new("Account", "Test", "account_password").unwrap();
+
+        let mut richer = account.clone();
+        richer.add_money(10);
+
+        assert_eq!(richer, account);
+        assert_ne!(other, account); // same names and balance, but another key
+
+        let accounts: std::collections::HashSet<Account> = vec![account.clone(), richer, other].into_iter().collect();
+
+        assert_eq!(accounts.len(), 2);
+        assert!(accounts.contains(&account));
+    }
+
+    #[test]
+    fn exported_secret_records_the_parameters_of_the_derivation() {
+        let mut account = Account::from_seed("Ines", "Roth", "1n3s_r0th", [3; 32]).unwrap();
+        let exported = account.export_secret("1n3s_r0th").unwrap();
+
+        // the parameters of the tests, then the salt and the encrypted secret key
+        let prefix = format!("$argon2id$v=19$m={},t=1,p=1$", Params::MIN_M_COST);
+
+        assert!(exported.starts_with(&prefix));
+        assert_eq!(exported[prefix.len()..].split('$').count(), 2);
+        assert_ne!(account.export_secret("1n3s_r0th").unwrap(), exported); // the salts differ
+
+        // the secret is decrypted with the recorded parameters, not with the current ones
+        let altered = exported.replacen(",t=1,", ",t=2,", 1);
+
+        assert_eq!(account.import_secret(&altered, "1n3s_r0th"), Err(TxError::Unauthorized));
+        assert_eq!(account.import_secret("[3; 64]", "1n3s_r0th"), Err(TxError::Unauthorized));
+
+        account.keypair[..32].copy_from_slice(&[0; 32]);
+        account.import_secret(&exported, "1n3s_r0th").unwrap();
+
+        assert_eq!(account.keypair()[..32], Account::from_seed("Ines", "Roth", "1n3s_r0th", [3; 32]).unwrap().keypair()[..32]);
+    }
+}
@@ -0,0 +1,52 @@
+//! The amounts of money of the currency, counted in integer units to avoid the rounding errors of `f64`.
+//!
+//! An `Amount` is a number of the smallest units of the currency, and `UNITS_PER_COIN` units make a coin;
+//! `to_coins()` and `from_coins()` convert the amounts to and from decimal coins, e.g. to display them.
+
+/// An amount of money, in the smallest units of the currency.
+pub type Amount = u64;
+
+/// A balance computed from the transactions in a chain, in the smallest units of the currency;
+/// unlike an `Amount` it can be negative, when an account spends money which was added outside of the chain.
+pub type Balance = i64;
+
+/// The number of units in a coin, like the satoshis in a bitcoin.
+pub const UNITS_PER_COIN: Amount = 100_000_000;
+
+/// Converts an amount to decimal coins; the conversion is meant for displaying the amounts,
+/// since the result can be rounded.
+/// 
+/// # Example
+/// ```
+/// # use blockchain::amount::{to_coins, UNITS_PER_COIN};
+/// assert_eq!(to_coins(UNITS_PER_COIN / 4), 0.25);
+/// ```
+pub fn to_coins(amount: Amount) -> f64 {
+    amount as f64 / UNITS_PER_COIN as f64
+}
+
+/// Converts decimal coins to an amount, rounding to the nearest unit.
+/// 
+/// The function returns `None` if the coins are negative or NaN, or if the amount would be too large,
+/// which includes infinite coins: since amounts are integers, a balance can't become NaN.
+/// 
+/// # Example
+/// ```
+/// # use blockchain::amount::{from_coins, UNITS_PER_COIN};
+/// assert_eq!(from_coins(1.5), Some(3 * UNITS_PER_COIN / 2));
+/// assert_eq!(from_coins(0.1), Some(10_000_000));
+/// 
+/// assert_eq!(from_coins(-1.0), None);
+/// assert_eq!(from_coins(f64::NAN), None);
+/// assert_eq!(from_coins(f64::INFINITY), None);
+/// ```
+pub fn from_coins(coins: f64) -> Option<Amount> {
+    let units = (coins * UNITS_PER_COIN as f64).round();
+
+    // the upper bound is 2^64, which isn't an `Amount` anymore
+    if units >= 0.0 && units < Amount::MAX as f64 {
+        Some(units as Amount)
+    } else {
+        None
+    }
+}
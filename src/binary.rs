@@ -0,0 +1,195 @@
+//! The binary encodings of the crate:
+//! - the compact encoding of the blocks and the transactions, used by `Block::to_bytes()` and `Transaction::to_bytes()`,
+//!   which encodes them with `bincode` from their `serde` implementations, with fixed-width little-endian integers;
+//!   the hashes, the signatures and the keys are written as they are, instead of the hex strings of the JSON encoding.
+//!   The encoding is available with the `bincode` feature.
+//! - the `Binary` encoding of the bytes signed by the senders of the transactions, which has the same layout
+//!   for the integers, the arrays, the strings, the vectors and the options, but writes the times as their seconds
+//!   and nanoseconds; it's written in the crate, so that the signed bytes never change with `bincode`.
+
+use std::{fmt, error};
+use chrono::{DateTime, Utc};
+#[cfg(feature = "bincode")]
+use bincode::{DefaultOptions, ErrorKind, Options};
+#[cfg(feature = "bincode")]
+use serde::{Serialize, de::DeserializeOwned};
+
+/// Returns the options of `bincode` the values are encoded with.
+#[cfg(feature = "bincode")]
+fn options() -> impl Options {
+    DefaultOptions::new().with_fixint_encoding().allow_trailing_bytes()
+}
+
+/// Encodes a value with `bincode`.
+#[cfg(feature = "bincode")]
+pub(crate) fn encode<T: Serialize>(value: &T) -> Vec<u8> {
+    options().serialize(value).expect("Error encoding the value.")
+}
+
+/// Decodes a value encoded with `encode()`, which must take all the bytes.
+#[cfg(feature = "bincode")]
+pub(crate) fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, DecodeError> {
+    let mut rest = bytes;
+
+    // the lengths read aren't trusted to allocate more than the bytes
+    let value = options()
+        .with_limit(bytes.len() as u64)
+        .deserialize_from(&mut rest)
+        .map_err(|e| match *e {
+            ErrorKind::Io(ref e) if e.kind() == std::io::ErrorKind::UnexpectedEof => DecodeError::UnexpectedEnd,
+            ErrorKind::SizeLimit => DecodeError::UnexpectedEnd,
+            _ => DecodeError::Invalid,
+        })?;
+
+    if !rest.is_empty() {
+        return Err(DecodeError::TrailingBytes);
+    }
+
+    Ok(value)
+}
+
+/// A value which can be written with the `Binary` encoding, e.g. in the bytes signed by the sender of a transaction.
+pub(crate) trait Binary {
+    /// Appends the encoding of the value to the output.
+    fn encode(&self, output: &mut Vec<u8>);
+}
+
+macro_rules! binary_int {
+    ($($int:ty),*) => {
+        $(
+            impl Binary for $int {
+                fn encode(&self, output: &mut Vec<u8>) {
+                    output.extend_from_slice(&self.to_le_bytes());
+                }
+            }
+        )*
+    };
+}
+
+binary_int!(u8, u16, u32, u64, u128, i64);
+
+// the sizes are always written as 64-bit integers, like in `bincode`
+impl Binary for usize {
+    fn encode(&self, output: &mut Vec<u8>) {
+        (*self as u64).encode(output);
+    }
+}
+
+impl<const N: usize> Binary for [u8; N] {
+    fn encode(&self, output: &mut Vec<u8>) {
+        output.extend_from_slice(self);
+    }
+}
+
+impl Binary for String {
+    fn encode(&self, output: &mut Vec<u8>) {
+        self.len().encode(output);
+        output.extend_from_slice(self.as_bytes());
+    }
+}
+
+impl<T: Binary> Binary for Option<T> {
+    fn encode(&self, output: &mut Vec<u8>) {
+        match self {
+            Some(value) => {
+                output.push(1);
+                value.encode(output);
+            },
+            None => output.push(0),
+        }
+    }
+}
+
+impl<T: Binary> Binary for Vec<T> {
+    fn encode(&self, output: &mut Vec<u8>) {
+        self.len().encode(output);
+
+        for value in self {
+            value.encode(output);
+        }
+    }
+}
+
+impl<A: Binary, B: Binary> Binary for (A, B) {
+    fn encode(&self, output: &mut Vec<u8>) {
+        self.0.encode(output);
+        self.1.encode(output);
+    }
+}
+
+// the times are written as the seconds and the nanoseconds since the Unix epoch
+impl Binary for DateTime<Utc> {
+    fn encode(&self, output: &mut Vec<u8>) {
+        self.timestamp().encode(output);
+        self.timestamp_subsec_nanos().encode(output);
+    }
+}
+
+/// An enum to handle errors generated while decoding the binary encoding of a `Block`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    UnexpectedEnd,
+    TrailingBytes,
+    Invalid,
+    UnsupportedVersion(u16),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::UnexpectedEnd => write!(f, "The bytes ended before the block."),
+            Self::TrailingBytes => write!(f, "There are bytes after the block."),
+            Self::Invalid => write!(f, "Invalid value."),
+            Self::UnsupportedVersion(version) => write!(f, "Unsupported block version {}.", version),
+        }
+    }
+}
+
+impl error::Error for DecodeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encoded<T: Binary>(value: T) -> Vec<u8> {
+        let mut output = Vec::new();
+        value.encode(&mut output);
+
+        output
+    }
+
+    #[test]
+    fn values_are_encoded_like_bincode() {
+        // the bytes written by `bincode::serialize()`, with the default fixed-width little-endian integers
+        assert_eq!(encoded(0x0102u16), [2, 1]);
+        assert_eq!(encoded(-2i64), [0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+        assert_eq!(encoded(1u128), [&[1][..], &[0; 15]].concat());
+        assert_eq!(encoded(3usize), [3, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(encoded([9u8; 3]), [9, 9, 9]);
+        assert_eq!(encoded(String::from("ab")), [2, 0, 0, 0, 0, 0, 0, 0, b'a', b'b']);
+        assert_eq!(encoded(Some(7u8)), [1, 7]);
+        assert_eq!(encoded(None::<u8>), [0]);
+        assert_eq!(encoded(vec![1u32, 2]), [2, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0]);
+        assert_eq!(encoded((4u8, 5u16)), [4, 5, 0]);
+
+        #[cfg(feature = "bincode")]
+        {
+            let value = (vec![(String::from("ab"), Some([9u8; 3]))], (-2i64, (1u128, 3usize)));
+
+            assert_eq!(encoded(value.clone()), encode(&value));
+        }
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn invalid_bytes_are_errors() {
+        let bytes = encode(&(vec![String::from("ab")], Some(0x0102u16)));
+
+        assert_eq!(decode(&bytes), Ok((vec![String::from("ab")], Some(0x0102u16))));
+        assert_eq!(decode::<Option<u8>>(&[2]), Err(DecodeError::Invalid));
+        assert_eq!(decode::<u16>(&[1, 2, 3]), Err(DecodeError::TrailingBytes));
+
+        // a length larger than the bytes isn't allocated
+        assert_eq!(decode::<String>(&[0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0, b'a']), Err(DecodeError::UnexpectedEnd));
+    }
+}
@@ -1,127 +1,2624 @@
-use crate::transaction::Transaction;
-use std::convert::TryInto;
-use sha2::{Sha512, Digest};
-use chrono::{DateTime, Utc};
-
-/// A structure to handle blocks for the blockchain of the currency.
-/// 
-/// Every block of the chain contains:
-/// - the index (the #0 block is the genesis block)
-/// - the SHA-512 hash of the previous block
-/// - the transactions of the block
-/// (the number of transactions per block is set while generating the blockchain)
-/// - the nonce, which is used for the proof of work
-/// - the `DateTime<Utc>` time when the block was generated
-/// - the hash of the block generated
-#[derive(Debug, Clone, PartialEq)]
-pub struct Block {
-    index: usize,
-    prev_hash: [u8; 64],
-    transactions: Vec<Transaction>,
-    nonce: u128,
-    time: DateTime<Utc>,
-    hash: [u8; 64],
-}
-
-impl Block {
-    /// Generates a new `Block`.
-    /// 
-    /// # Example
-    /// ```
-    /// # use blockchain::{block::Block, transaction::Transaction, account::Account};
-    /// let mut glenn = Account::new("Glenn", "Paris", "glenn_paris_PassWord88");
-    /// let william = Account::new("William", "Brown", "WilliamTheConqueror22");
-    /// glenn.add_money(20.0);
-    /// 
-    /// let transaction = Transaction::new(glenn, william, 20.0, "glenn_paris_PassWord88");
-    ///
-    /// let genesis = Block::default(); // that's the actual genesis block
-    ///
-    /// let new_block = Block::new(1, genesis.hash(), vec![transaction]);
-    /// 
-    /// assert_eq!(new_block.index(), 1);
-    /// ```
-    pub fn new(index: usize, prev_hash: [u8; 64], transactions: Vec<Transaction>) -> Self {
-        let mut block = Self {
-            index,
-            prev_hash,
-            transactions,
-            nonce: 0,
-            time: Utc::now(),
-            hash: [0; 64],
-        };
-
-        block.calculate_hash();
-
-        block
-    }
-
-    /// This method returns the hash of the block, since the `hash` field isn't `pub`.
-    /// 
-    /// # Example
-    /// ```
-    /// # use blockchain::block::Block;
-    /// let genesis_block = Block::default();
-    /// 
-    /// assert_eq!(genesis_block.hash().len(), 64); // the hash changes everytime because of the time
-    /// ```
-    pub fn hash(&self) -> [u8; 64] {
-        self.hash
-    }
-
-    /// This method returns the index of the block, since the `index` field isn't `pub`.
-    /// 
-    /// # Example
-    /// ```
-    /// # use blockchain::block::Block;
-    /// let genesis_block = Block::default();
-    /// 
-    /// assert_eq!(genesis_block.index(), 0);
-    /// ```
-    #[allow(dead_code)]
-    pub fn index(&self) -> usize {
-        self.index
-    }
-
-    /// This method is called when a new block is generated,
-    /// and it is used to calculate the SHA-512 hash of the new block.
-    /// 
-    /// The hash is calculated by using:
-    /// - the index of the block
-    /// - the previous hash
-    /// - the `Transaction`s hashes
-    /// - the `DateTime<Utc>` time when the block was generated
-    /// - the nonce used for the proof of work
-    /// 
-    /// The proof of work is checked in the condition of the while loop.
-    fn calculate_hash(&mut self) {
-        while self.hash[0..2] != [69, 69] {
-            let mut hasher = Sha512::new();
-
-            let transactions_hashes = self.transactions.iter().fold(String::new(), |acc, t| format!("{:?}{:?}", acc, t.hash()));
-            
-            let digest = format!("{}{:?}{}{:?}{}",
-                self.index,
-                self.prev_hash,
-                transactions_hashes,
-                self.time,
-                self.nonce
-            );
-    
-            hasher.update(digest.as_bytes());
-            
-            self.hash = hasher
-                .finalize()[..]
-                .try_into()
-                .expect("Error generating the SHA-512 hash of the block.");
-
-            self.nonce += 1;
-        }
-    }
-}
-
-impl Default for Block {
-    fn default() -> Self {
-        Block::new(0, [0; 64], Vec::new())
-    }
-}
+use crate::{
+    transaction::{self, Transaction, ValidationError},
+    amount::Amount,
+    hasher::{Hasher, Sha512Hasher, ScryptHasher, ProofOfWork},
+    header::BlockHeader,
+    clock::Clock,
+};
+#[cfg(feature = "serde")]
+use crate::binary::DecodeError;
+#[cfg(feature = "bincode")]
+use crate::binary;
+use std::{cmp, fmt, error, thread};
+use std::collections::HashSet;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Utc};
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+/// The difficulty used by `Block::new`, expressed as the number of leading bits of the hash
+/// of the block that must match the target in order to satisfy the proof of work.
+pub const DEFAULT_DIFFICULTY: usize = 16;
+
+/// The target byte used by `Block::new`: since it's `0`, the hash must have `difficulty` leading zero bits.
+pub const DEFAULT_TARGET: u8 = 0;
+
+/// The version of the format of the blocks generated by this crate, returned by `Block::version()`:
+/// the blocks with a newer version, or with the version `0`, are rejected when they're deserialized or decoded.
+pub const BLOCK_VERSION: u16 = 3;
+
+/// The first version of the format of the blocks whose transactions must be in the canonical order,
+/// see `Block::is_canonically_ordered()`: the blocks of the first version were sorted by time, and they're still accepted.
+pub const CANONICAL_ORDER_VERSION: u16 = 2;
+
+/// The first version of the format of the blocks whose difficulty and target byte are hashed with the header,
+/// see `Block::header_bytes()`: the older blocks can change them after they're mined, so the chains don't accept them as new blocks.
+pub const PROOF_OF_WORK_VERSION: u16 = 3;
+
+/// The number of nonces tried by `Block::mine_with_progress()` between two calls of the progress callback.
+pub const PROGRESS_INTERVAL: u128 = 100_000;
+
+/// The Merkle root of a block without transactions, e.g. a block mined while there are no pending transactions.
+pub const EMPTY_MERKLE_ROOT: [u8; 64] = [0; 64];
+
+/// The size of the canonical encoding of a block header without extra data, returned by `header_bytes()`.
+const HEADER_SIZE: usize = 64 + 64 + 8 + 16 + 16;
+
+/// The tag coming before the extra data in the encoding of a header, followed by its length, see `header_bytes()`.
+const EXTRA_DATA_TAG: u8 = 1;
+
+/// The tag coming before the chain identifier in the encoding of a header, see `header_bytes()`.
+const CHAIN_ID_TAG: u8 = 2;
+
+/// The tag coming before the version in the encoding of a header, see `header_bytes()`.
+const VERSION_TAG: u8 = 3;
+
+/// The tag coming before the public key of the producer in the encoding of a header, see `header_bytes()`.
+const PRODUCER_TAG: u8 = 4;
+
+/// The tag coming before the difficulty and the target byte in the encoding of a header, see `header_bytes()`.
+const PROOF_OF_WORK_TAG: u8 = 5;
+
+/// A structure to handle blocks for the blockchain of the currency.
+/// 
+/// Every block of the chain contains:
+/// - the index (the #0 block is the genesis block)
+/// - the SHA-512 hash of the previous block
+/// - the transactions of the block
+///   (the number of transactions per block is set while generating the blockchain)
+/// - the nonce, which is used for the proof of work
+/// - the `DateTime<Utc>` time when the block was generated
+/// - the difficulty of the proof of work, i.e. the number of leading bits of the hash matching the target
+/// - the target byte of the proof of work: the first `difficulty` bits of the hash must match
+///   the first `difficulty` bits of a sequence of target bytes
+/// - the hash of the block generated
+/// - some arbitrary extra data, e.g. a message in the genesis block, which is empty by default
+/// - the identifier of the chain the block is mined for, which is `0` by default
+/// - the version of the format of the block, which is `BLOCK_VERSION`
+/// - the public key of the account which produced the block, if any, see `producer()`
+/// 
+/// The transactions of a block can be dropped with `prune()`, keeping only their Merkle root,
+/// so that the hash of the block can still be verified.
+/// 
+/// The blocks are hashed with the `Hasher` given as the type parameter, which is SHA-512 by default;
+/// all the functions generating a `Block` use SHA-512, except for `with_hasher()`.
+/// 
+/// With the `serde` feature enabled, a `Block` can be serialized and deserialized,
+/// and the hashes are encoded as hex strings; the deserialized block keeps the exact same hash.
+/// 
+/// # Example
+/// ```
+/// # use blockchain::block::Block;
+/// # #[cfg(feature = "serde")] {
+/// let block = Block::new_with_difficulty(1, [0; 64], Vec::new(), 4);
+/// 
+/// let json = serde_json::to_string(&block).unwrap();
+/// let deserialized: Block = serde_json::from_str(&json).unwrap();
+/// 
+/// assert_eq!(deserialized, block);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = ""))]
+pub struct Block<H = Sha512Hasher> {
+    #[cfg_attr(feature = "serde", serde(default = "default_version", deserialize_with = "deserialize_version"))]
+    version: u16,
+    index: usize,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))]
+    prev_hash: [u8; 64],
+    transactions: Vec<Transaction>,
+    nonce: u128,
+    time: DateTime<Utc>,
+    difficulty: usize,
+    target: u8,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))]
+    hash: [u8; 64],
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_hex::option"))]
+    pruned_root: Option<[u8; 64]>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    extra_data: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    chain_id: u32,
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_hex::key::option"))]
+    producer: Option<[u8; 32]>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    hasher: PhantomData<H>,
+}
+
+impl Block {
+    /// Generates a new `Block`.
+    /// 
+    /// # Panics
+    /// The function panics if no nonce up to `u128::MAX` satisfies the difficulty, see `new_with_difficulty()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::{block::Block, transaction::Transaction, account::Account};
+    /// let mut glenn = Account::new("Glenn", "Paris", "glenn_paris_PassWord88").unwrap();
+    /// let william = Account::new("William", "Brown", "WilliamTheConqueror22").unwrap();
+    /// glenn.add_money(20);
+    /// 
+    /// let transaction = Transaction::new(glenn, william, 20, "glenn_paris_PassWord88").unwrap();
+    /// 
+    /// let genesis = Block::default(); // that's the actual genesis block
+    /// 
+    /// let new_block = Block::new(1, genesis.hash(), vec![transaction]);
+    /// 
+    /// assert_eq!(new_block.index(), 1);
+    /// ```
+    pub fn new(index: usize, prev_hash: [u8; 64], transactions: Vec<Transaction>) -> Self {
+        Block::new_with_difficulty(index, prev_hash, transactions, DEFAULT_DIFFICULTY)
+    }
+
+    /// Generates a new `Block` like `new()`, checking first that it has at most `max_tx` transactions,
+    /// like the number of transactions per block of a `BlockChain`.
+    /// 
+    /// The function returns a `Result<Block, BlockError>`: if there are more than `max_tx` transactions,
+    /// a `BlockError::TooManyTransactions` error is returned, and the block isn't mined.
+    /// 
+    /// # Panics
+    /// The function panics if no nonce up to `u128::MAX` satisfies the difficulty, see `new_with_difficulty()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::{block::{Block, BlockError}, transaction::Transaction, account::Account};
+    /// let miner = Account::new("Iona", "Marsh", "i0n4_m4rsh").unwrap();
+    /// let transactions = vec![Transaction::coinbase(miner.clone(), 5), Transaction::coinbase(miner, 5)];
+    /// 
+    /// assert!(Block::new_checked(1, [0; 64], transactions.clone(), 2).is_ok());
+    /// assert_eq!(Block::new_checked(1, [0; 64], transactions, 1), Err(BlockError::TooManyTransactions));
+    /// ```
+    pub fn new_checked(index: usize, prev_hash: [u8; 64], transactions: Vec<Transaction>, max_tx: usize) -> Result<Self, BlockError> {
+        if transactions.len() > max_tx {
+            return Err(BlockError::TooManyTransactions);
+        }
+
+        Ok(Block::new(index, prev_hash, transactions))
+    }
+
+    /// Generates a new `Block`, mining it with the given difficulty,
+    /// which is the number of leading zero bits its hash must have.
+    /// 
+    /// # Panics
+    /// The function panics if no nonce up to `u128::MAX` satisfies the difficulty, which doesn't happen in practice:
+    /// a nonce is found long before for any difficulty up to 512 bits, and above it the mining never ends.
+    /// `try_new()` bounds the nonces, returning a `MiningError` instead.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// let genesis = Block::default();
+    /// 
+    /// let new_block = Block::new_with_difficulty(1, genesis.hash(), Vec::new(), 4); // a low difficulty is mined quickly
+    /// 
+    /// assert_eq!(new_block.difficulty(), 4);
+    /// assert_eq!(new_block.hash()[0] >> 4, 0); // the first 4 bits of the hash are zeros
+    /// ```
+    pub fn new_with_difficulty(index: usize, prev_hash: [u8; 64], transactions: Vec<Transaction>, difficulty: usize) -> Self {
+        Block::try_new(index, prev_hash, transactions, difficulty, u128::MAX).expect("Error mining the block.")
+    }
+
+    /// Generates a new `Block` and mines it with the given difficulty, like `new_with_difficulty()`:
+    /// the name makes it clear that the proof of work is done, unlike `assemble_unmined()`.
+    /// 
+    /// # Panics
+    /// The function panics if no nonce up to `u128::MAX` satisfies the difficulty, see `new_with_difficulty()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// let block = Block::mine(1, [0; 64], Vec::new(), 4);
+    /// 
+    /// assert!(block.verify());
+    /// ```
+    pub fn mine(index: usize, prev_hash: [u8; 64], transactions: Vec<Transaction>, difficulty: usize) -> Self {
+        Block::new_with_difficulty(index, prev_hash, transactions, difficulty)
+    }
+
+    /// Generates a new `Block` with the given fields, without mining it: the nonce is `0` and the hash is zeroed,
+    /// so the block doesn't verify until it's mined with `mine_in_place()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// use chrono::Utc;
+    /// 
+    /// let mut block = Block::assemble_unmined(1, [0; 64], Vec::new(), 4, Utc::now());
+    /// 
+    /// assert_eq!(block.hash(), [0; 64]);
+    /// assert!(!block.verify());
+    /// 
+    /// block.mine_in_place();
+    /// 
+    /// assert!(block.verify());
+    /// ```
+    pub fn assemble_unmined(index: usize, prev_hash: [u8; 64], transactions: Vec<Transaction>, difficulty: usize, time: DateTime<Utc>) -> Self {
+        Block::unmined(index, prev_hash, transactions, difficulty, time)
+    }
+
+    /// Generates a new `Block`, mining it with the given difficulty and target byte:
+    /// the first `difficulty` bits of the hash must match the first `difficulty` bits
+    /// of a sequence of `target` bytes.
+    /// 
+    /// A `BlockChain` only accepts the blocks mined with `DEFAULT_TARGET`, since the target changes which hashes
+    /// satisfy the proof of work; the other targets are for blocks used outside of a chain.
+    /// 
+    /// # Panics
+    /// The function panics if no nonce up to `u128::MAX` satisfies the difficulty, see `new_with_difficulty()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// let block = Block::new_with_target(1, [0; 64], Vec::new(), 8, 69);
+    /// 
+    /// assert_eq!(block.target(), 69);
+    /// assert_eq!(block.hash()[0], 69); // the first 8 bits of the hash are the target byte
+    /// ```
+    pub fn new_with_target(index: usize, prev_hash: [u8; 64], transactions: Vec<Transaction>, difficulty: usize, target: u8) -> Self {
+        let mut block = Block::unmined(index, prev_hash, transactions, difficulty, Utc::now());
+        block.target = target;
+
+        block.calculate_hash(u128::MAX, 1, None).expect("Error mining the block.");
+
+        block
+    }
+
+    /// Generates a new `Block`, mining it with the given difficulty but trying
+    /// only the nonces from `0` up to `max_nonce` (included).
+    /// 
+    /// The function returns a `Result<Block, MiningError>`, because if no nonce in the range
+    /// satisfies the proof of work, a `MiningError::Exhausted` error is returned.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::{Block, MiningError};
+    /// let block = Block::try_new(1, [0; 64], Vec::new(), 4, u128::MAX).unwrap();
+    /// 
+    /// assert!(block.verify());
+    /// 
+    /// let impossible_block = Block::try_new(1, [0; 64], Vec::new(), 512, 100); // no hash has 512 leading zero bits
+    /// 
+    /// assert_eq!(impossible_block, Err(MiningError::Exhausted));
+    /// ```
+    pub fn try_new(index: usize, prev_hash: [u8; 64], transactions: Vec<Transaction>, difficulty: usize, max_nonce: u128) -> Result<Self, MiningError> {
+        let mut block = Block::unmined(index, prev_hash, transactions, difficulty, Utc::now());
+
+        block.calculate_hash(max_nonce, 1, None)?;
+
+        Ok(block)
+    }
+
+    /// Generates a new `Block`, mining it with the given difficulty starting from the nonce `start_nonce`
+    /// instead of `0`, so that the workers mining the same block can be given different ranges of nonces.
+    /// 
+    /// The function returns a `Result<Block, MiningError>`, because if no nonce from `start_nonce`
+    /// up to `u128::MAX` satisfies the proof of work, a `MiningError::Exhausted` error is returned.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::{Block, MiningError};
+    /// let block = Block::mine_from_nonce(1, [0; 64], Vec::new(), 4, 1_000_000).unwrap();
+    /// 
+    /// assert!(block.nonce() >= 1_000_000);
+    /// assert!(block.verify());
+    /// 
+    /// let impossible_block = Block::mine_from_nonce(1, [0; 64], Vec::new(), 512, u128::MAX - 100);
+    /// 
+    /// assert_eq!(impossible_block, Err(MiningError::Exhausted));
+    /// ```
+    pub fn mine_from_nonce(index: usize, prev_hash: [u8; 64], transactions: Vec<Transaction>, difficulty: usize, start_nonce: u128) -> Result<Self, MiningError> {
+        let mut block = Block::unmined(index, prev_hash, transactions, difficulty, Utc::now());
+
+        block.nonce = start_nonce;
+        block.calculate_hash(u128::MAX, 1, None)?;
+
+        Ok(block)
+    }
+
+    /// Generates a new `Block`, mining it with the given difficulty until a valid hash is found
+    /// or until the `stop` flag is set to `true`, for example by another thread.
+    /// 
+    /// The function returns `None` if the mining was cancelled.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
+    /// use std::{thread, time::Duration};
+    /// 
+    /// let stop = Arc::new(AtomicBool::new(false));
+    /// let flag = Arc::clone(&stop);
+    /// 
+    /// let handle = thread::spawn(move || {
+    ///     thread::sleep(Duration::from_millis(10));
+    ///     flag.store(true, Ordering::Relaxed);
+    /// });
+    /// 
+    /// let block = Block::new_cancellable(1, [0; 64], Vec::new(), 512, &stop); // this block would never be mined
+    /// 
+    /// handle.join().unwrap();
+    /// 
+    /// assert_eq!(block, None);
+    /// ```
+    pub fn new_cancellable(index: usize, prev_hash: [u8; 64], transactions: Vec<Transaction>, difficulty: usize, stop: &AtomicBool) -> Option<Self> {
+        let mut block = Block::unmined(index, prev_hash, transactions, difficulty, Utc::now());
+
+        block.calculate_hash(u128::MAX, 1, Some(stop)).ok()?;
+
+        Some(block)
+    }
+
+    /// Generates a new `Block`, mining it with the given difficulty like `new_with_difficulty()`,
+    /// and calls `on_progress` with the number of nonces tried so far every `PROGRESS_INTERVAL` nonces,
+    /// e.g. to show the progress of a long mining; the callback isn't called anymore once the block is mined.
+    /// 
+    /// # Panics
+    /// The function panics if no nonce up to `u128::MAX` satisfies the difficulty, see `new_with_difficulty()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::{Block, PROGRESS_INTERVAL};
+    /// let mut reports = Vec::new();
+    /// 
+    /// let block = Block::mine_with_progress(1, [0; 64], Vec::new(), 4, |attempts| reports.push(attempts));
+    /// 
+    /// assert!(block.verify());
+    /// assert!(reports.iter().all(|attempts| *attempts <= block.nonce()));
+    /// assert!(reports.iter().all(|attempts| attempts % PROGRESS_INTERVAL == 0));
+    /// ```
+    pub fn mine_with_progress(
+        index: usize,
+        prev_hash: [u8; 64],
+        transactions: Vec<Transaction>,
+        difficulty: usize,
+        mut on_progress: impl FnMut(u128),
+    ) -> Self {
+        let mut block = Block::unmined(index, prev_hash, transactions, difficulty, Utc::now());
+        let target = block.target;
+
+        block.search_nonce_with_progress(u128::MAX, 1, None, |hash| satisfies(hash, difficulty, target), PROGRESS_INTERVAL, &mut on_progress)
+            .expect("Error mining the block.");
+
+        block
+    }
+
+    /// Generates a new `Block`, mining it with the given difficulty using `threads` worker threads.
+    /// 
+    /// The nonces are partitioned across the threads, so that the thread `k` tries the nonces
+    /// `k`, `k + threads`, `k + 2 * threads` and so on; the first thread that finds a valid hash
+    /// stops the other ones.
+    /// 
+    /// # Panics
+    /// The function panics if no nonce up to `u128::MAX` satisfies the difficulty, see `new_with_difficulty()`,
+    /// or if a mining thread panics.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// let block = Block::new_parallel(1, [0; 64], Vec::new(), 8, 4);
+    /// 
+    /// assert!(block.verify()); // the block is valid whichever thread found it
+    /// ```
+    pub fn new_parallel(index: usize, prev_hash: [u8; 64], transactions: Vec<Transaction>, difficulty: usize, threads: usize) -> Self {
+        let threads = threads.max(1) as u128;
+
+        let block = Block::unmined(index, prev_hash, transactions, difficulty, Utc::now());
+
+        let stop = AtomicBool::new(false);
+
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads).map(|k| {
+                let mut block = block.clone();
+                let stop = &stop;
+
+                block.nonce = k;
+
+                scope.spawn(move || {
+                    let mined = block.calculate_hash(u128::MAX, threads, Some(stop));
+
+                    if mined.is_ok() {
+                        stop.store(true, Ordering::Relaxed);
+                    }
+
+                    mined.map(|_| block)
+                })
+            }).collect();
+
+            workers
+                .into_iter()
+                .filter_map(|worker| worker.join().expect("Error joining the mining thread.").ok())
+                .next()
+                .expect("Error mining the block.")
+        })
+    }
+
+    /// Generates a new `Block`, mining it with the given difficulty, and returns it
+    /// with the `MiningStats` of the mining, i.e. how many hashes were tried and how long it took.
+    /// 
+    /// The attempts are the final nonce of the block: since the nonces are tried starting from `0`,
+    /// they're the number of nonces rejected before the nonce of the block.
+    /// 
+    /// # Panics
+    /// The function panics if no nonce up to `u128::MAX` satisfies the difficulty, see `new_with_difficulty()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// let (block, stats) = Block::mine_with_stats(1, [0; 64], Vec::new(), 8);
+    /// 
+    /// assert!(block.verify());
+    /// assert_eq!(stats.attempts, block.nonce());
+    /// ```
+    pub fn mine_with_stats(index: usize, prev_hash: [u8; 64], transactions: Vec<Transaction>, difficulty: usize) -> (Self, MiningStats) {
+        let start = Instant::now();
+
+        let block = Block::new_with_difficulty(index, prev_hash, transactions, difficulty);
+
+        let stats = MiningStats {
+            attempts: block.nonce,
+            elapsed: start.elapsed(),
+        };
+
+        (block, stats)
+    }
+
+    /// Generates the genesis `Block` with a fixed `DateTime<Utc>` time,
+    /// so that everyone generating it with the same time gets the same block, and the same hash.
+    /// 
+    /// # Panics
+    /// The function panics if no nonce up to `u128::MAX` satisfies the difficulty, see `new_with_difficulty()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// use chrono::{TimeZone, Utc};
+    /// 
+    /// let time = Utc.with_ymd_and_hms(2021, 3, 14, 0, 0, 0).unwrap();
+    /// 
+    /// assert_eq!(Block::genesis(time), Block::genesis(time));
+    /// ```
+    pub fn genesis(time: DateTime<Utc>) -> Self {
+        Block::mined_at(0, [0; 64], Vec::new(), DEFAULT_DIFFICULTY, time)
+    }
+
+    /// Generates the genesis `Block` with a fixed `DateTime<Utc>` time, like `genesis()`,
+    /// stamped with the given extra data, e.g. a headline or the identifier of the chain.
+    /// 
+    /// # Panics
+    /// The function panics if no nonce up to `u128::MAX` satisfies the difficulty, see `new_with_difficulty()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// use chrono::{TimeZone, Utc};
+    /// 
+    /// let time = Utc.with_ymd_and_hms(2021, 3, 14, 0, 0, 0).unwrap();
+    /// let genesis = Block::genesis_with_extra_data(time, b"The Times 14/Mar/2021".to_vec());
+    /// 
+    /// assert_eq!(genesis.extra_data(), b"The Times 14/Mar/2021");
+    /// assert!(genesis.verify());
+    /// ```
+    pub fn genesis_with_extra_data(time: DateTime<Utc>, extra_data: Vec<u8>) -> Self {
+        let mut block = Block::unmined(0, [0; 64], Vec::new(), DEFAULT_DIFFICULTY, time);
+        block.extra_data = extra_data;
+
+        block.calculate_hash(u128::MAX, 1, None).expect("Error mining the block.");
+
+        block
+    }
+
+    /// Generates a new `Block` with the given extra data, mining it with the given difficulty;
+    /// the extra data is hashed with the header of the block.
+    /// 
+    /// # Panics
+    /// The function panics if no nonce up to `u128::MAX` satisfies the difficulty, see `new_with_difficulty()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// let block = Block::with_extra_data(1, [0; 64], Vec::new(), 4, b"chain #7".to_vec());
+    /// 
+    /// assert_eq!(block.extra_data(), b"chain #7");
+    /// assert!(block.verify());
+    /// ```
+    pub fn with_extra_data(index: usize, prev_hash: [u8; 64], transactions: Vec<Transaction>, difficulty: usize, extra_data: Vec<u8>) -> Self {
+        let mut block = Block::unmined(index, prev_hash, transactions, difficulty, Utc::now());
+        block.extra_data = extra_data;
+
+        block.calculate_hash(u128::MAX, 1, None).expect("Error mining the block.");
+
+        block
+    }
+
+    /// Generates a new `Block` for the chain with the given identifier, mining it with the given difficulty;
+    /// the identifier is hashed with the header of the block, so the block isn't valid on another chain.
+    /// 
+    /// # Panics
+    /// The function panics if no nonce up to `u128::MAX` satisfies the difficulty, see `new_with_difficulty()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// let block = Block::new_on_chain(1, [0; 64], Vec::new(), 4, 7);
+    /// 
+    /// assert_eq!(block.chain_id(), 7);
+    /// assert!(block.verify());
+    /// ```
+    pub fn new_on_chain(index: usize, prev_hash: [u8; 64], transactions: Vec<Transaction>, difficulty: usize, chain_id: u32) -> Self {
+        Block::mine_on_chain(index, prev_hash, transactions, difficulty, chain_id, Utc::now(), u128::MAX).expect("Error mining the block.")
+    }
+
+    /// Generates a new `Block` for the chain with the given identifier like `try_new()`, with the given time,
+    /// trying only the nonces from `0` up to `max_nonce` (included).
+    pub(crate) fn mine_on_chain(
+        index: usize,
+        prev_hash: [u8; 64],
+        transactions: Vec<Transaction>,
+        difficulty: usize,
+        chain_id: u32,
+        time: DateTime<Utc>,
+        max_nonce: u128,
+    ) -> Result<Self, MiningError> {
+        Block::unmined_on_chain(index, prev_hash, transactions, difficulty, chain_id, time).mine_up_to(max_nonce)
+    }
+
+    /// Generates a new `Block` for the chain with the given identifier, with the given time, which is not mined yet.
+    /// 
+    /// The transactions are sorted in the canonical order, see `sort_canonically()`.
+    pub(crate) fn unmined_on_chain(
+        index: usize,
+        prev_hash: [u8; 64],
+        transactions: Vec<Transaction>,
+        difficulty: usize,
+        chain_id: u32,
+        time: DateTime<Utc>,
+    ) -> Self {
+        let mut block = Block::unmined(index, prev_hash, transactions, difficulty, time);
+        block.chain_id = chain_id;
+
+        block
+    }
+
+    /// Sets the public key of the producer of a block which is not mined yet, see `producer()`.
+    pub(crate) fn with_producer(mut self, producer: [u8; 32]) -> Self {
+        self.producer = Some(producer);
+
+        self
+    }
+
+    /// Mines a block which is not mined yet like `mine_up_to()`, hashing it with the given proof of work function.
+    pub(crate) fn mine_up_to_with(self, max_nonce: u128, proof_of_work: ProofOfWork) -> Result<Self, MiningError> {
+        match proof_of_work {
+            ProofOfWork::Sha512 => self.mine_up_to(max_nonce),
+            ProofOfWork::Scrypt => self.into_hasher::<ScryptHasher>().mine_up_to(max_nonce).map(Block::into_hasher),
+        }
+    }
+
+    /// Generates a new `Block` like `new_with_difficulty()`, with the time returned by the given `Clock`
+    /// instead of the current time, e.g. to mine the blocks at known times in the tests.
+    /// 
+    /// # Panics
+    /// The function panics if no nonce up to `u128::MAX` satisfies the difficulty, see `new_with_difficulty()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// # use blockchain::clock::FixedClock;
+    /// use chrono::{TimeZone, Utc};
+    /// 
+    /// let time = Utc.with_ymd_and_hms(2021, 3, 14, 0, 0, 0).unwrap();
+    /// let block = Block::new_with_clock(1, [0; 64], Vec::new(), 4, &FixedClock::new(time));
+    /// 
+    /// assert_eq!(block.time(), time);
+    /// assert!(block.verify());
+    /// ```
+    pub fn new_with_clock(index: usize, prev_hash: [u8; 64], transactions: Vec<Transaction>, difficulty: usize, clock: &dyn Clock) -> Self {
+        Block::mined_at(index, prev_hash, transactions, difficulty, clock.now())
+    }
+
+    /// Generates a `Block` from its parts, e.g. a block received from a peer, without mining it:
+    /// the block keeps the given nonce, time and hash, so `verify()` must be used
+    /// to check that the parts are consistent. The target of the block is `DEFAULT_TARGET`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// let mined = Block::new_with_difficulty(1, [0; 64], Vec::new(), 4);
+    /// 
+    /// let block = Block::from_parts(1, [0; 64], Vec::new(), 4, mined.nonce(), mined.time(), mined.hash());
+    /// 
+    /// assert_eq!(block, mined);
+    /// assert!(block.verify());
+    /// 
+    /// let forged = Block::from_parts(2, [0; 64], Vec::new(), 4, mined.nonce(), mined.time(), mined.hash());
+    /// 
+    /// assert!(!forged.verify()); // the hash doesn't match the index
+    /// ```
+    pub fn from_parts(index: usize, prev_hash: [u8; 64], transactions: Vec<Transaction>, difficulty: usize, nonce: u128, time: DateTime<Utc>, hash: [u8; 64]) -> Self {
+        // the transactions are kept in the given order, since the hash was computed with it
+        Self {
+            transactions,
+            nonce,
+            hash,
+            ..Block::unmined(index, prev_hash, Vec::new(), difficulty, time)
+        }
+    }
+
+    /// This function returns the SHA-512 hash of a block whose header, without the nonce, is the given prefix,
+    /// returned by `header_digest_prefix()`, and whose nonce is the given nonce.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// use chrono::Utc;
+    /// 
+    /// let time = Utc::now();
+    /// let prefix = Block::from_parts(1, [0; 64], Vec::new(), 4, 0, time, [0; 64]).header_digest_prefix();
+    /// 
+    /// // the nonces are searched outside of the crate
+    /// let nonce = (0..).find(|&nonce| Block::meets_target(&Block::finalize_with_nonce(&prefix, nonce), 4)).unwrap();
+    /// let hash = Block::finalize_with_nonce(&prefix, nonce);
+    /// 
+    /// assert!(Block::from_parts(1, [0; 64], Vec::new(), 4, nonce, time, hash).verify());
+    /// ```
+    pub fn finalize_with_nonce(prefix: &[u8], nonce: u128) -> [u8; 64] {
+        let mut state = Sha512Hasher::new();
+
+        Sha512Hasher::update(&mut state, prefix);
+
+        digest_nonce::<Sha512Hasher>(&state, nonce)
+    }
+
+    /// Generates a pruned `Block` with the fields of a `BlockHeader`, so that the header can be verified like a block.
+    pub(crate) fn from_header(header: &BlockHeader) -> Self {
+        Self {
+            nonce: header.nonce,
+            target: header.target,
+            hash: header.hash,
+            pruned_root: Some(header.merkle_root),
+            extra_data: header.extra_data.clone(),
+            chain_id: header.chain_id,
+            version: header.version,
+            producer: header.producer,
+            ..Block::unmined(header.index, header.prev_hash, Vec::new(), header.difficulty, header.time)
+        }
+    }
+
+    /// This method returns the `BlockHeader` of the block, i.e. the block without its transactions,
+    /// which commits to the transactions through the Merkle root.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// let block = Block::new_with_difficulty(1, [0; 64], Vec::new(), 4);
+    /// 
+    /// assert_eq!(block.header().merkle_root, block.merkle_root());
+    /// ```
+    pub fn header(&self) -> BlockHeader {
+        BlockHeader {
+            index: self.index,
+            prev_hash: self.prev_hash,
+            merkle_root: self.merkle_root(),
+            time: self.time,
+            difficulty: self.difficulty,
+            target: self.target,
+            nonce: self.nonce,
+            hash: self.hash,
+            extra_data: self.extra_data.clone(),
+            chain_id: self.chain_id,
+            version: self.version,
+            producer: self.producer,
+        }
+    }
+
+    /// This function checks if a Merkle proof, generated by `merkle_proof()`, proves that
+    /// the transaction with the given hash and index is included in the tree with the given root.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::{block::Block, transaction::Transaction, account::Account};
+    /// let mut ross = Account::new("Ross", "Ng", "R0ss_N9_pass").unwrap();
+    /// let ada = Account::new("Ada", "Byron", "countess_of_lovelace").unwrap();
+    /// ross.add_money(30);
+    /// 
+    /// let transactions = vec![
+    ///     Transaction::new(ross.clone(), ada.clone(), 1, "R0ss_N9_pass").unwrap(),
+    ///     Transaction::new(ross, ada, 2, "R0ss_N9_pass").unwrap(),
+    /// ];
+    /// 
+    /// let block = Block::new_with_difficulty(1, [0; 64], transactions, 4);
+    /// 
+    /// let proof = block.merkle_proof(0);
+    /// 
+    /// assert!(!Block::verify_merkle_proof(block.transactions()[0].hash(), 1, &proof, block.merkle_root())); // wrong index
+    /// ```
+    pub fn verify_merkle_proof(tx_hash: [u8; 64], tx_index: usize, proof: &[[u8; 64]], root: [u8; 64]) -> bool {
+        let mut hash = tx_hash;
+        let mut index = tx_index;
+
+        for sibling in proof {
+            hash = if index & 1 == 0 {
+                merkle_hash::<Sha512Hasher>(&hash, sibling)
+            } else {
+                merkle_hash::<Sha512Hasher>(sibling, &hash)
+            };
+
+            index /= 2;
+        }
+
+        hash == root
+    }
+
+    /// Checks if a hash meets the given difficulty, i.e. if it has at least `difficulty` leading zero bits;
+    /// this is the rule of the proof of work of the blocks with the `DEFAULT_TARGET`.
+    /// 
+    /// The difficulty is measured in bits, which is finer-grained than measuring it in leading zero
+    /// hex digits (nibbles): a difficulty of `n` leading zero nibbles is a difficulty of `4 * n` bits.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// let mut hash = [0xff; 64];
+    /// 
+    /// assert!(Block::meets_target(&hash, 0)); // every hash meets a difficulty of 0
+    /// assert!(!Block::meets_target(&hash, 1));
+    /// 
+    /// hash[0] = 0x0f; // 1 leading zero nibble
+    /// 
+    /// assert!(Block::meets_target(&hash, 4));
+    /// assert!(!Block::meets_target(&hash, 5));
+    /// 
+    /// hash[0] = 0x00;
+    /// hash[1] = 0x3f; // 2 leading zero nibbles and 2 more zero bits
+    /// 
+    /// assert!(Block::meets_target(&hash, 10));
+    /// assert!(!Block::meets_target(&hash, 11));
+    /// ```
+    pub fn meets_target(hash: &[u8; 64], difficulty: usize) -> bool {
+        matching_bits(hash, 0) >= difficulty
+    }
+
+    /// Checks if a hash meets the given 64-byte target, i.e. if the hash, read as a big-endian integer,
+    /// is less than or equal to the target, like in Bitcoin; a target with `n` leading zero bits
+    /// followed by ones is the same as a difficulty of `n` bits, see `meets_target()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// let mut target = [0xff; 64];
+    /// target[0] = 0x12;
+    /// 
+    /// let mut hash = [0; 64];
+    /// hash[0] = 0x12;
+    /// 
+    /// assert!(Block::meets_target_threshold(&hash, &target));
+    /// 
+    /// hash[0] = 0x13;
+    /// 
+    /// assert!(!Block::meets_target_threshold(&hash, &target));
+    /// ```
+    pub fn meets_target_threshold(hash: &[u8; 64], target: &[u8; 64]) -> bool {
+        // the arrays are compared byte by byte from the first one, like big-endian integers
+        hash <= target
+    }
+
+    /// Generates a new `Block`, mining it until its hash meets the given 64-byte target, see `meets_target_threshold()`.
+    /// 
+    /// The difficulty of the block is the number of leading zero bits of the target, which every hash
+    /// meeting the target has, so the block verifies like the other blocks; `verify()` can't check
+    /// the rest of the target, which must be checked with `meets_target_threshold()`.
+    /// 
+    /// # Panics
+    /// The function panics if no nonce up to `u128::MAX` satisfies the target, see `new_with_difficulty()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// let mut target = [0xff; 64];
+    /// target[0] = 0x0a; // between 4 and 5 leading zero bits
+    /// 
+    /// let block = Block::new_with_threshold(1, [0; 64], Vec::new(), target);
+    /// 
+    /// assert!(Block::meets_target_threshold(block.hash_ref(), &target));
+    /// assert_eq!(block.difficulty(), 4);
+    /// assert!(block.verify());
+    /// ```
+    pub fn new_with_threshold(index: usize, prev_hash: [u8; 64], transactions: Vec<Transaction>, target: [u8; 64]) -> Self {
+        let mut block = Block::unmined(index, prev_hash, transactions, matching_bits(&target, 0), Utc::now());
+
+        block.search_nonce(u128::MAX, 1, None, |hash| Block::meets_target_threshold(hash, &target))
+            .expect("Error mining the block.");
+
+        block
+    }
+
+    /// This method checks the block like `verify()`, hashing it with the given proof of work function instead of SHA-512:
+    /// the blocks of a chain built with `BlockChainBuilder::proof_of_work()` are `Block`s whatever the function they're mined with,
+    /// so they're verified with the function of the chain, see `BlockChain::proof_of_work()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// # use blockchain::hasher::{ProofOfWork, ScryptHasher};
+    /// let block = Block::new_with_difficulty(1, [0; 64], Vec::new(), 4);
+    /// 
+    /// assert!(block.verify_with(ProofOfWork::Sha512));
+    /// assert!(!block.verify_with(ProofOfWork::Scrypt));
+    /// ```
+    pub fn verify_with(&self, proof_of_work: ProofOfWork) -> bool {
+        self.verify_hashes_with(proof_of_work) && self.verify_signatures_batch()
+    }
+
+    /// Checks the block like `verify_with()`, but without the signatures of the transactions.
+    pub(crate) fn verify_hashes_with(&self, proof_of_work: ProofOfWork) -> bool {
+        match proof_of_work {
+            ProofOfWork::Sha512 => self.verify_hashes(),
+            ProofOfWork::Scrypt => self.clone().into_hasher::<ScryptHasher>().verify_hashes(),
+        }
+    }
+}
+
+impl<H: Hasher> Block<H> {
+    /// Generates a new `Block`, hashed with the `Hasher` `H` instead of SHA-512,
+    /// mining it with the given difficulty.
+    /// 
+    /// # Panics
+    /// The function panics if no nonce up to `u128::MAX` satisfies the difficulty, see `new_with_difficulty()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// # use blockchain::hasher::Sha512Hasher;
+    /// let block = Block::<Sha512Hasher>::with_hasher(1, [0; 64], Vec::new(), 4);
+    /// 
+    /// assert!(block.verify());
+    /// ```
+    pub fn with_hasher(index: usize, prev_hash: [u8; 64], transactions: Vec<Transaction>, difficulty: usize) -> Self {
+        let mut block = Block::unmined(index, prev_hash, transactions, difficulty, Utc::now());
+
+        block.calculate_hash(u128::MAX, 1, None).expect("Error mining the block.");
+
+        block
+    }
+
+    /// Generates a new `Block` with the given `DateTime<Utc>` time instead of the current time.
+    pub(crate) fn mined_at(index: usize, prev_hash: [u8; 64], transactions: Vec<Transaction>, difficulty: usize, time: DateTime<Utc>) -> Self {
+        let mut block = Block::unmined(index, prev_hash, transactions, difficulty, time);
+
+        block.calculate_hash(u128::MAX, 1, None).expect("Error mining the block.");
+
+        block
+    }
+
+    /// This method mines the block, searching the nonces from `0`, e.g. after `Block::assemble_unmined()`;
+    /// the nonce and the hash are replaced, so a block that was already mined is mined again.
+    /// 
+    /// # Panics
+    /// The method panics if no nonce up to `u128::MAX` satisfies the difficulty, see `new_with_difficulty()`.
+    pub fn mine_in_place(&mut self) {
+        self.nonce = 0;
+
+        self.calculate_hash(u128::MAX, 1, None).expect("Error mining the block.");
+    }
+
+    /// Mines a block which is not mined yet, trying only the nonces from `0` up to `max_nonce` (included).
+    pub(crate) fn mine_up_to(mut self, max_nonce: u128) -> Result<Self, MiningError> {
+        self.calculate_hash(max_nonce, 1, None)?;
+
+        Ok(self)
+    }
+
+    /// Converts the block into a `Block` hashed with the `Hasher` `S`, keeping all its fields, including the nonce and the hash.
+    pub(crate) fn into_hasher<S: Hasher>(self) -> Block<S> {
+        Block {
+            version: self.version,
+            index: self.index,
+            prev_hash: self.prev_hash,
+            transactions: self.transactions,
+            nonce: self.nonce,
+            time: self.time,
+            difficulty: self.difficulty,
+            target: self.target,
+            hash: self.hash,
+            pruned_root: self.pruned_root,
+            extra_data: self.extra_data,
+            chain_id: self.chain_id,
+            producer: self.producer,
+            hasher: PhantomData,
+        }
+    }
+
+    /// Generates a new `Block` which is not mined yet, with the transactions sorted in the canonical order, see `sort_canonically()`.
+    fn unmined(index: usize, prev_hash: [u8; 64], mut transactions: Vec<Transaction>, difficulty: usize, time: DateTime<Utc>) -> Self {
+        sort_canonically(&mut transactions);
+
+        Self {
+            index,
+            prev_hash,
+            transactions,
+            nonce: 0,
+            time,
+            difficulty,
+            target: DEFAULT_TARGET,
+            hash: [0; 64],
+            pruned_root: None,
+            extra_data: Vec::new(),
+            chain_id: 0,
+            version: BLOCK_VERSION,
+            producer: None,
+            hasher: PhantomData,
+        }
+    }
+
+    /// This method returns the hash of the block, since the `hash` field isn't `pub`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// let genesis_block = Block::default();
+    /// 
+    /// assert_eq!(genesis_block.hash().len(), 64); // the hash changes everytime because of the time
+    /// ```
+    pub fn hash(&self) -> [u8; 64] {
+        self.hash
+    }
+
+    /// This method returns a reference to the hash of the block, like `hash()` but without copying it,
+    /// e.g. to compare the hashes of many blocks.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// let block = Block::new_with_difficulty(1, [0; 64], Vec::new(), 4);
+    /// 
+    /// assert_eq!(*block.hash_ref(), block.hash());
+    /// ```
+    pub fn hash_ref(&self) -> &[u8; 64] {
+        &self.hash
+    }
+
+    /// This method returns the `BlockId` of the block, i.e. its hash, which can be used
+    /// to put the blocks in a `HashSet` or as the keys of a `HashMap`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// use std::collections::HashSet;
+    /// 
+    /// let block = Block::new_with_difficulty(1, [0; 64], Vec::new(), 4);
+    /// 
+    /// let mut ids = HashSet::new();
+    /// ids.insert(block.id());
+    /// ids.insert(block.clone().id());
+    /// 
+    /// assert_eq!(ids.len(), 1);
+    /// ```
+    pub fn id(&self) -> BlockId {
+        BlockId(self.hash)
+    }
+
+    /// This method returns the index of the block, since the `index` field isn't `pub`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// let genesis_block = Block::default();
+    /// 
+    /// assert_eq!(genesis_block.index(), 0);
+    /// ```
+    #[allow(dead_code)]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// This method returns the hash of the previous block, since the `prev_hash` field isn't `pub`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// let genesis = Block::default();
+    /// 
+    /// let new_block = Block::new_with_difficulty(1, genesis.hash(), Vec::new(), 4);
+    /// 
+    /// assert_eq!(new_block.prev_hash(), genesis.hash()); // the blocks are linked by the hashes
+    /// ```
+    pub fn prev_hash(&self) -> [u8; 64] {
+        self.prev_hash
+    }
+
+    /// This method returns the transactions of the block, since the `transactions` field isn't `pub`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::{block::Block, transaction::Transaction, account::Account};
+    /// let mut ingrid = Account::new("Ingrid", "Moss", "ingrid_MOSS_1987").unwrap();
+    /// let oscar = Account::new("Oscar", "Fielding", "0scar?Fielding").unwrap();
+    /// ingrid.add_money(15);
+    /// 
+    /// let transaction = Transaction::new(ingrid, oscar, 5, "ingrid_MOSS_1987").unwrap();
+    /// 
+    /// let block = Block::new_with_difficulty(1, [0; 64], vec![transaction.clone()], 4);
+    /// 
+    /// assert_eq!(block.transactions(), &[transaction]);
+    /// ```
+    pub fn transactions(&self) -> &[Transaction] {
+        &self.transactions
+    }
+
+    /// This method returns the nonce found by the proof of work, since the `nonce` field isn't `pub`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// let block = Block::new_with_difficulty(1, [0; 64], Vec::new(), 0);
+    /// 
+    /// assert_eq!(block.nonce(), 0); // with no difficulty the first nonce is always valid
+    /// ```
+    pub fn nonce(&self) -> u128 {
+        self.nonce
+    }
+
+    /// This method returns the `DateTime<Utc>` time when the block was generated, since the `time` field isn't `pub`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// # use chrono::Utc;
+    /// let before = Utc::now();
+    /// 
+    /// let block = Block::new_with_difficulty(1, [0; 64], Vec::new(), 4);
+    /// 
+    /// assert!(block.time() >= before);
+    /// ```
+    pub fn time(&self) -> DateTime<Utc> {
+        self.time
+    }
+
+    /// This method returns the difficulty the block was mined with, since the `difficulty` field isn't `pub`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::{Block, DEFAULT_DIFFICULTY};
+    /// let genesis_block = Block::default();
+    /// 
+    /// assert_eq!(genesis_block.difficulty(), DEFAULT_DIFFICULTY);
+    /// ```
+    pub fn difficulty(&self) -> usize {
+        self.difficulty
+    }
+
+    /// This method returns the target byte the block was mined with, since the `target` field isn't `pub`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::{Block, DEFAULT_TARGET};
+    /// let block = Block::new_with_difficulty(1, [0; 64], Vec::new(), 4);
+    /// 
+    /// assert_eq!(block.target(), DEFAULT_TARGET);
+    /// ```
+    pub fn target(&self) -> u8 {
+        self.target
+    }
+
+    /// This method returns the extra data of the block, since the `extra_data` field isn't `pub`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// let block = Block::new_with_difficulty(1, [0; 64], Vec::new(), 4);
+    /// 
+    /// assert!(block.extra_data().is_empty());
+    /// ```
+    pub fn extra_data(&self) -> &[u8] {
+        &self.extra_data
+    }
+
+    /// This method returns the identifier of the chain the block is mined for, since the `chain_id` field isn't `pub`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// let block = Block::new_with_difficulty(1, [0; 64], Vec::new(), 4);
+    /// 
+    /// assert_eq!(block.chain_id(), 0);
+    /// ```
+    pub fn chain_id(&self) -> u32 {
+        self.chain_id
+    }
+
+    /// This method returns the public key of the account which produced the block, since the `producer` field isn't `pub`:
+    /// the blocks mined by a `BlockChain` with a miner, see `BlockChain::set_miner()`, are produced by the miner,
+    /// while the other blocks have no producer. The producer is hashed with the block, so it can't be changed.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::account::Account;
+    /// let uma = Account::new("Uma", "Vance", "um4_v4nc3").unwrap();
+    /// 
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// blockchain.set_miner(uma.clone());
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// assert_eq!(blockchain.chain()[0].producer(), None);
+    /// assert_eq!(blockchain.chain()[1].producer(), Some(uma.public_key()));
+    /// ```
+    pub fn producer(&self) -> Option<[u8; 32]> {
+        self.producer
+    }
+
+    /// This method returns the version of the format of the block, since the `version` field isn't `pub`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::{Block, BLOCK_VERSION};
+    /// let block = Block::new_with_difficulty(1, [0; 64], Vec::new(), 4);
+    /// 
+    /// assert_eq!(block.version(), BLOCK_VERSION);
+    /// ```
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// This method returns the number of leading zero bits of the hash of the block,
+    /// which can be more than the difficulty the block was mined with.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// let block = Block::new_with_difficulty(1, [0; 64], Vec::new(), 4);
+    /// 
+    /// assert!(block.achieved_difficulty() >= 4);
+    /// ```
+    pub fn achieved_difficulty(&self) -> usize {
+        matching_bits(&self.hash, 0)
+    }
+
+    /// This method returns the root of the Merkle tree built over the hashes of the transactions of the block.
+    /// 
+    /// Every node of the tree is the hash of its two children, and when a level
+    /// has an odd number of nodes the last one is duplicated; a block without transactions has
+    /// the `EMPTY_MERKLE_ROOT`, made of zeros. The root of a pruned block is the root of the transactions it had before `prune()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::{block::Block, transaction::Transaction, account::Account};
+    /// let mut lorna = Account::new("Lorna", "Hayes", "Lorna_Hayes.1979").unwrap();
+    /// let vince = Account::new("Vince", "Carter", "vince++carter").unwrap();
+    /// lorna.add_money(15);
+    /// 
+    /// let transaction = Transaction::new(lorna, vince, 5, "Lorna_Hayes.1979").unwrap();
+    /// 
+    /// let block = Block::new_with_difficulty(1, [0; 64], vec![transaction.clone()], 4);
+    /// 
+    /// assert_eq!(block.merkle_root(), transaction.hash()); // with a single transaction the root is its hash
+    /// ```
+    pub fn merkle_root(&self) -> [u8; 64] {
+        self.pruned_root.unwrap_or_else(|| self.transactions_root())
+    }
+
+    /// Computes the Merkle root of the transactions of the block, ignoring the root kept by `prune()`.
+    fn transactions_root(&self) -> [u8; 64] {
+        let mut level: Vec<[u8; 64]> = self.transactions.iter().map(|t| t.hash()).collect();
+
+        if level.is_empty() {
+            return EMPTY_MERKLE_ROOT;
+        }
+
+        while level.len() > 1 {
+            level = merkle_parents::<H>(&level);
+        }
+
+        level[0]
+    }
+
+    /// This method drops the transactions of the block, keeping only their Merkle root,
+    /// so that the block still verifies with `verify()`; pruning a block twice does nothing.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::{block::Block, transaction::Transaction, account::Account};
+    /// let mut freya = Account::new("Freya", "Lund", "fr3ya-lund").unwrap();
+    /// let joel = Account::new("Joel", "Park", "joel.park.77").unwrap();
+    /// freya.add_money(15);
+    /// 
+    /// let transaction = Transaction::new(freya, joel, 5, "fr3ya-lund").unwrap();
+    /// 
+    /// let mut block = Block::new_with_difficulty(1, [0; 64], vec![transaction], 4);
+    /// let root = block.merkle_root();
+    /// 
+    /// block.prune();
+    /// 
+    /// assert!(block.is_pruned());
+    /// assert!(block.transactions().is_empty());
+    /// assert_eq!(block.merkle_root(), root);
+    /// assert!(block.verify());
+    /// ```
+    pub fn prune(&mut self) {
+        self.pruned_root = Some(self.merkle_root());
+        self.transactions = Vec::new();
+    }
+
+    /// This method returns `true` if the transactions of the block were dropped with `prune()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// let block = Block::new_with_difficulty(1, [0; 64], Vec::new(), 4);
+    /// 
+    /// assert!(!block.is_pruned());
+    /// ```
+    pub fn is_pruned(&self) -> bool {
+        self.pruned_root.is_some()
+    }
+
+    /// This method returns the total of the outputs of the coinbase transaction of the block,
+    /// i.e. the reward of the miner plus the fees of the other transactions,
+    /// or `None` for the genesis block, a pruned block or a block without a coinbase transaction.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::{block::Block, transaction::Transaction, account::Account};
+    /// let miner = Account::new("Nadia", "Ferro", "n4d14_f3rr0").unwrap();
+    /// 
+    /// let block = Block::new_with_difficulty(1, [0; 64], vec![Transaction::coinbase(miner, 50)], 4);
+    /// 
+    /// assert_eq!(block.coinbase_value(), Some(50));
+    /// assert_eq!(Block::new_with_difficulty(1, [0; 64], Vec::new(), 4).coinbase_value(), None);
+    /// ```
+    pub fn coinbase_value(&self) -> Option<Amount> {
+        if self.index == 0 || self.is_pruned() {
+            return None;
+        }
+
+        self.transactions.iter().find(|transaction| transaction.is_coinbase()).map(Transaction::amount)
+    }
+
+    /// This method returns the Merkle proof of the transaction at the given index,
+    /// which is the list of the sibling hashes from the transaction up to the root of the tree.
+    /// 
+    /// The proof can be checked with `Block::verify_merkle_proof()`, without knowing the other transactions.
+    /// 
+    /// # Panics
+    /// The method panics if `tx_index` is out of bounds.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::{block::Block, transaction::Transaction, account::Account};
+    /// let mut tessa = Account::new("Tessa", "Quinn", "TessaQ_4ever").unwrap();
+    /// let milo = Account::new("Milo", "Grant", "m1l0_Gr4nt").unwrap();
+    /// tessa.add_money(30);
+    /// 
+    /// let transactions = vec![
+    ///     Transaction::new(tessa.clone(), milo.clone(), 5, "TessaQ_4ever").unwrap(),
+    ///     Transaction::new(tessa.clone(), milo.clone(), 6, "TessaQ_4ever").unwrap(),
+    ///     Transaction::new(tessa, milo, 7, "TessaQ_4ever").unwrap(),
+    /// ];
+    /// 
+    /// let block = Block::new_with_difficulty(1, [0; 64], transactions, 4);
+    /// 
+    /// let proof = block.merkle_proof(2);
+    /// 
+    /// assert!(Block::verify_merkle_proof(block.transactions()[2].hash(), 2, &proof, block.merkle_root()));
+    /// ```
+    pub fn merkle_proof(&self, tx_index: usize) -> Vec<[u8; 64]> {
+        let mut level: Vec<[u8; 64]> = self.transactions.iter().map(|t| t.hash()).collect();
+
+        assert!(tx_index < level.len(), "The transaction index is out of bounds.");
+
+        let mut index = tx_index;
+        let mut proof = Vec::new();
+
+        while level.len() > 1 {
+            proof.push(*level.get(index ^ 1).unwrap_or(&level[index]));
+
+            level = merkle_parents::<H>(&level);
+            index /= 2;
+        }
+
+        proof
+    }
+
+    /// This method checks if the block is valid, by calculating again its hash
+    /// and checking if it matches the `hash` of the block, and if the hash satisfies
+    /// the proof of work for the `difficulty` and the `target` of the block;
+    /// the hash of every transaction is checked too, with `Transaction::verify_hash()`,
+    /// and so is the Merkle root kept with the transactions, if any, see `has_consistent_merkle_root()`;
+    /// finally, the signatures of the transactions are checked with `verify_signatures_batch()`.
+    /// 
+    /// The difficulty and the target are recorded in the block, and serialized with it, so a block
+    /// received from a peer is checked with the work it was mined with, whatever the difficulty of the chain
+    /// of the node checking it; the chain can then require a minimum difficulty, see `BlockChain::next_difficulty()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// let block = Block::new_with_difficulty(1, [0; 64], Vec::new(), 4);
+    /// 
+    /// assert!(block.verify());
+    /// ```
+    pub fn verify(&self) -> bool {
+        self.verify_hashes() && self.verify_signatures_batch()
+    }
+
+    /// Checks the block like `verify()`, but without the signatures of the transactions.
+    pub(crate) fn verify_hashes(&self) -> bool {
+        self.digest(&self.merkle_root()) == self.hash
+            && self.satisfies_proof_of_work()
+            && self.transactions.iter().all(|transaction| transaction.verify_hash())
+            && self.has_consistent_merkle_root()
+    }
+
+    /// This method checks that the Merkle root kept by a pruned block, see `prune()`, is the root of its transactions,
+    /// if the block still has some, e.g. a block imported from a file: the hash of the block is calculated
+    /// with the kept root, so without this check the transactions could be swapped keeping the same header.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::{block::Block, transaction::Transaction, account::Account};
+    /// let miner = Account::new("Wren", "Xu", "wr3n_xu").unwrap();
+    /// 
+    /// let block = Block::new_with_difficulty(1, [0; 64], vec![Transaction::coinbase(miner, 5)], 4);
+    /// let mut pruned = block.clone();
+    /// pruned.prune();
+    /// 
+    /// assert!(block.has_consistent_merkle_root());
+    /// assert!(pruned.has_consistent_merkle_root()); // the pruned block has no transactions to check
+    /// ```
+    pub fn has_consistent_merkle_root(&self) -> bool {
+        match self.pruned_root {
+            Some(root) => self.transactions.is_empty() || self.transactions_root() == root,
+            None => true,
+        }
+    }
+
+    /// This method checks that every transaction of the block is in the block only once, comparing their hashes:
+    /// a transaction repeated in the same block would be a double spending, but it's still hashed twice
+    /// in the Merkle root, so `verify()` doesn't notice it.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::{block::Block, transaction::Transaction, account::Account};
+    /// let miner = Account::new("Remy", "Blanc", "r3my_bl4nc").unwrap();
+    /// let coinbase = Transaction::coinbase(miner, 5);
+    /// 
+    /// let block = Block::new_with_difficulty(1, [0; 64], vec![coinbase.clone(), coinbase], 4);
+    /// 
+    /// assert!(block.verify());
+    /// assert!(!block.has_unique_transactions());
+    /// ```
+    pub fn has_unique_transactions(&self) -> bool {
+        let mut hashes = HashSet::with_capacity(self.transactions.len());
+
+        self.transactions.iter().all(|transaction| hashes.insert(transaction.hash()))
+    }
+
+    /// This method checks that the transactions of the block are in the canonical order, see `sort_canonically()`,
+    /// like the transactions of the blocks mined by a `BlockChain` or built with a `BlockBuilder`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::{block::Block, transaction::Transaction, account::Account};
+    /// # use chrono::Utc;
+    /// let mut zoe = Account::new("Zoe", "Amar", "z0e_4m4r").unwrap();
+    /// let ali = Account::new("Ali", "Brandt", "ali.brandt.pw").unwrap();
+    /// zoe.add_money(10);
+    /// 
+    /// let mut transactions = vec![
+    ///     Transaction::new(zoe.clone(), ali.clone(), 1, "z0e_4m4r").unwrap(),
+    ///     Transaction::new(zoe, ali, 2, "z0e_4m4r").unwrap().with_nonce(1),
+    /// ];
+    /// transactions.reverse();
+    /// 
+    /// // the blocks generated by this crate are sorted, but a block received from a peer may not be
+    /// let received = Block::from_parts(1, [0; 64], transactions.clone(), 4, 0, Utc::now(), [0; 64]);
+    /// 
+    /// assert!(!received.is_canonically_ordered());
+    /// assert!(Block::new_with_difficulty(1, [0; 64], transactions, 4).is_canonically_ordered());
+    /// ```
+    pub fn is_canonically_ordered(&self) -> bool {
+        self.transactions.windows(2).all(|pair| canonical_cmp(&pair[0], &pair[1]) != cmp::Ordering::Greater)
+    }
+
+    /// Checks if the transactions of the block are in the order required by its version:
+    /// the blocks from `CANONICAL_ORDER_VERSION` on must be in the canonical order, see `is_canonically_ordered()`.
+    /// The older blocks are exempt only when they're loaded from the old storage: a chain rejects the new blocks
+    /// with an older version than `BLOCK_VERSION`, and the blocks with an older version than the previous block.
+    pub(crate) fn has_required_order(&self) -> bool {
+        self.version < CANONICAL_ORDER_VERSION || self.is_canonically_ordered()
+    }
+
+    /// This method checks the signatures of all the transactions of the block at once,
+    /// like calling `Transaction::verify_signature()` on every transaction, and returns `true` if they're all valid.
+    /// 
+    /// The signatures are verified in parallel with the strict verification of `ed25519_dalek`, which always
+    /// gives the same result for the same signature, unlike its batch verification, see `transaction::verify_signatures_batch()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::{block::Block, transaction::Transaction, account::Account};
+    /// let mut wade = Account::new("Wade", "Hale", "w4d3_h4l3").unwrap();
+    /// let xavi = Account::new("Xavi", "Roca", "xavi.roca.pw").unwrap();
+    /// wade.add_money(10);
+    /// 
+    /// let transactions = vec![
+    ///     Transaction::new(wade.clone(), xavi.clone(), 1, "w4d3_h4l3").unwrap(),
+    ///     Transaction::new(wade, xavi, 2, "w4d3_h4l3").unwrap().with_nonce(1),
+    /// ];
+    /// 
+    /// let block = Block::new_with_difficulty(1, [0; 64], transactions, 4);
+    /// 
+    /// assert!(block.verify_signatures_batch());
+    /// ```
+    pub fn verify_signatures_batch(&self) -> bool {
+        transaction::verify_signatures_batch(&self.transactions)
+    }
+
+    /// This method checks if the hash of the block satisfies the proof of work, i.e. if
+    /// at least `difficulty` leading bits of the hash match the leading bits of the target.
+    fn satisfies_proof_of_work(&self) -> bool {
+        satisfies(&self.hash, self.difficulty, self.target)
+    }
+
+    /// This method is called when a new block is generated,
+    /// and it is used to calculate the hash of the new block.
+    /// 
+    /// The proof of work is satisfied when at least `difficulty` leading bits of the hash match the target,
+    /// otherwise the nonce is incremented by `step` and the hash is calculated again; if the nonce
+    /// would go past `max_nonce` (or past `u128::MAX`), a `MiningError::Exhausted` error is returned,
+    /// and if the `stop` flag is set, a `MiningError::Cancelled` error is returned.
+    fn calculate_hash(&mut self, max_nonce: u128, step: u128, stop: Option<&AtomicBool>) -> Result<(), MiningError> {
+        let (difficulty, target) = (self.difficulty, self.target);
+
+        self.search_nonce(max_nonce, step, stop, |hash| satisfies(hash, difficulty, target))
+    }
+
+    /// Searches the nonces like `calculate_hash()`, until the hash of the block satisfies the given condition.
+    fn search_nonce(&mut self, max_nonce: u128, step: u128, stop: Option<&AtomicBool>, condition: impl Fn(&[u8; 64]) -> bool) -> Result<(), MiningError> {
+        self.search_nonce_with_progress(max_nonce, step, stop, condition, u128::MAX, &mut |_| ())
+    }
+
+    /// Searches the nonces like `search_nonce()`, calling `on_progress` with the number of nonces tried
+    /// without success every `interval` nonces.
+    fn search_nonce_with_progress(
+        &mut self,
+        max_nonce: u128,
+        step: u128,
+        stop: Option<&AtomicBool>,
+        condition: impl Fn(&[u8; 64]) -> bool,
+        interval: u128,
+        on_progress: &mut dyn FnMut(u128),
+    ) -> Result<(), MiningError> {
+        let header = self.header_hasher(&self.merkle_root());
+        let mut attempts: u128 = 0;
+
+        loop {
+            if stop.is_some_and(|s| s.load(Ordering::Relaxed)) {
+                return Err(MiningError::Cancelled);
+            }
+
+            self.hash = digest_nonce::<H>(&header, self.nonce);
+
+            if condition(&self.hash) {
+                return Ok(());
+            }
+
+            attempts += 1;
+
+            if attempts.is_multiple_of(interval) {
+                on_progress(attempts);
+            }
+
+            match self.nonce.checked_add(step) {
+                Some(nonce) if nonce <= max_nonce => self.nonce = nonce,
+                _ => return Err(MiningError::Exhausted),
+            }
+        }
+    }
+
+    /// This method returns the canonical byte encoding of the block header, which is what gets hashed
+    /// to obtain the hash of the block: the raw bytes of the hash of the previous block
+    /// and of the Merkle root of the transactions, followed by the index, the time
+    /// in nanoseconds since the Unix epoch, and the nonce, all in little-endian.
+    /// If the block has a chain identifier, see `chain_id()`, extra data, or a producer, see `producer()`, they come before the nonce,
+    /// and so does the version, see `version()`, unless it's the first version.
+    /// From `PROOF_OF_WORK_VERSION` on, the difficulty (as a `u64`) and the target byte come after the version,
+    /// so that the proof of work a block claims can't be changed after it's mined.
+    /// The chain identifier, the producer, the version and the proof of work come after a tag byte, and so does the extra data,
+    /// followed by its length, so a field can't be mistaken for another one.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// let block = Block::new_with_difficulty(1, [0; 64], Vec::new(), 4);
+    /// 
+    /// let header = block.header_bytes();
+    /// 
+    /// assert_eq!(header.len(), 64 + 64 + 8 + 16 + 1 + 2 + 1 + 8 + 1 + 16); // the version and the proof of work come after their tags
+    /// assert_eq!(&header[..64], &block.prev_hash()[..]);
+    /// assert_eq!(&header[header.len() - 16..], &block.nonce().to_le_bytes()[..]);
+    /// ```
+    pub fn header_bytes(&self) -> Vec<u8> {
+        let mut header = self.header_digest_prefix();
+
+        header.extend_from_slice(&self.nonce.to_le_bytes());
+
+        header
+    }
+
+    /// This method returns the canonical encoding of the header like `header_bytes()`, without the nonce:
+    /// an external miner can search the nonces with `Block::finalize_with_nonce()`,
+    /// and build the mined block with the winning nonce using `Block::from_parts()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// let block = Block::new_with_difficulty(1, [0; 64], Vec::new(), 4);
+    /// 
+    /// let prefix = block.header_digest_prefix();
+    /// 
+    /// assert_eq!(prefix.len(), block.header_bytes().len() - 16);
+    /// assert_eq!(Block::finalize_with_nonce(&prefix, block.nonce()), block.hash());
+    /// ```
+    pub fn header_digest_prefix(&self) -> Vec<u8> {
+        self.header_prefix(&self.merkle_root())
+    }
+
+    /// This method returns the size of the block in bytes: the size of the header returned by `header_bytes()`,
+    /// plus the size of the hash and the size of every transaction, returned by `Transaction::size_bytes()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let miner = Account::new("Otto", "Vance", "0tt0_v4nc3").unwrap();
+    /// let coinbase = Transaction::coinbase(miner, 10);
+    /// 
+    /// let empty = Block::new_with_difficulty(1, [0; 64], Vec::new(), 4);
+    /// let block = Block::new_with_difficulty(1, [0; 64], vec![coinbase.clone()], 4);
+    /// 
+    /// assert_eq!(empty.size_bytes(), empty.header_bytes().len() + 64);
+    /// assert_eq!(block.size_bytes(), empty.size_bytes() + coinbase.size_bytes());
+    /// ```
+    pub fn size_bytes(&self) -> usize {
+        self.header_size() + self.hash.len() + self.transactions.iter().map(|transaction| transaction.size_bytes()).sum::<usize>()
+    }
+
+    /// This method encodes the whole block, with its transactions, with `bincode` in a compact binary format
+    /// (see the `binary` module), which is smaller than the JSON encoding; the block is decoded with `from_bytes()`.
+    /// The method is available with the `bincode` feature.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// let block = Block::new_with_difficulty(1, [0; 64], Vec::new(), 4);
+    /// 
+    /// let bytes = block.to_bytes();
+    /// 
+    /// assert_eq!(Block::from_bytes(&bytes), Ok(block));
+    /// ```
+    #[cfg(feature = "bincode")]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        binary::encode(self)
+    }
+
+    /// Decodes a block encoded with `to_bytes()`; the block isn't verified, see `verify()`.
+    /// 
+    /// The function returns a `Result<Block, DecodeError>`:
+    /// - if the bytes end before the block, a `DecodeError::UnexpectedEnd` error is returned
+    /// - if there are bytes left after the block, a `DecodeError::TrailingBytes` error is returned
+    /// - if the version of the block is newer than `BLOCK_VERSION`, or `0`, a `DecodeError::UnsupportedVersion` error is returned
+    /// - if a field has an invalid value, a `DecodeError::Invalid` error is returned
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// # use blockchain::binary::DecodeError;
+    /// let bytes = Block::new_with_difficulty(1, [0; 64], Vec::new(), 4).to_bytes();
+    /// 
+    /// let truncated: Result<Block, _> = Block::from_bytes(&bytes[..bytes.len() - 1]);
+    /// let extended: Result<Block, _> = Block::from_bytes(&[&bytes[..], &[0]].concat());
+    /// 
+    /// assert_eq!(truncated, Err(DecodeError::UnexpectedEnd));
+    /// assert_eq!(extended, Err(DecodeError::TrailingBytes));
+    /// ```
+    #[cfg(feature = "bincode")]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        // the version is the first field, so that it's checked before decoding the rest of the block
+        let version = binary::decode(bytes.get(..2).ok_or(DecodeError::UnexpectedEnd)?)?;
+
+        if !is_supported_version(version) {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+
+        binary::decode(bytes)
+    }
+
+    /// Encodes the fields of the header that don't change while mining, i.e. everything but the nonce.
+    fn header_prefix(&self, merkle_root: &[u8; 64]) -> Vec<u8> {
+        let nanos = self.time.timestamp() as i128 * 1_000_000_000 + self.time.timestamp_subsec_nanos() as i128;
+
+        let mut header = Vec::with_capacity(self.header_size());
+
+        header.extend_from_slice(&self.prev_hash);
+        header.extend_from_slice(merkle_root);
+        header.extend_from_slice(&(self.index as u64).to_le_bytes());
+        header.extend_from_slice(&nanos.to_le_bytes());
+
+        // without a chain identifier and extra data the header is the same as before they were added, and so is the hash
+        if self.chain_id != 0 {
+            header.push(CHAIN_ID_TAG);
+            header.extend_from_slice(&self.chain_id.to_le_bytes());
+        }
+
+        if !self.extra_data.is_empty() {
+            header.push(EXTRA_DATA_TAG);
+            header.extend_from_slice(&(self.extra_data.len() as u64).to_le_bytes());
+            header.extend_from_slice(&self.extra_data);
+        }
+
+        if let Some(producer) = &self.producer {
+            header.push(PRODUCER_TAG);
+            header.extend_from_slice(producer);
+        }
+
+        // the blocks of the first version are hashed like before the version was added
+        if self.version != 1 {
+            header.push(VERSION_TAG);
+            header.extend_from_slice(&self.version.to_le_bytes());
+        }
+
+        if self.version >= PROOF_OF_WORK_VERSION {
+            header.push(PROOF_OF_WORK_TAG);
+            header.extend_from_slice(&(self.difficulty as u64).to_le_bytes());
+            header.push(self.target);
+        }
+
+        header
+    }
+
+    /// Returns the size of the encoding of the header, returned by `header_bytes()`.
+    fn header_size(&self) -> usize {
+        let chain_id = if self.chain_id == 0 { 0 } else { 1 + 4 };
+        let extra_data = if self.extra_data.is_empty() { 0 } else { 1 + 8 + self.extra_data.len() };
+        let producer = if self.producer.is_none() { 0 } else { 1 + 32 };
+        let version = if self.version == 1 { 0 } else { 1 + 2 };
+        let proof_of_work = if self.version < PROOF_OF_WORK_VERSION { 0 } else { 1 + 8 + 1 };
+
+        HEADER_SIZE + chain_id + extra_data + producer + version + proof_of_work
+    }
+
+    /// Hashes the fields of the block that don't change while mining,
+    /// so that only the nonce has to be hashed for each attempt.
+    fn header_hasher(&self, merkle_root: &[u8; 64]) -> H::State {
+        let mut state = H::new();
+
+        H::update(&mut state, &self.header_prefix(merkle_root));
+
+        state
+    }
+
+    fn digest(&self, merkle_root: &[u8; 64]) -> [u8; 64] {
+        digest_nonce::<H>(&self.header_hasher(merkle_root), self.nonce)
+    }
+}
+
+impl Default for Block {
+    fn default() -> Self {
+        Block::new(0, [0; 64], Vec::new())
+    }
+}
+
+impl<H> fmt::Display for Block<H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Block #{} {} (previous: {}), {} transactions, nonce {}, mined at {}",
+            self.index,
+            short_hex(&self.hash),
+            short_hex(&self.prev_hash),
+            self.transactions.len(),
+            self.nonce,
+            self.time.to_rfc3339(),
+        )
+    }
+}
+
+/// The identifier of a `Block`, which is its hash, returned by `Block::id()`.
+/// 
+/// Unlike a `Block`, whose equality compares all of its fields, a `BlockId` implements `Eq` and `Hash`;
+/// two blocks that are equal have the same `BlockId`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BlockId(pub [u8; 64]);
+
+impl fmt::Display for BlockId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hash_to_hex(&self.0))
+    }
+}
+
+/// The statistics of the mining of a `Block`, returned by `Block::mine_with_stats()`:
+/// - the number of nonces rejected before the nonce of the block, i.e. its nonce
+/// - the time spent mining
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MiningStats {
+    pub attempts: u128,
+    pub elapsed: Duration,
+}
+
+/// A structure to assemble the transactions of a `Block` before mining it: the transactions are
+/// collected in a vector allocated once for the maximum number of transactions of the block,
+/// and they're checked as they're pushed, so that `build()` only has to mine the block.
+/// 
+/// The block is mined with `DEFAULT_DIFFICULTY`, for the chain `0` and at the current time,
+/// unless they're changed with `with_difficulty()`, `with_chain_id()` and `with_time()`.
+/// 
+/// # Example
+/// ```
+/// # use blockchain::block::{Block, BlockBuilder, BlockError};
+/// # use blockchain::transaction::Transaction;
+/// # use blockchain::account::Account;
+/// let miner = Account::new("Tess", "Ward", "t3ss_w4rd").unwrap();
+/// 
+/// let mut builder = BlockBuilder::new(1, [0; 64], 1).with_difficulty(4);
+/// 
+/// assert_eq!(builder.push(Transaction::coinbase(miner.clone(), 5)), Ok(()));
+/// assert_eq!(builder.push(Transaction::coinbase(miner, 5)), Err(BlockError::TooManyTransactions));
+/// 
+/// let block = builder.build().unwrap();
+/// 
+/// assert_eq!(block.transactions().len(), 1);
+/// assert!(block.verify());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockBuilder {
+    index: usize,
+    prev_hash: [u8; 64],
+    transactions: Vec<Transaction>,
+    max_transactions: usize,
+    difficulty: usize,
+    chain_id: u32,
+    time: Option<DateTime<Utc>>,
+}
+
+impl BlockBuilder {
+    /// Generates a new `BlockBuilder` for the block with the given index, linked to the block with the given hash,
+    /// which can contain at most `transactions_per_block` transactions.
+    pub fn new(index: usize, prev_hash: [u8; 64], transactions_per_block: usize) -> Self {
+        Self {
+            index,
+            prev_hash,
+            transactions: Vec::with_capacity(transactions_per_block),
+            max_transactions: transactions_per_block,
+            difficulty: DEFAULT_DIFFICULTY,
+            chain_id: 0,
+            time: None,
+        }
+    }
+
+    /// This method sets the difficulty the block will be mined with.
+    pub fn with_difficulty(mut self, difficulty: usize) -> Self {
+        self.difficulty = difficulty;
+        self
+    }
+
+    /// This method sets the identifier of the chain the block will be mined for, see `Block::new_on_chain()`;
+    /// the transactions pushed must be signed for the same chain, except for the coinbase transactions.
+    pub fn with_chain_id(mut self, chain_id: u32) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    /// This method sets the time the block will be mined at, instead of the time `build()` is called.
+    pub fn with_time(mut self, time: DateTime<Utc>) -> Self {
+        self.time = Some(time);
+        self
+    }
+
+    /// This method checks the given transaction and adds it to the block.
+    /// 
+    /// The method returns a `Result<(), BlockError>`, and if the transaction isn't added the block is left untouched:
+    /// - if the block already has `transactions_per_block` transactions, a `BlockError::TooManyTransactions` error is returned
+    /// - if the transaction isn't valid, see `Transaction::validate()`, or it's signed for another chain,
+    ///   a `BlockError::InvalidTransaction` error is returned
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::{BlockBuilder, BlockError};
+    /// # use blockchain::transaction::{Transaction, ValidationError};
+    /// # use blockchain::account::Account;
+    /// let uma = Account::new("Uma", "Frost", "um4_fr0st").unwrap();
+    /// let vic = Account::new("Vic", "Stone", "vic.stone.pw").unwrap();
+    /// 
+    /// let mut builder = BlockBuilder::new(1, [0; 64], 5);
+    /// let overspending = Transaction::new(uma, vic, 5, "um4_fr0st").unwrap(); // uma has no money
+    /// 
+    /// assert_eq!(builder.push(overspending), Err(BlockError::InvalidTransaction(ValidationError::InvalidAmount)));
+    /// assert!(builder.is_empty());
+    /// ```
+    pub fn push(&mut self, transaction: Transaction) -> Result<(), BlockError> {
+        if self.is_full() {
+            return Err(BlockError::TooManyTransactions);
+        }
+
+        transaction.validate(transaction.hash()).map_err(BlockError::InvalidTransaction)?;
+
+        if !transaction.is_coinbase() && transaction.chain_id() != self.chain_id {
+            return Err(BlockError::InvalidTransaction(ValidationError::WrongChain));
+        }
+
+        self.transactions.push(transaction);
+
+        Ok(())
+    }
+
+    /// This method returns the transactions pushed so far.
+    pub fn transactions(&self) -> &[Transaction] {
+        &self.transactions
+    }
+
+    /// This method returns the number of transactions pushed so far.
+    pub fn len(&self) -> usize {
+        self.transactions.len()
+    }
+
+    /// This method returns `true` if no transactions were pushed.
+    pub fn is_empty(&self) -> bool {
+        self.transactions.is_empty()
+    }
+
+    /// This method returns `true` if the block has `transactions_per_block` transactions, so no more can be pushed.
+    pub fn is_full(&self) -> bool {
+        self.transactions.len() >= self.max_transactions
+    }
+
+    /// This method mines the block with the transactions pushed, sorted in the canonical order
+    /// (see `sort_canonically()`), and returns it.
+    /// 
+    /// The method returns a `Result<Block, MiningError>`: if no nonce satisfies the difficulty,
+    /// a `MiningError::Exhausted` error is returned.
+    pub fn build(self) -> Result<Block, MiningError> {
+        let time = self.time.unwrap_or_else(Utc::now);
+
+        Block::mine_on_chain(self.index, self.prev_hash, self.transactions, self.difficulty, self.chain_id, time, u128::MAX)
+    }
+}
+
+/// An enum to handle errors generated while mining `Block`s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MiningError {
+    Exhausted,
+    Cancelled,
+    IndexOverflow,
+}
+
+impl fmt::Display for MiningError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Exhausted => write!(f, "No valid nonce was found."),
+            Self::Cancelled => write!(f, "The mining was cancelled."),
+            Self::IndexOverflow => write!(f, "The index of the block would overflow."),
+        }
+    }
+}
+
+impl error::Error for MiningError {}
+
+/// An enum to handle errors generated while checking `Block`s before mining them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockError {
+    TooManyTransactions,
+    InvalidTransaction(ValidationError),
+}
+
+impl fmt::Display for BlockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::TooManyTransactions => write!(f, "Too many transactions in the block."),
+            Self::InvalidTransaction(e) => write!(f, "Invalid transaction in the block: {}", e),
+        }
+    }
+}
+
+impl error::Error for BlockError {}
+
+/// The version of the blocks serialized before the version was added.
+#[cfg(feature = "serde")]
+pub(crate) fn default_version() -> u16 {
+    1
+}
+
+/// Deserializes the version of a block, rejecting the versions newer than `BLOCK_VERSION`.
+#[cfg(feature = "serde")]
+pub(crate) fn deserialize_version<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<u16, D::Error> {
+    let version = u16::deserialize(deserializer)?;
+
+    if !is_supported_version(version) {
+        return Err(serde::de::Error::custom(DecodeError::UnsupportedVersion(version)));
+    }
+
+    Ok(version)
+}
+
+/// Checks if the blocks with the given version can be loaded, i.e. if it's a version from `1` to `BLOCK_VERSION`.
+#[cfg(feature = "serde")]
+fn is_supported_version(version: u16) -> bool {
+    (1..=BLOCK_VERSION).contains(&version)
+}
+
+/// Sorts the transactions of a block in the canonical order, so that the blocks assembled with the same transactions
+/// have the same Merkle root, and so the same hash, whatever order the transactions were collected in:
+/// the coinbase transactions come first, in their order, followed by the other transactions sorted by the public key of their sender,
+/// so that the transactions of a sender are sorted by nonce, and then by their hash.
+/// 
+/// # Example
+/// ```
+/// # use blockchain::block::sort_canonically;
+/// # use blockchain::{transaction::Transaction, account::Account};
+/// let mut bo = Account::new("Bo", "Carver", "b0_c4rv3r").unwrap();
+/// let cy = Account::new("Cy", "Doyle", "cy.doyle.pw").unwrap();
+/// bo.add_money(10);
+/// 
+/// let transfer = Transaction::new(bo, cy.clone(), 3, "b0_c4rv3r").unwrap();
+/// let coinbase = Transaction::coinbase(cy, 5);
+/// 
+/// let mut transactions = vec![transfer, coinbase.clone()];
+/// sort_canonically(&mut transactions);
+/// 
+/// assert_eq!(transactions[0], coinbase);
+/// ```
+pub fn sort_canonically(transactions: &mut [Transaction]) {
+    transactions.sort_by(canonical_cmp);
+}
+
+/// Compares two transactions in the canonical order, see `sort_canonically()`.
+fn canonical_cmp(a: &Transaction, b: &Transaction) -> cmp::Ordering {
+    match (a.is_coinbase(), b.is_coinbase()) {
+        (false, false) => canonical_key(a).cmp(&canonical_key(b)),
+        (a_coinbase, b_coinbase) => b_coinbase.cmp(&a_coinbase),
+    }
+}
+
+/// Returns the key of a transaction which isn't a coinbase in the canonical order: the public key of its sender, its nonce and its hash;
+/// the transactions aren't sorted by hash alone, which would put the later nonces of a sender before the earlier ones.
+fn canonical_key(transaction: &Transaction) -> (Option<[u8; 32]>, u64, [u8; 64]) {
+    (transaction.sender().map(|sender| sender.public_key()), transaction.nonce(), transaction.hash())
+}
+
+/// Encodes a hash as a string of 128 lowercase hex characters.
+/// 
+/// # Example
+/// ```
+/// # use blockchain::block::hash_to_hex;
+/// let mut hash = [0; 64];
+/// hash[0] = 0xab;
+/// 
+/// assert_eq!(hash_to_hex(&hash).len(), 128);
+/// assert!(hash_to_hex(&hash).starts_with("ab00"));
+/// ```
+pub fn hash_to_hex(hash: &[u8; 64]) -> String {
+    hash.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Decodes a hash from a string of exactly 128 hex characters, lowercase or uppercase.
+/// 
+/// The function returns a `Result<[u8; 64], ParseError>`:
+/// - if the string isn't 128 characters long, a `ParseError::InvalidLength` error is returned
+/// - if the string contains characters which aren't hex digits, a `ParseError::InvalidCharacter` error is returned
+/// 
+/// # Example
+/// ```
+/// # use blockchain::block::{Block, hash_to_hex, hash_from_hex, ParseError};
+/// let block = Block::new_with_difficulty(1, [0; 64], Vec::new(), 4);
+/// 
+/// assert_eq!(hash_from_hex(&hash_to_hex(&block.hash())), Ok(block.hash()));
+/// assert_eq!(hash_from_hex("abcd"), Err(ParseError::InvalidLength));
+/// assert_eq!(hash_from_hex(&"zz".repeat(64)), Err(ParseError::InvalidCharacter));
+/// ```
+pub fn hash_from_hex(hex: &str) -> Result<[u8; 64], ParseError> {
+    if hex.len() != 128 {
+        return Err(ParseError::InvalidLength);
+    }
+
+    let mut hash = [0; 64];
+
+    for (byte, digits) in hash.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        let high = (digits[0] as char).to_digit(16).ok_or(ParseError::InvalidCharacter)?;
+        let low = (digits[1] as char).to_digit(16).ok_or(ParseError::InvalidCharacter)?;
+
+        *byte = (high * 16 + low) as u8;
+    }
+
+    Ok(hash)
+}
+
+/// An enum to handle errors generated while parsing hashes with `hash_from_hex()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    InvalidLength,
+    InvalidCharacter,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::InvalidLength => write!(f, "A hash must be 128 hex characters long."),
+            Self::InvalidCharacter => write!(f, "Invalid hex character."),
+        }
+    }
+}
+
+impl error::Error for ParseError {}
+
+/// Encodes the first 8 and the last 8 hex characters of a hash, to print it in a short form.
+fn short_hex(hash: &[u8; 64]) -> String {
+    let hex = hash_to_hex(hash);
+
+    format!("{}..{}", &hex[..8], &hex[120..])
+}
+
+/// Calculates the hash of two nodes of the Merkle tree.
+fn merkle_hash<H: Hasher>(left: &[u8; 64], right: &[u8; 64]) -> [u8; 64] {
+    let mut state = H::new();
+
+    H::update(&mut state, left);
+    H::update(&mut state, right);
+
+    H::finalize(state)
+}
+
+/// Calculates the level of the Merkle tree above the given one,
+/// duplicating the last node if the level has an odd number of nodes.
+fn merkle_parents<H: Hasher>(level: &[[u8; 64]]) -> Vec<[u8; 64]> {
+    level
+        .chunks(2)
+        .map(|pair| merkle_hash::<H>(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+        .collect()
+}
+
+/// Finishes the hash of a block header with the given nonce.
+fn digest_nonce<H: Hasher>(header: &H::State, nonce: u128) -> [u8; 64] {
+    let mut state = header.clone();
+
+    H::update(&mut state, &nonce.to_le_bytes());
+
+    H::finalize(state)
+}
+
+/// Checks if at least `difficulty` leading bits of a hash match the leading bits of a sequence of target bytes.
+fn satisfies(hash: &[u8; 64], difficulty: usize, target: u8) -> bool {
+    matching_bits(hash, target) >= difficulty
+}
+
+/// Counts the number of leading bits of a hash matching the bits of a sequence of target bytes;
+/// with a target of `0`, it's the number of leading zero bits of the hash.
+fn matching_bits(hash: &[u8; 64], target: u8) -> usize {
+    let mut bits = 0;
+
+    for byte in hash.iter().map(|byte| byte ^ target) {
+        bits += byte.leading_zeros() as usize;
+
+        if byte != 0 {
+            break;
+        }
+    }
+
+    bits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{account::Account, hasher::DoubleSha512Hasher, clock::FixedClock};
+    use chrono::TimeZone;
+
+    #[test]
+    fn target_byte_matches_leading_bits() {
+        let mut hash = [0xff; 64];
+        hash[0] = 69;
+        hash[1] = 69;
+
+        assert_eq!(matching_bits(&hash, 69), 16); // 0xff ^ 69 = 0b1011_1010
+        assert_eq!(matching_bits(&hash, 0), 1); // 69 = 0b0100_0101
+        assert_eq!(matching_bits(&[0; 64], 0), 512);
+    }
+
+    #[test]
+    fn tampered_block_does_not_verify() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(10);
+
+        let transaction = Transaction::new(sender.clone(), receiver.clone(), 5, "sender_password").unwrap();
+
+        let mut block = Block::new_with_difficulty(1, [0; 64], vec![transaction], 4);
+
+        assert!(block.verify());
+
+        block.transactions[0] = Transaction::new(sender, receiver, 10, "sender_password").unwrap();
+
+        assert!(!block.verify());
+    }
+
+    #[test]
+    fn digest_is_hash_of_header_bytes() {
+        let mut block = Block::genesis(Utc.timestamp_opt(1_600_000_000, 0).unwrap());
+
+        for nonce in [0, 7, 1234567890, u128::MAX].iter() {
+            block.nonce = *nonce;
+
+            assert_eq!(block.digest(&block.merkle_root()), Sha512Hasher::hash(&block.header_bytes()));
+        }
+    }
+
+    #[test]
+    fn display_shows_short_hashes() {
+        let block = Block::new_with_difficulty(1, [0xab; 64], Vec::new(), 4);
+
+        let hash: String = block.hash()[..4].iter().map(|byte| format!("{:02x}", byte)).collect();
+        let printed = block.to_string();
+
+        assert!(printed.starts_with("Block #1 "));
+        assert!(printed.contains(&hash));
+        assert!(printed.contains("abababab..abababab"));
+    }
+
+    /// A hasher folding the data with XOR, to test that the blocks don't depend on SHA-512.
+    #[derive(Debug, Clone, PartialEq)]
+    struct XorHasher;
+
+    impl Hasher for XorHasher {
+        type State = ([u8; 64], usize);
+
+        fn new() -> Self::State {
+            ([0; 64], 0)
+        }
+
+        fn update(state: &mut Self::State, data: &[u8]) {
+            for byte in data {
+                state.0[state.1 % 64] ^= byte;
+                state.1 += 1;
+            }
+        }
+
+        fn finalize(state: Self::State) -> [u8; 64] {
+            state.0
+        }
+    }
+
+    #[test]
+    fn block_is_mined_with_the_given_hasher() {
+        let block = Block::<XorHasher>::with_hasher(1, [0; 64], Vec::new(), 4);
+
+        assert!(block.verify());
+        assert_eq!(block.hash(), XorHasher::hash(&block.header_bytes()));
+        assert_ne!(block.hash(), Sha512Hasher::hash(&block.header_bytes()));
+    }
+
+    #[test]
+    fn double_hash_differs_from_single_hash() {
+        let time = Utc.timestamp_opt(1_600_000_000, 0).unwrap();
+
+        let single = Block::<Sha512Hasher>::mined_at(1, [0; 64], Vec::new(), 4, time);
+        let double = Block::<DoubleSha512Hasher>::mined_at(1, [0; 64], Vec::new(), 4, time);
+
+        assert!(single.verify());
+        assert!(double.verify());
+        assert_eq!(double.hash(), DoubleSha512Hasher::hash(&double.header_bytes()));
+        assert_ne!(double.hash(), Sha512Hasher::hash(&double.header_bytes()));
+    }
+
+    #[test]
+    fn attempts_follow_the_nonce() {
+        let (block, stats) = Block::mine_with_stats(1, [0; 64], Vec::new(), 4);
+
+        assert_eq!(stats.attempts, block.nonce());
+        assert!(stats.elapsed < Duration::from_secs(60));
+    }
+
+    #[test]
+    fn same_block_has_one_id() {
+        let block = Block::new_with_difficulty(1, [0; 64], Vec::new(), 4);
+        let other = Block::new_with_difficulty(2, block.hash(), Vec::new(), 4);
+
+        let ids: std::collections::HashSet<BlockId> = [&block, &block.clone(), &other].iter().map(|block| block.id()).collect();
+
+        assert_eq!(ids.len(), 2);
+        assert_eq!(block.id().to_string(), hash_to_hex(&block.hash()));
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn binary_block_is_smaller_than_json() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(10);
+
+        let transactions = vec![
+            Transaction::coinbase(receiver.clone(), 50),
+            Transaction::new_with_fee(sender, receiver, 5, 1, "sender_password").unwrap(),
+        ];
+        let mut block = Block::new_with_difficulty(1, [0; 64], transactions, 4);
+        block.prune();
+
+        let bytes = block.to_bytes();
+
+        assert_eq!(Block::from_bytes(&bytes), Ok(block.clone()));
+        assert!(bytes.len() < serde_json::to_vec(&block).unwrap().len());
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn binary_layout_of_a_block_is_fixed() {
+        let time = Utc.timestamp_opt(1_600_000_000, 5).unwrap();
+        let block = Block::<Sha512Hasher>::from_parts(1, [3; 64], Vec::new(), 4, 6, time, [7; 64]);
+        let rfc3339 = "2020-09-13T12:26:40.000000005Z";
+
+        // the layout of `bincode`, with the times written as their RFC 3339 strings by `chrono`
+        let expected = [
+            &BLOCK_VERSION.to_le_bytes()[..],
+            &1u64.to_le_bytes(),
+            &[3; 64],
+            &0u64.to_le_bytes(), // the number of transactions
+            &6u128.to_le_bytes(),
+            &(rfc3339.len() as u64).to_le_bytes(),
+            rfc3339.as_bytes(),
+            &4u64.to_le_bytes(),
+            &[DEFAULT_TARGET],
+            &[7; 64],
+            &[0], // no pruned root
+            &0u64.to_le_bytes(), // no extra data
+            &0u32.to_le_bytes(),
+            &[0], // no producer
+        ].concat();
+
+        assert_eq!(block.to_bytes(), expected);
+        assert_eq!(Block::from_bytes(&expected), Ok(block));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn tampered_amount_is_detected() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(10);
+
+        let transaction = Transaction::new(sender, receiver, 5, "sender_password").unwrap();
+        let block = Block::new_with_difficulty(1, [0; 64], vec![transaction.clone()], 4);
+
+        let json = serde_json::to_string(&transaction).unwrap();
+        assert_eq!(json.matches("},5]]").count(), 1);
+
+        let tampered: Transaction = serde_json::from_str(&json.replace("},5]]", "},9]]")).unwrap();
+
+        assert!(serde_json::from_str::<Transaction>(&json).unwrap().verify_hash());
+        assert_eq!(tampered.amount(), 9);
+        assert!(!tampered.verify_hash());
+
+        let mut forged = block.clone();
+        forged.transactions[0] = tampered;
+
+        assert!(block.verify());
+        assert!(!forged.verify());
+    }
+
+    #[test]
+    fn empty_block_is_mined_and_verified() {
+        let mut block = Block::new_with_difficulty(1, [0; 64], Vec::new(), 8);
+
+        assert!(block.verify());
+        assert_eq!(block.merkle_root(), EMPTY_MERKLE_ROOT);
+        assert!(Block::default().verify());
+
+        block.prune();
+
+        assert!(block.verify());
+        assert_eq!(block.merkle_root(), EMPTY_MERKLE_ROOT);
+    }
+
+    #[test]
+    fn forged_signature_fails_the_batch() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(10);
+
+        let mut forged = Transaction::new(sender.clone(), receiver.clone(), 2, "sender_password").unwrap().with_nonce(1);
+        forged.sender = Some(receiver.clone());
+
+        let transactions = vec![
+            Transaction::coinbase(receiver.clone(), 50),
+            Transaction::new(sender, receiver, 1, "sender_password").unwrap(),
+            forged,
+        ];
+        let block = Block::new_with_difficulty(1, [0; 64], transactions, 4);
+
+        assert!(!block.verify_signatures_batch());
+    }
+
+    #[test]
+    fn achieved_difficulty_counts_leading_zeros() {
+        let time = Utc.timestamp_opt(1_600_000_000, 0).unwrap();
+        let with_hash = |hash| Block::from_parts(1, [0; 64], Vec::new(), 0, 0, time, hash);
+
+        let mut hash = [0xff; 64];
+        assert_eq!(with_hash(hash).achieved_difficulty(), 0);
+
+        hash[0] = 0b0001_0000;
+        assert_eq!(with_hash(hash).achieved_difficulty(), 3);
+
+        hash[0] = 0;
+        hash[1] = 0b0000_0001;
+        assert_eq!(with_hash(hash).achieved_difficulty(), 15);
+
+        assert_eq!(with_hash([0; 64]).achieved_difficulty(), 512);
+    }
+
+    #[test]
+    fn hash_ref_points_to_the_hash() {
+        let block = Block::new_with_difficulty(1, [0; 64], Vec::new(), 4);
+
+        assert!(std::ptr::eq(block.hash_ref(), &block.hash));
+        assert_eq!(block.hash_ref(), &block.hash());
+    }
+
+    #[test]
+    fn extra_data_changes_the_hash() {
+        let time = Utc.timestamp_opt(1_600_000_000, 0).unwrap();
+
+        let plain = Block::genesis(time);
+        let stamped = Block::genesis_with_extra_data(time, b"chain #1".to_vec());
+        let empty = Block::genesis_with_extra_data(time, Vec::new());
+
+        assert_ne!(plain.hash(), stamped.hash());
+        assert_ne!(stamped.hash(), Block::genesis_with_extra_data(time, b"chain #2".to_vec()).hash());
+        assert_eq!(empty, plain);
+        assert!(stamped.verify());
+        assert_eq!(stamped.size_bytes(), plain.size_bytes() + 1 + 8 + 8);
+    }
+
+    #[test]
+    fn found_nonce_reproduces_the_hash() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(10);
+
+        let transaction = Transaction::new(sender, receiver, 5, "sender_password").unwrap();
+        let block = Block::with_extra_data(1, [1; 64], vec![transaction], 8, b"pool #3".to_vec());
+
+        let prefix = block.header_digest_prefix();
+
+        assert_eq!(Block::finalize_with_nonce(&prefix, block.nonce()), block.hash());
+        assert_ne!(Block::finalize_with_nonce(&prefix, block.nonce() + 1), block.hash());
+        assert_eq!([&prefix[..], &block.nonce().to_le_bytes()[..]].concat(), block.header_bytes());
+    }
+
+    #[test]
+    fn threshold_compares_hashes_as_big_endian_integers() {
+        let mut target = [0; 64];
+        target[1] = 0x80;
+        target[63] = 0x01;
+
+        let mut below = target;
+        below[63] = 0x00;
+
+        let mut above = target;
+        above[63] = 0x02;
+
+        let mut above_by_a_leading_byte = [0; 64];
+        above_by_a_leading_byte[0] = 0x01;
+
+        assert!(Block::meets_target_threshold(&below, &target));
+        assert!(Block::meets_target_threshold(&target, &target));
+        assert!(!Block::meets_target_threshold(&above, &target));
+        assert!(!Block::meets_target_threshold(&above_by_a_leading_byte, &target));
+
+        let mut easy = [0xff; 64];
+        easy[0] = 0x3c;
+
+        let block = Block::new_with_threshold(1, [0; 64], Vec::new(), easy);
+
+        assert!(Block::meets_target_threshold(block.hash_ref(), &easy));
+        assert_eq!(block.difficulty(), 2);
+        assert!(block.verify());
+    }
+
+    #[test]
+    fn built_block_equals_the_block_mined_directly() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(10);
+
+        // the transactions are in the canonical order, with the coinbase first
+        let transactions = vec![
+            Transaction::coinbase(receiver.clone(), 1),
+            Transaction::new(sender, receiver, 5, "sender_password").unwrap(),
+        ];
+
+        let time = Utc::now();
+        let mut builder = BlockBuilder::new(1, [1; 64], 2).with_difficulty(8).with_time(time);
+
+        for transaction in transactions.clone() {
+            builder.push(transaction).unwrap();
+        }
+
+        assert!(builder.is_full());
+        assert_eq!(builder.build().unwrap(), Block::new_with_clock(1, [1; 64], transactions, 8, &FixedClock::new(time)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn unknown_versions_are_rejected() {
+        let block = Block::new_with_difficulty(1, [0; 64], Vec::new(), 4);
+
+        #[cfg(feature = "bincode")]
+        {
+            let bytes = block.to_bytes();
+
+            assert_eq!(Block::from_bytes(&bytes), Ok(block.clone()));
+
+            let mut newer = bytes;
+            newer[..2].copy_from_slice(&7u16.to_le_bytes());
+
+            assert_eq!(Block::<Sha512Hasher>::from_bytes(&newer), Err(DecodeError::UnsupportedVersion(7)));
+        }
+
+        let json = serde_json::to_string(&block).unwrap();
+        let deserialized: Block = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(deserialized.version(), BLOCK_VERSION);
+        assert!(deserialized.verify());
+
+        let older: Block = serde_json::from_str(&json.replace("\"version\":3", "\"version\":1")).unwrap();
+
+        assert_eq!(older.version(), 1);
+
+        let error = serde_json::from_str::<Block>(&json.replace("\"version\":3", "\"version\":7")).unwrap_err();
+
+        assert!(error.to_string().contains("Unsupported block version 7"));
+    }
+
+    #[test]
+    fn progress_is_reported_until_the_block_is_mined() {
+        let time = Utc.timestamp_opt(1_600_000_000, 0).unwrap();
+        let mut block: Block = Block::unmined(1, [0; 64], Vec::new(), 12, time);
+        let mut reports = Vec::new();
+
+        block.search_nonce_with_progress(u128::MAX, 1, None, |hash| satisfies(hash, 12, 0), 100, &mut |attempts| reports.push(attempts))
+            .unwrap();
+
+        // the nonces from 0 to `nonce - 1` failed, and the progress is reported every 100 of them
+        let expected: Vec<u128> = (1..=block.nonce() / 100).map(|i| i * 100).collect();
+
+        assert!(!reports.is_empty());
+        assert_eq!(reports, expected);
+        assert!(block.verify());
+    }
+
+    #[test]
+    fn assembled_block_mined_in_place_equals_the_mined_block() {
+        let miner = Account::new("Miner", "Test", "miner_password").unwrap();
+        let transactions = vec![Transaction::coinbase(miner, 5)];
+        let time = Utc::now();
+
+        let mut block = Block::assemble_unmined(1, [1; 64], transactions.clone(), DEFAULT_DIFFICULTY, time);
+
+        assert_eq!((block.nonce(), block.hash()), (0, [0; 64]));
+
+        block.mine_in_place();
+
+        assert_eq!(block, Block::new_with_clock(1, [1; 64], transactions, DEFAULT_DIFFICULTY, &FixedClock::new(time)));
+        assert!(block.verify());
+    }
+
+    #[test]
+    fn blocks_assembled_in_any_order_have_the_same_hash() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(10);
+
+        let transactions: Vec<Transaction> = (0..4)
+            .map(|nonce| Transaction::new(sender.clone(), receiver.clone(), 1, "sender_password").unwrap().with_nonce(nonce))
+            .chain(std::iter::once(Transaction::coinbase(receiver.clone(), 5)))
+            .collect();
+
+        let time = Utc::now();
+        let assemble = |transactions: &[Transaction]| {
+            let mut builder = BlockBuilder::new(1, [1; 64], 5).with_difficulty(4).with_time(time);
+
+            for transaction in transactions {
+                builder.push(transaction.clone()).unwrap();
+            }
+
+            builder.build().unwrap()
+        };
+
+        let mut reversed = transactions.clone();
+        reversed.reverse();
+
+        let block = assemble(&transactions);
+
+        assert_eq!(block.hash(), assemble(&reversed).hash());
+        assert!(block.is_canonically_ordered());
+        assert!(block.transactions()[0].is_coinbase());
+    }
+
+    #[test]
+    fn optional_fields_of_the_header_are_not_ambiguous() {
+        let time = Utc.timestamp_opt(1_600_000_000, 0).unwrap();
+
+        // the producer holds the bytes of the chain identifier and of the extra data of the other block
+        let mut producer = [0xab; 32];
+        producer[..4].copy_from_slice(&7u32.to_le_bytes());
+        producer[4..12].copy_from_slice(&20u64.to_le_bytes());
+
+        let mut produced = Block::genesis(time);
+        produced.producer = Some(producer);
+
+        let mut stamped = Block::genesis_with_extra_data(time, vec![0xab; 20]);
+        stamped.chain_id = 7;
+
+        assert_ne!(produced.header_bytes(), stamped.header_bytes());
+        assert_ne!(produced.digest(&produced.merkle_root()), stamped.digest(&stamped.merkle_root()));
+    }
+
+    #[test]
+    fn canonical_order_is_required_from_its_version() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(10);
+
+        let transactions: Vec<Transaction> = (0..3)
+            .map(|nonce| Transaction::new(sender.clone(), receiver.clone(), 1, "sender_password").unwrap().with_nonce(nonce))
+            .rev()
+            .collect();
+
+        let mut block = Block::assemble_unmined(1, [1; 64], transactions, 4, Utc::now());
+
+        // the transactions of a sender are sorted by nonce
+        assert_eq!(block.transactions().iter().map(Transaction::nonce).collect::<Vec<u64>>(), vec![0, 1, 2]);
+        assert!(block.has_required_order());
+
+        block.transactions.reverse();
+        block.mine_in_place();
+
+        assert!(!block.has_required_order());
+
+        // the blocks of the first version were sorted by time, so their order isn't checked
+        block.version = 1;
+        block.mine_in_place();
+
+        assert!(!block.is_canonically_ordered());
+        assert!(block.has_required_order());
+        assert!(block.verify());
+    }
+
+    #[test]
+    fn proof_of_work_is_hashed_from_its_version() {
+        let block = Block::new_with_difficulty(1, [1; 64], Vec::new(), 4);
+
+        let mut forged = block.clone();
+        forged.target = forged.hash[0];
+        forged.difficulty += 8;
+
+        assert!(!forged.verify());
+
+        // without the proof of work in the header, the target byte gives 8 bits of work for free
+        let mut older = block;
+        older.version = PROOF_OF_WORK_VERSION - 1;
+        older.mine_in_place();
+
+        while matching_bits(&older.hash, older.hash[0]) < older.difficulty + 8 {
+            older.nonce += 1;
+            older.hash = older.digest(&older.merkle_root());
+        }
+
+        let digest = older.digest(&older.merkle_root());
+        older.target = older.hash[0];
+        older.difficulty += 8;
+
+        assert_eq!(older.digest(&older.merkle_root()), digest);
+        assert!(older.verify());
+    }
+}
@@ -1,138 +1,5490 @@
-use crate::{
-    account::Account,
-    transaction::{Transaction, ValidationError},
-    block::Block,
-};
-
-/// A struct to handle the blockchain of the currency.
-/// 
-/// The treansaction contains:
-/// - the index of the last block put in the chain
-/// - the chain of `Block`s
-/// - the pending transactions, already validated, waiting to be put in a new block
-/// - the number of transactions per block
-/// 
-/// When the blockchain is created, it comes with the genesis block already put in the chain,
-/// and the genesis is derived from the `Default` implementation of the `Block`.
-#[derive(Debug, Clone, PartialEq)]
-pub struct BlockChain {
-    pub index: usize,
-    chain: Vec<Block>,
-    transactions: Vec<Transaction>,
-    transactions_per_block: usize,
-}
-
-impl BlockChain {
-    /// Generates a new `BlockChain`.
-    /// 
-    /// # Example
-    /// ```
-    /// # use blockchain::blockchain::BlockChain;
-    /// let blockchain = BlockChain::new(5); // here you can choose the number of transactions per block
-    /// 
-    /// assert_eq!(blockchain.chain().len(), 1); // the blockchain starts with the genesis block
-    /// ```
-    pub fn new(transactions_per_block: usize) -> Self {
-        let genesis_block = Block::default();
-
-        Self {
-            index: 0,
-            chain: vec![genesis_block],
-            transactions: Vec::new(),
-            transactions_per_block,
-        }
-    }
-
-    /// This method creates a transaction with the arguments, and then this transaction is checked:
-    /// if it's a valid transaction, it goes into the `Vec<Transaction>` pending transactions vector,
-    /// and the amount is transferred from the sender's `Account` into the receiver's `Account`;
-    /// if the transaction isn't valid, details are provided.
-    /// 
-    /// When the number of pending transactions is equal to the number of `transactions_per_block`,
-    /// set while creating the blockchain, a new `Block` is generated.
-    /// 
-    /// # Example
-    /// ```
-    /// # use blockchain::blockchain::BlockChain;
-    /// # use blockchain::account::Account;
-    /// let mut alex = Account::new("Alex", "White", "1992#?I_like_Rust92");
-    /// let mut bob = Account::new("Bob", "Reds", "sUpEr_SeCuRe_PaSsWoRd#+!789");
-    /// alex.add_money(100.0); // alex must have enough money to perform the transaction!
-    /// 
-    /// let mut blockchain = BlockChain::new(1); // the number of transactions per block is set to 1
-    /// blockchain.push_transaction(&mut alex, &mut bob, 50.0, "1992#?I_like_Rust92"); // the chain is going to have two blocks, the first one being the genesis block
-    /// 
-    /// assert_eq!(blockchain.index, 1); // the genesis block has index #0
-    /// ```
-    pub fn push_transaction(&mut self, sender: &mut Account, receiver: &mut Account, amount: f64, sender_password: &str) {
-        let transaction = Transaction::new(sender.clone(), receiver.clone(), amount, sender_password);
-
-        println!("Validating transaction...");
-
-        match transaction.validate(transaction.hash()) {
-            Ok(_) => {
-                self.transactions.push(transaction);
-
-                // the amount is checked in the validation of the transaction
-                unsafe {
-                    sender.sub_money_unchecked(amount);
-                    receiver.add_money_unchecked(amount);
-                }
-
-                println!("validated!");
-            },
-            Err(e) => match e {
-                ValidationError::Tempered => eprintln!("{} Details: transaction from {} to {}, for an amount of {}, resulted to be tempered.",
-                    e,
-                    transaction.sender,
-                    transaction.receiver,
-                    transaction.amount(),
-                ),
-                ValidationError::WrongPassword => eprintln!("{} Details: the sender's password is not correct.", e),
-                ValidationError::InvalidSignature => eprintln!("{} Details: transaction from {} to {}, for an amount of {}, wasn't validated because of invalid signature.",
-                    e,
-                    transaction.sender,
-                    transaction.receiver,
-                    transaction.amount(),
-                ),
-                ValidationError::InvalidAmount => eprintln!("{} Details: transaction from {} to {}, for an amount of {}, wasn't validated because of an invalid amount.",
-                    e,
-                    transaction.sender,
-                    transaction.receiver,
-                    transaction.amount(),
-                ),
-            },
-        };
-
-        if self.transactions.len() == self.transactions_per_block {
-            self.index += 1;
-
-            println!("Validating block...");
-
-            let new_block = Block::new(
-                self.index,
-                self.chain.last().unwrap().hash(),
-                self.transactions.clone()
-            );
-
-            self.chain.push(new_block);
-
-            self.transactions.clear();
-
-            println!("validated!");
-        }
-    }
-    
-    /// This method returns the `chain` of the blockchain, since this field isn't `pub`.
-    /// 
-    /// # Example
-    /// ```
-    /// # use blockchain::blockchain::BlockChain;
-    /// let blockchain = BlockChain::new(8);
-    /// 
-    /// assert_eq!(blockchain.chain().len(), 1); // the blockchain starts with the genesis block
-    /// ```
-    pub fn chain(&self) -> Vec<Block> {
-        self.chain.clone()
-    }
-}
+use crate::{
+    account::Account,
+    amount::{Amount, Balance},
+    transaction::{Transaction, ValidationError},
+    block::{Block, MiningError, BLOCK_VERSION, DEFAULT_DIFFICULTY, DEFAULT_TARGET},
+    error::Error,
+    store::ChainStore,
+    header::HeaderChain,
+    hasher::ProofOfWork,
+    clock::{Clock, SystemClock},
+};
+use std::{collections::{HashMap, HashSet}, fmt, error, io, sync::Arc, time::Duration};
+use chrono::{DateTime, Utc};
+use rayon::prelude::*;
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+#[cfg(feature = "serde")]
+use std::{fs, io::{BufRead, Write}, path::Path};
+
+/// The default maximum time a block can be ahead of the current time, which is 2 hours.
+pub const DEFAULT_MAX_FUTURE_DRIFT: Duration = Duration::from_secs(2 * 60 * 60);
+
+/// The default time after which a pending transaction expires, which is 2 weeks.
+pub const DEFAULT_MEMPOOL_TTL: Duration = Duration::from_secs(14 * 24 * 60 * 60);
+
+/// The default maximum length of the memo of a transaction, in bytes.
+pub const DEFAULT_MAX_MEMO_LEN: usize = 256;
+
+/// The number of transactions per block of a `BlockChain` generated by a `BlockChainBuilder`, unless it's changed.
+pub const DEFAULT_TRANSACTIONS_PER_BLOCK: usize = 5;
+
+/// The maximum number of orphan blocks kept by a `BlockChain`; when there are more, the oldest ones are dropped.
+pub const MAX_ORPHANS: usize = 100;
+
+/// The number of the last blocks of the chain whose fees are used by `BlockChain::estimate_fee()`.
+pub const FEE_ESTIMATE_BLOCKS: usize = 10;
+
+/// The number of the last blocks of the chain whose average time is used by `BlockChain::estimated_hashrate()`.
+pub const HASHRATE_ESTIMATE_BLOCKS: usize = 10;
+
+/// A struct to handle the blockchain of the currency.
+/// 
+/// The treansaction contains:
+/// - the index of the last block put in the chain
+/// - the chain of `Block`s
+/// - the pending transactions, already validated, waiting to be put in a new block
+/// - the number of transactions per block
+/// - the difficulty used to mine the new blocks
+/// - the miner's `Account`, if any, rewarded for every block mined with `mine_pending()`
+/// - the reward given to the miner for every block
+/// - the number of blocks after which the block reward is halved
+/// - the number of blocks after which the difficulty is adjusted, and the target time between two blocks
+/// - how far in the future the time of a block can be, compared to the current time
+/// - the maximum size of a block mined with `mine_pending()`, in bytes
+/// - the number of blocks after which the coinbase rewards can be spent
+/// - the time after which a pending transaction expires, see `evict_expired()`
+/// - the maximum amount of a transaction
+/// - the minimum fee of a pending transaction
+/// - the maximum length of the memo of a transaction, in bytes
+/// - the maximum number of blocks `replace_chain()` can remove, see `set_max_reorg_depth()`
+/// - the identifier of the chain, hashed with the blocks and signed with the transactions, see `new_with_chain_id()`
+/// - the orphan blocks, received before their parent, which aren't serialized
+/// - the callbacks registered with `on_block()`, `on_reorg()` and `on_retarget()`, which aren't serialized nor cloned
+/// - the `ChainStore` the blocks are written to, see `set_store()`, which isn't serialized nor cloned
+/// - the `Clock` used to mine and to validate the blocks, see `set_clock()`, which isn't serialized
+/// 
+/// When the blockchain is created, it comes with the genesis block already put in the chain,
+/// and the genesis is derived from the `Default` implementation of the `Block`;
+/// the genesis block can also pre-fund some accounts, see `with_genesis_allocations()`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BlockChain {
+    pub index: usize,
+    chain: Vec<Block>,
+    transactions: Vec<Transaction>,
+    transactions_per_block: usize,
+    difficulty: usize,
+    // the difficulty the chain was generated with, required from the block #1 when replaying a chain
+    #[cfg_attr(feature = "serde", serde(default))]
+    initial_difficulty: usize,
+    miner: Option<Account>,
+    block_reward: Amount,
+    halving_interval: usize,
+    adjustment_window: usize,
+    target_block_time: Duration,
+    max_future_drift: Duration,
+    max_block_bytes: usize,
+    coinbase_maturity: usize,
+    mempool_ttl: Duration,
+    max_tx_amount: Amount,
+    min_fee: Amount,
+    max_memo_len: usize,
+    max_reorg_depth: usize,
+    #[cfg_attr(feature = "serde", serde(default))]
+    chain_id: u32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    proof_of_work: ProofOfWork,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    orphans: Vec<Block>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    observers: Observers,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    store: Store,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    clock: ChainClock,
+}
+
+impl BlockChain {
+    /// Generates a new `BlockChain`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let blockchain = BlockChain::new(5); // here you can choose the number of transactions per block
+    /// 
+    /// assert_eq!(blockchain.chain().len(), 1); // the blockchain starts with the genesis block
+    /// ```
+    pub fn new(transactions_per_block: usize) -> Self {
+        BlockChain::new_with_difficulty(transactions_per_block, DEFAULT_DIFFICULTY)
+    }
+
+    /// Returns a `BlockChainBuilder` to configure a new `BlockChain` before generating its genesis block.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let blockchain = BlockChain::builder().difficulty(4).chain_id(3).build().unwrap();
+    /// 
+    /// assert_eq!(blockchain.chain_id(), 3);
+    /// ```
+    pub fn builder() -> BlockChainBuilder {
+        BlockChainBuilder::default()
+    }
+
+    /// Generates a new `BlockChain`, whose blocks (including the genesis block) are mined with the given difficulty.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// 
+    /// assert_eq!(blockchain.chain()[0].difficulty(), 4);
+    /// ```
+    pub fn new_with_difficulty(transactions_per_block: usize, difficulty: usize) -> Self {
+        BlockChain::with_genesis_allocations(transactions_per_block, difficulty, Vec::new())
+    }
+
+    /// Generates a new `BlockChain` without proof of work, e.g. for tests: its blocks (including the genesis block)
+    /// are mined with a difficulty of `0`, which every hash meets, so every block is mined instantly with the first nonce,
+    /// and the blocks still verify; it's the same as `new_with_difficulty(transactions_per_block, 0)`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut blockchain = BlockChain::test_mode(5);
+    /// blockchain.mine_n_empty(10).unwrap();
+    /// 
+    /// assert!(blockchain.chain().iter().all(|block| block.nonce() == 0));
+    /// assert!(blockchain.is_valid());
+    /// ```
+    pub fn test_mode(transactions_per_block: usize) -> Self {
+        BlockChain::new_with_difficulty(transactions_per_block, 0)
+    }
+
+    /// Generates a new `BlockChain` whose genesis block is mined with the given `genesis_difficulty`,
+    /// while the other blocks are mined with the given `difficulty`; with a `genesis_difficulty` of `0`
+    /// the genesis block needs no proof of work, so the blockchain is created immediately.
+    /// 
+    /// Every block stores the difficulty it was mined with, so the chain is validated
+    /// against the difficulty of each block.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut blockchain = BlockChain::new_with_genesis_difficulty(5, 0, 4);
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// assert_eq!(blockchain.chain()[0].difficulty(), 0);
+    /// assert_eq!(blockchain.chain()[1].difficulty(), 4);
+    /// assert!(blockchain.is_valid());
+    /// ```
+    pub fn new_with_genesis_difficulty(transactions_per_block: usize, genesis_difficulty: usize, difficulty: usize) -> Self {
+        let genesis_block = Block::new_with_difficulty(0, [0; 64], Vec::new(), genesis_difficulty);
+
+        BlockChain::with_genesis_block(transactions_per_block, difficulty, genesis_block)
+    }
+
+    /// Generates a new `BlockChain` with the given chain identifier, whose blocks (including the genesis block)
+    /// are mined with the given difficulty; the chains created with the other functions have an identifier of `0`.
+    /// 
+    /// The identifier is hashed with every block, and the chain accepts only the transactions signed for it,
+    /// see `Transaction::with_chain_id()`, so the blocks and the transactions of a chain can't be replayed on another chain.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut blockchain = BlockChain::new_with_chain_id(5, 4, 7);
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// assert_eq!(blockchain.chain_id(), 7);
+    /// assert!(blockchain.chain().iter().all(|block| block.chain_id() == 7));
+    /// assert!(blockchain.is_valid());
+    /// ```
+    pub fn new_with_chain_id(transactions_per_block: usize, difficulty: usize, chain_id: u32) -> Self {
+        let genesis_block = Block::new_on_chain(0, [0; 64], Vec::new(), difficulty, chain_id);
+
+        BlockChain::with_genesis_block(transactions_per_block, difficulty, genesis_block)
+    }
+
+    /// Generates a new `BlockChain` like `new_with_difficulty()`, which takes the time from the given `Clock`
+    /// instead of the system clock, see `set_clock()`; the genesis block is mined at the time of the clock.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::clock::MockClock;
+    /// use chrono::{Duration, TimeZone, Utc};
+    /// use std::sync::Arc;
+    /// 
+    /// let time = Utc.with_ymd_and_hms(2021, 3, 14, 0, 0, 0).unwrap();
+    /// let clock = Arc::new(MockClock::new(time));
+    /// 
+    /// let mut blockchain = BlockChain::new_with_clock(5, 4, clock.clone());
+    /// 
+    /// clock.advance(Duration::minutes(1));
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// assert_eq!(blockchain.chain()[0].time(), time);
+    /// assert_eq!(blockchain.chain()[1].time(), time + Duration::minutes(1));
+    /// ```
+    pub fn new_with_clock(transactions_per_block: usize, difficulty: usize, clock: Arc<dyn Clock>) -> Self {
+        let genesis_block = Block::mined_at(0, [0; 64], Vec::new(), difficulty, clock.now());
+
+        let mut blockchain = BlockChain::with_genesis_block(transactions_per_block, difficulty, genesis_block);
+        blockchain.set_clock(clock);
+
+        blockchain
+    }
+
+    /// Generates a new `BlockChain` whose genesis block pre-funds the given accounts:
+    /// every allocation is put in the genesis block as a coinbase transaction,
+    /// so `balance_of()` counts it as the starting funds of the account.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::account::Account;
+    /// let wren = Account::new("Wren", "Abbott", "wr3n_4bb0tt").unwrap();
+    /// let yara = Account::new("Yara", "Bishop", "yara.bishop.pw").unwrap();
+    /// 
+    /// let blockchain = BlockChain::with_genesis_allocations(5, 4, vec![(wren.clone(), 100), (yara.clone(), 20)]);
+    /// 
+    /// assert_eq!(blockchain.balance_of(&wren), 100);
+    /// assert_eq!(blockchain.balance_of(&yara), 20);
+    /// assert_eq!(blockchain.chain()[0].transactions().len(), 2);
+    /// ```
+    pub fn with_genesis_allocations(transactions_per_block: usize, difficulty: usize, allocations: Vec<(Account, Amount)>) -> Self {
+        let allocations = allocations
+            .into_iter()
+            .map(|(account, amount)| Transaction::coinbase(account, amount))
+            .collect();
+
+        let genesis_block = Block::new_with_difficulty(0, [0; 64], allocations, difficulty);
+
+        BlockChain::with_genesis_block(transactions_per_block, difficulty, genesis_block)
+    }
+
+    /// Generates a new `BlockChain` starting with the given genesis block.
+    fn with_genesis_block(transactions_per_block: usize, difficulty: usize, genesis_block: Block) -> Self {
+        let chain_id = genesis_block.chain_id();
+
+        Self {
+            index: 0,
+            chain: vec![genesis_block],
+            transactions: Vec::new(),
+            transactions_per_block,
+            difficulty,
+            initial_difficulty: difficulty,
+            miner: None,
+            block_reward: 0,
+            halving_interval: 0,
+            adjustment_window: 0,
+            target_block_time: Duration::from_secs(0),
+            max_future_drift: DEFAULT_MAX_FUTURE_DRIFT,
+            max_block_bytes: usize::MAX,
+            coinbase_maturity: 0,
+            mempool_ttl: DEFAULT_MEMPOOL_TTL,
+            max_tx_amount: Amount::MAX,
+            min_fee: 0,
+            max_memo_len: DEFAULT_MAX_MEMO_LEN,
+            max_reorg_depth: usize::MAX,
+            chain_id,
+            proof_of_work: ProofOfWork::Sha512,
+            orphans: Vec::new(),
+            observers: Observers::default(),
+            store: Store::default(),
+            clock: ChainClock::default(),
+        }
+    }
+
+    /// Generates a new `BlockChain` with the blocks of the given `ChainStore`, and keeps writing
+    /// the new blocks to the store, see `set_store()`; if the store is empty, the chain starts with
+    /// a new genesis block mined with the given difficulty, which is written to the store.
+    /// 
+    /// If the blocks of the store aren't a valid chain, an `io::ErrorKind::InvalidData` error is returned.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::store::{ChainStore, InMemoryChainStore};
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// let mut store = InMemoryChainStore::new();
+    /// 
+    /// for block in blockchain.chain() {
+    ///     store.append_block(&block).unwrap();
+    /// }
+    /// 
+    /// let loaded = BlockChain::from_store(5, 4, Box::new(store)).unwrap();
+    /// 
+    /// assert_eq!(loaded.chain(), blockchain.chain());
+    /// ```
+    pub fn from_store(transactions_per_block: usize, difficulty: usize, store: Box<dyn ChainStore + Send + Sync>) -> io::Result<Self> {
+        let chain = store.load_blocks()?;
+
+        let mut blockchain = match chain.first() {
+            None => BlockChain::new_with_difficulty(transactions_per_block, difficulty),
+            Some(genesis) => {
+                if !is_valid_chain(&chain, Utc::now(), DEFAULT_MAX_FUTURE_DRIFT, genesis.chain_id(), ProofOfWork::Sha512) {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "The blockchain isn't valid."));
+                }
+
+                let mut blockchain = BlockChain::with_genesis_block(transactions_per_block, difficulty, genesis.clone());
+
+                blockchain.index = chain.len() - 1;
+                blockchain.chain = chain;
+
+                blockchain
+            },
+        };
+
+        blockchain.set_store(store)?;
+
+        Ok(blockchain)
+    }
+
+    /// This method sets the miner's `Account`, which receives a coinbase transaction
+    /// with the block reward in every block mined with `mine_pending()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::account::Account;
+    /// let miner = Account::new("Dario", "Fo", "d4r10_f0_m1n3r").unwrap();
+    /// 
+    /// let mut blockchain = BlockChain::new_with_difficulty(2, 4);
+    /// blockchain.set_miner(miner.clone());
+    /// blockchain.set_block_reward(50);
+    /// 
+    /// blockchain.mine_pending().unwrap();
+    /// 
+    /// assert_eq!(blockchain.balance_of(&miner), 50);
+    /// ```
+    pub fn set_miner(&mut self, miner: Account) {
+        self.miner = Some(miner);
+    }
+
+    /// This method sets the number of transactions per block, set while creating the blockchain,
+    /// for the blocks mined from now on; the blocks already in the chain aren't changed.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let mut kai = Account::new("Kai", "Lund", "k41_lund").unwrap();
+    /// let lia = Account::new("Lia", "Moss", "lia.moss.pw").unwrap();
+    /// kai.add_money(10);
+    /// 
+    /// let mut blockchain = BlockChain::with_genesis_allocations(1, 4, vec![(kai.clone(), 10)]);
+    /// 
+    /// for nonce in 0..3 {
+    ///     blockchain.add_pending(Transaction::new(kai.clone(), lia.clone(), 1, "k41_lund").unwrap().with_nonce(nonce)).unwrap();
+    /// }
+    /// 
+    /// blockchain.set_transactions_per_block(3);
+    /// blockchain.mine_pending().unwrap();
+    /// 
+    /// assert_eq!(blockchain.chain()[1].transactions().len(), 3);
+    /// ```
+    pub fn set_transactions_per_block(&mut self, transactions_per_block: usize) {
+        self.transactions_per_block = transactions_per_block;
+    }
+
+    /// This method sets the reward given to the miner for every block mined with `mine_pending()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::account::Account;
+    /// let miner = Account::new("Elsa", "Berg", "elsa_BERG_m1n3s").unwrap();
+    /// 
+    /// let mut blockchain = BlockChain::new_with_difficulty(2, 4);
+    /// blockchain.set_miner(miner.clone());
+    /// blockchain.set_block_reward(25);
+    /// 
+    /// blockchain.mine_pending().unwrap();
+    /// blockchain.mine_pending().unwrap();
+    /// 
+    /// assert_eq!(blockchain.balance_of(&miner), 50);
+    /// ```
+    pub fn set_block_reward(&mut self, block_reward: Amount) {
+        self.block_reward = block_reward;
+    }
+
+    /// This method sets the number of blocks after which the block reward is halved;
+    /// if it's `0` (the default), the block reward is never halved.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::account::Account;
+    /// let miner = Account::new("Sven", "Olsen", "sv3n_0ls3n").unwrap();
+    /// 
+    /// let mut blockchain = BlockChain::new_with_difficulty(2, 4);
+    /// blockchain.set_miner(miner.clone());
+    /// blockchain.set_block_reward(40);
+    /// blockchain.set_halving_interval(2);
+    /// 
+    /// blockchain.mine_pending().unwrap(); // block #1
+    /// blockchain.mine_pending().unwrap(); // block #2, the reward is halved
+    /// 
+    /// assert_eq!(blockchain.balance_of(&miner), 60); // 40 + 20 = 60
+    /// ```
+    pub fn set_halving_interval(&mut self, halving_interval: usize) {
+        self.halving_interval = halving_interval;
+    }
+
+    /// This method returns the reward given to the miner for the next block,
+    /// which is the block reward halved once every `halving_interval` blocks.
+    /// 
+    /// Like in Bitcoin, the reward is rounded down to a whole unit, so it eventually becomes `0`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut blockchain = BlockChain::new_with_difficulty(2, 4);
+    /// blockchain.set_block_reward(50);
+    /// blockchain.set_halving_interval(2);
+    /// 
+    /// assert_eq!(blockchain.current_reward(), 50); // the next block is the block #1
+    /// 
+    /// blockchain.mine_pending().unwrap();
+    /// 
+    /// assert_eq!(blockchain.current_reward(), 25); // the next block is the block #2
+    /// ```
+    pub fn current_reward(&self) -> Amount {
+        self.reward_at(self.index + 1)
+    }
+
+    /// This method returns the reward given to the miner for the block with the given index.
+    fn reward_at(&self, index: usize) -> Amount {
+        if self.halving_interval == 0 {
+            return self.block_reward;
+        }
+
+        match index / self.halving_interval {
+            halvings if halvings < 64 => self.block_reward >> halvings,
+            _ => 0,
+        }
+    }
+
+    /// This method creates a transaction with the arguments, and then this transaction is checked:
+    /// if it's a valid transaction, it goes into the `Vec<Transaction>` pending transactions vector,
+    /// and the amount is transferred from the sender's `Account` into the receiver's `Account`;
+    /// if the transaction isn't valid, details are provided.
+    /// 
+    /// When the number of pending transactions reaches the number of `transactions_per_block`,
+    /// set while creating the blockchain or with `set_transactions_per_block()`, a new `Block` is generated.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::account::Account;
+    /// let mut alex = Account::new("Alex", "White", "1992#?I_like_Rust92").unwrap();
+    /// let mut bob = Account::new("Bob", "Reds", "sUpEr_SeCuRe_PaSsWoRd#+!789").unwrap();
+    /// alex.add_money(100); // alex must have enough money to perform the transaction!
+    /// 
+    /// let mut blockchain = BlockChain::with_genesis_allocations(1, 4, vec![(alex.clone(), 100)]); // the number of transactions per block is set to 1
+    /// blockchain.push_transaction(&mut alex, &mut bob, 50, "1992#?I_like_Rust92"); // the chain is going to have two blocks, the first one being the genesis block
+    /// 
+    /// assert_eq!(blockchain.index, 1); // the genesis block has index #0
+    /// ```
+    pub fn push_transaction(&mut self, sender: &mut Account, receiver: &mut Account, amount: Amount, sender_password: &str) {
+        let transaction = match Transaction::new(sender.clone(), receiver.clone(), amount, sender_password) {
+            Ok(transaction) => transaction.with_nonce(self.next_nonce(sender)).with_chain_id(self.chain_id),
+            Err(e) => {
+                eprintln!("{} Details: the sender's password is not correct.", e);
+
+                return;
+            },
+        };
+
+        println!("Validating transaction...");
+
+        match self.check_pending(&transaction) {
+            Ok(_) => {
+                self.transactions.push(transaction);
+
+                // the amount is checked in the validation of the transaction
+                unsafe {
+                    sender.sub_money_unchecked(amount);
+                    receiver.add_money_unchecked(amount);
+                }
+
+                println!("validated!");
+            },
+            Err(e) => match e {
+                ValidationError::Tempered => eprintln!("{} Details: transaction from {} to {}, for an amount of {}, resulted to be tempered.",
+                    e,
+                    sender,
+                    receiver,
+                    transaction.amount(),
+                ),
+                ValidationError::WrongPassword => eprintln!("{} Details: the sender's password is not correct.", e),
+                ValidationError::InvalidSignature => eprintln!("{} Details: transaction from {} to {}, for an amount of {}, wasn't validated because of invalid signature.",
+                    e,
+                    sender,
+                    receiver,
+                    transaction.amount(),
+                ),
+                ValidationError::InvalidNonce => eprintln!("{} Details: the transaction was already put in the chain.", e),
+                ValidationError::ImmatureCoinbase => eprintln!("{} Details: the sender can't spend the rewards which aren't mature yet.", e),
+                ValidationError::AmountTooLarge => eprintln!("{} Details: the amount of the transaction is over the maximum amount of the chain.", e),
+                ValidationError::WrongChain => eprintln!("{} Details: the transaction is signed for another chain.", e),
+                ValidationError::FeeTooLow => eprintln!("{} Details: the fee of the transaction is under the minimum fee of the chain.", e),
+                ValidationError::MemoTooLong => eprintln!("{} Details: the memo of the transaction is longer than the maximum length of the chain.", e),
+                ValidationError::UnknownSender => eprintln!("{} Details: the sender never received money in the chain.", e),
+                ValidationError::InvalidAmount => eprintln!("{} Details: transaction from {} to {}, for an amount of {}, wasn't validated because of an invalid amount.",
+                    e,
+                    sender,
+                    receiver,
+                    transaction.amount(),
+                ),
+            },
+        };
+
+        if self.transactions.len() >= self.transactions_per_block {
+            println!("Validating block...");
+
+            match self.mine_pending() {
+                Ok(_) => println!("validated!"),
+                Err(e) => eprintln!("{} Details: the pending transactions are left waiting for the next block.", e),
+            }
+        }
+    }
+
+    /// This method returns the current difficulty of the chain, since the `difficulty` field isn't `pub`:
+    /// the blocks mined by the chain, e.g. with `add_block()` or `mine_pending()`, record the difficulty
+    /// they're mined with, which is the one returned by `next_difficulty()`, and it becomes the current difficulty.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut blockchain = BlockChain::new_with_difficulty(2, 4);
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// assert_eq!(blockchain.difficulty(), 4);
+    /// assert_eq!(blockchain.tip().difficulty(), blockchain.difficulty());
+    /// ```
+    pub fn difficulty(&self) -> usize {
+        self.difficulty
+    }
+
+    /// This method sets the difficulty of the chain, which the next blocks are mined with,
+    /// unless the difficulty adjustment changes it, see `set_difficulty_adjustment()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut blockchain = BlockChain::new_with_difficulty(2, 4);
+    /// blockchain.set_difficulty(6);
+    /// 
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// assert_eq!(blockchain.tip().difficulty(), 6);
+    /// ```
+    pub fn set_difficulty(&mut self, difficulty: usize) {
+        self.difficulty = difficulty;
+    }
+
+    /// This method sets the difficulty adjustment: every `adjustment_window` blocks, the time it took
+    /// to mine the last `adjustment_window` blocks is compared with the `target_block_time` between two blocks,
+    /// and the difficulty is adjusted; if `adjustment_window` is `0` (the default), the difficulty is never adjusted.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// use std::time::Duration;
+    /// 
+    /// let mut blockchain = BlockChain::new_with_difficulty(2, 2);
+    /// blockchain.set_difficulty_adjustment(2, Duration::from_secs(600));
+    /// 
+    /// for _ in 0..3 {
+    ///     blockchain.add_block(Vec::new()).unwrap();
+    /// }
+    /// 
+    /// assert_eq!(blockchain.next_difficulty(), 3); // the blocks were mined way faster than 10 minutes each
+    /// ```
+    pub fn set_difficulty_adjustment(&mut self, adjustment_window: usize, target_block_time: Duration) {
+        self.adjustment_window = adjustment_window;
+        self.target_block_time = target_block_time;
+    }
+
+    /// This method returns the difficulty that will be used to mine the next block.
+    /// 
+    /// When the index of the next block is a multiple of the `adjustment_window`, the time elapsed
+    /// between the last block and the block `adjustment_window` blocks before it is compared with the
+    /// expected time (`adjustment_window` times the `target_block_time`):
+    /// - if the blocks were mined in less than half the expected time, the difficulty is increased by one bit
+    /// - if the blocks were mined in more than twice the expected time, the difficulty is decreased by one bit
+    /// 
+    /// Since every bit doubles the work needed to mine a block, the difficulty is only adjusted when
+    /// the time is off by at least a factor of two, and it's never adjusted below `1`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let blockchain = BlockChain::new_with_difficulty(2, 4);
+    /// 
+    /// assert_eq!(blockchain.next_difficulty(), 4); // the difficulty adjustment is disabled by default
+    /// ```
+    pub fn next_difficulty(&self) -> usize {
+        self.difficulty_after(&self.chain[..=self.index], self.difficulty)
+    }
+
+    /// Returns the difficulty of the block after the given blocks, adjusting the given difficulty
+    /// of the last block like `next_difficulty()`.
+    fn difficulty_after(&self, chain: &[Block], difficulty: usize) -> usize {
+        let window = self.adjustment_window;
+        let index = chain.len() - 1;
+
+        if window == 0 || !(index + 1).is_multiple_of(window) || index < window {
+            return difficulty;
+        }
+
+        let first = &chain[index - window];
+        let last = &chain[index];
+
+        // identical (or decreasing) timestamps are treated as no time elapsed
+        let elapsed = (last.time() - first.time()).to_std().unwrap_or_default().as_nanos();
+        let expected = self.target_block_time.as_nanos() * window as u128;
+
+        let difficulty = if elapsed < expected / 2 {
+            difficulty + 1
+        } else if elapsed > expected.saturating_mul(2) {
+            difficulty.saturating_sub(1)
+        } else {
+            difficulty
+        };
+
+        difficulty.max(1)
+    }
+
+    /// This method returns the average time between two blocks over the last `window` blocks of the chain
+    /// (or over the whole chain, if it's shorter), i.e. the time elapsed between the first and the last of them
+    /// divided by the number of intervals; if there are fewer than two blocks, `None` is returned.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut blockchain = BlockChain::new_with_difficulty(2, 4);
+    /// 
+    /// assert_eq!(blockchain.average_block_time(10), None); // there's only the genesis block
+    /// 
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// assert!(blockchain.average_block_time(10).is_some());
+    /// ```
+    pub fn average_block_time(&self, window: usize) -> Option<Duration> {
+        let blocks = &self.chain[self.chain.len() - window.min(self.chain.len())..];
+
+        if blocks.len() < 2 {
+            return None;
+        }
+
+        let first = &blocks[0];
+        let last = &blocks[blocks.len() - 1];
+
+        // like in `next_difficulty()`, decreasing timestamps are treated as no time elapsed
+        let elapsed = (last.time() - first.time()).to_std().unwrap_or_default();
+
+        Some(elapsed / (blocks.len() - 1) as u32)
+    }
+
+    /// This method estimates the number of hashes per second calculated by all the miners of the network,
+    /// i.e. the `2^difficulty` hashes needed on average to mine a block with the difficulty of the chain,
+    /// divided by the average time between the last `HASHRATE_ESTIMATE_BLOCKS` blocks, see `average_block_time()`;
+    /// if the average time is unknown, because there's only the genesis block, or it's zero, `None` is returned.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut blockchain = BlockChain::new_with_difficulty(2, 4);
+    /// 
+    /// assert_eq!(blockchain.estimated_hashrate(), None); // there's only the genesis block
+    /// 
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// assert!(blockchain.estimated_hashrate().is_some());
+    /// ```
+    pub fn estimated_hashrate(&self) -> Option<f64> {
+        let seconds = self.average_block_time(HASHRATE_ESTIMATE_BLOCKS)?.as_secs_f64();
+
+        if seconds == 0.0 {
+            return None;
+        }
+
+        Some(2f64.powi(self.difficulty as i32) / seconds)
+    }
+
+    /// This method suggests a fee for a new transaction, i.e. the median of the fees of the transactions
+    /// in the last `FEE_ESTIMATE_BLOCKS` blocks of the chain (the greater of the two middle fees, if their number is even),
+    /// without the coinbase transactions; if there are no such transactions, or if the median is lower
+    /// than the minimum fee, see `set_min_fee()`, the minimum fee is returned.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let mut rosa = Account::new("Rosa", "Vidal", "r0s4_v1d4l").unwrap();
+    /// let sami = Account::new("Sami", "Lind", "sami.lind.pw").unwrap();
+    /// rosa.add_money(100);
+    /// 
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// blockchain.set_min_fee(1);
+    /// 
+    /// assert_eq!(blockchain.estimate_fee(), 1); // there are no transactions yet
+    /// 
+    /// blockchain.add_block(vec![
+    ///     Transaction::new_with_fee(rosa.clone(), sami.clone(), 10, 2, "r0s4_v1d4l").unwrap(),
+    ///     Transaction::new_with_fee(rosa.clone(), sami.clone(), 10, 6, "r0s4_v1d4l").unwrap().with_nonce(1),
+    ///     Transaction::new_with_fee(rosa, sami, 10, 3, "r0s4_v1d4l").unwrap().with_nonce(2),
+    /// ]).unwrap();
+    /// 
+    /// assert_eq!(blockchain.estimate_fee(), 3);
+    /// ```
+    pub fn estimate_fee(&self) -> Amount {
+        let blocks = &self.chain[self.chain.len() - FEE_ESTIMATE_BLOCKS.min(self.chain.len())..];
+
+        let mut fees: Vec<Amount> = blocks
+            .iter()
+            .flat_map(Block::transactions)
+            .filter(|transaction| !transaction.is_coinbase())
+            .map(Transaction::fee)
+            .collect();
+
+        if fees.is_empty() {
+            return self.min_fee;
+        }
+
+        fees.sort_unstable();
+
+        fees[fees.len() / 2].max(self.min_fee)
+    }
+
+    /// This method validates the given transaction and, if it's valid, puts it in the pending transactions,
+    /// waiting to be put in a new block by `mine_pending()`; otherwise, the `ValidationError` is returned.
+    /// 
+    /// Besides the validation of the transaction itself, if the nonce of the transaction isn't greater than
+    /// the nonces of the sender already in the chain, or if another pending transaction of the sender
+    /// has the same nonce, a `ValidationError::InvalidNonce` error is returned, so transactions can't be replayed.
+    /// 
+    /// Note: unlike `push_transaction()`, this method **does not** transfer the money between the accounts.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::transaction::{Transaction, ValidationError};
+    /// # use blockchain::account::Account;
+    /// let mut yuki = Account::new("Yuki", "Tanaka", "yuki_t4n4k4").unwrap();
+    /// let rafael = Account::new("Rafael", "Costa", "RafaCosta#10").unwrap();
+    /// yuki.add_money(10);
+    /// 
+    /// let mut blockchain = BlockChain::with_genesis_allocations(2, 4, vec![(yuki.clone(), 10)]);
+    /// 
+    /// let valid_transaction = Transaction::new(yuki.clone(), rafael.clone(), 5, "yuki_t4n4k4").unwrap();
+    /// let invalid_transaction = Transaction::new(yuki, rafael, 50, "yuki_t4n4k4").unwrap().with_nonce(1);
+    /// 
+    /// assert_eq!(blockchain.add_pending(valid_transaction.clone()), Ok(()));
+    /// assert_eq!(blockchain.add_pending(invalid_transaction), Err(ValidationError::InvalidAmount));
+    /// assert_eq!(blockchain.pending().len(), 1);
+    /// 
+    /// blockchain.mine_pending().unwrap();
+    /// 
+    /// assert_eq!(blockchain.add_pending(valid_transaction), Err(ValidationError::InvalidNonce)); // the transaction was already mined
+    /// ```
+    pub fn add_pending(&mut self, transaction: Transaction) -> Result<(), ValidationError> {
+        self.check_pending(&transaction)?;
+
+        self.transactions.push(transaction);
+
+        Ok(())
+    }
+
+    /// This method creates a transaction of the given amount from an account to another one, with the next nonce
+    /// of the sender and signed for this chain, and puts it in the pending transactions, like `add_pending()`;
+    /// if the transaction is put in the pending transactions, the amount is subtracted from the balance of the sender.
+    /// 
+    /// The method returns a `Result<(), Error>`, and if it fails nothing is changed:
+    /// - if the amount is zero, or more than the balance of the sender, or the password isn't the password of the sender,
+    ///   an `Error::Transaction` error is returned, see `Transaction::try_new()`
+    /// - if the transaction can't be put in the pending transactions, an `Error::InvalidTransaction` error is returned
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::transaction::TxError;
+    /// # use blockchain::account::Account;
+    /// # use blockchain::Error;
+    /// let mut rosa = Account::new("Rosa", "Stein", "r0s4_st31n").unwrap();
+    /// let theo = Account::new("Theo", "Urban", "theo.urban").unwrap();
+    /// rosa.add_money(10);
+    /// 
+    /// let mut blockchain = BlockChain::with_genesis_allocations(5, 4, vec![(rosa.clone(), 10)]);
+    /// 
+    /// assert!(blockchain.transfer(&mut rosa, &theo, 4, "r0s4_st31n").is_ok());
+    /// assert!(matches!(blockchain.transfer(&mut rosa, &theo, 7, "r0s4_st31n"), Err(Error::Transaction(TxError::InsufficientFunds))));
+    /// 
+    /// assert_eq!(blockchain.pending().len(), 1);
+    /// assert_eq!(rosa.balance(), 6);
+    /// ```
+    pub fn transfer(&mut self, from: &mut Account, to: &Account, amount: Amount, password: &str) -> Result<(), Error> {
+        let transaction = Transaction::try_new(from.clone(), to.clone(), amount, password)?
+            .with_nonce(self.next_nonce(from))
+            .with_chain_id(self.chain_id);
+
+        self.add_pending(transaction)?;
+
+        // the amount is checked while creating the transaction
+        unsafe {
+            from.sub_money_unchecked(amount);
+        }
+
+        Ok(())
+    }
+
+    /// This method returns the nonce the next transaction of the given account should have,
+    /// which is the greatest nonce of the account in the chain and in the pending transactions, plus one;
+    /// if the account has no transactions, the nonce is `0`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let mut dana = Account::new("Dana", "Frost", "d4n4_fr0st").unwrap();
+    /// let eli = Account::new("Eli", "Gray", "eli_gray_pw").unwrap();
+    /// dana.add_money(10);
+    /// 
+    /// let mut blockchain = BlockChain::with_genesis_allocations(2, 4, vec![(dana.clone(), 10)]);
+    /// 
+    /// assert_eq!(blockchain.next_nonce(&dana), 0);
+    /// 
+    /// let nonce = blockchain.next_nonce(&dana);
+    /// blockchain.add_pending(Transaction::new(dana.clone(), eli, 1, "d4n4_fr0st").unwrap().with_nonce(nonce)).unwrap();
+    /// 
+    /// assert_eq!(blockchain.next_nonce(&dana), 1);
+    /// ```
+    pub fn next_nonce(&self, account: &Account) -> u64 {
+        let public_key = account.public_key();
+
+        self.transactions()
+            .chain(self.transactions.iter())
+            .filter(|transaction| is_sender(transaction, &public_key))
+            .map(|transaction| transaction.nonce() + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// This method validates a transaction before putting it in the pending transactions,
+    /// checking that its amount isn't over the maximum amount, that its fee isn't under the minimum fee,
+    /// that its nonce wasn't already used by the sender, that the sender has the money in the chain,
+    /// i.e. that its balance returned by `balance_of()` covers its pending transactions and this one,
+    /// and, with a coinbase maturity, that the sender doesn't spend immature rewards.
+    fn check_pending(&self, transaction: &Transaction) -> Result<(), ValidationError> {
+        transaction.validate(transaction.hash())?;
+        self.check_amount(transaction)?;
+        self.check_memo(transaction)?;
+        self.check_chain_id(transaction)?;
+
+        if !transaction.is_coinbase() && transaction.fee() < self.min_fee {
+            return Err(ValidationError::FeeTooLow);
+        }
+
+        if let Some(sender) = &transaction.sender {
+            let public_key = sender.public_key();
+
+            let mined = self.transactions()
+                .any(|t| is_sender(t, &public_key) && t.nonce() >= transaction.nonce());
+
+            let pending = self.transactions
+                .iter()
+                .any(|t| is_sender(t, &public_key) && t.nonce() == transaction.nonce());
+
+            if mined || pending {
+                return Err(ValidationError::InvalidNonce);
+            }
+
+            let spent = self.transactions
+                .iter()
+                .chain(std::iter::once(transaction))
+                .filter(|t| is_sender(t, &public_key))
+                .fold(0, |spent: Amount, t| spent.saturating_add(cost(t)));
+
+            // the balance of the account itself isn't trusted, it's only the balance the sender claims
+            if i128::from(spent) > i128::from(self.balance_of(sender)) {
+                return Err(ValidationError::InvalidAmount);
+            }
+
+            if self.coinbase_maturity > 0 && i128::from(spent) > i128::from(self.spendable_balance_of(sender)) {
+                return Err(ValidationError::ImmatureCoinbase);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the amount of a transaction, which is the sum of its outputs, isn't over the maximum amount
+    /// set with `set_max_tx_amount()`; the coinbase transactions aren't limited.
+    fn check_amount(&self, transaction: &Transaction) -> Result<(), ValidationError> {
+        if !transaction.is_coinbase() && transaction.amount() > self.max_tx_amount {
+            Err(ValidationError::AmountTooLarge)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Checks that the memo of a transaction, if any, isn't longer than the maximum length set with `set_max_memo_len()`.
+    fn check_memo(&self, transaction: &Transaction) -> Result<(), ValidationError> {
+        if transaction.memo().is_some_and(|memo| memo.len() > self.max_memo_len) {
+            Err(ValidationError::MemoTooLong)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Checks that a transaction is signed for this chain, see `new_with_chain_id()`;
+    /// the coinbase transactions aren't signed, and they're bound to the chain by the hash of their block.
+    fn check_chain_id(&self, transaction: &Transaction) -> Result<(), ValidationError> {
+        if !transaction.is_coinbase() && transaction.chain_id() != self.chain_id {
+            Err(ValidationError::WrongChain)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// This method mines a new `Block` with the pending transactions with the highest fees
+    /// (the oldest ones first, if the fees are the same), up to the number of `transactions_per_block`,
+    /// and as long as the size of the block doesn't exceed the size set with `set_max_block_bytes()`;
+    /// the other pending transactions are left waiting for the next block.
+    /// 
+    /// The transactions of the same sender in the block can't spend more than the balance of the sender
+    /// in the chain, returned by `spendable_balance_of()`: a transaction which would exceed it is a double-spend,
+    /// so it's removed from the pending transactions, and the removed transactions are returned.
+    /// 
+    /// The transactions of a sender are mined in the order of their nonces: a transaction is put in the block
+    /// only if its nonce comes right after the last nonce of the sender in the chain (or in the block),
+    /// otherwise it waits for the transactions with the missing nonces.
+    /// 
+    /// In the block, the transactions are sorted in the canonical order, see `block::sort_canonically()`,
+    /// so that the order doesn't depend on the order they were received in.
+    /// 
+    /// If a miner is set, a coinbase transaction rewarding the miner with `current_reward()`,
+    /// plus the fees of the transactions of the block, is put as the first transaction of the block.
+    /// 
+    /// The method returns a `Result<Vec<Transaction>, MiningError>`: if the block isn't mined, see `add_block()`,
+    /// the error is returned and the pending transactions are left waiting, except for the double-spends, which are removed anyway.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let mut zara = Account::new("Zara", "Iqbal", "Z4r4_1qb4l").unwrap();
+    /// let leon = Account::new("Leon", "Weber", "leonweber.pass").unwrap();
+    /// zara.add_money(10);
+    /// 
+    /// let mut blockchain = BlockChain::with_genesis_allocations(2, 4, vec![(zara.clone(), 10)]);
+    /// 
+    /// for (nonce, amount) in [1, 2, 3].iter().enumerate() {
+    ///     blockchain.add_pending(Transaction::new(zara.clone(), leon.clone(), *amount, "Z4r4_1qb4l").unwrap().with_nonce(nonce as u64)).unwrap();
+    /// }
+    /// 
+    /// blockchain.mine_pending().unwrap();
+    /// 
+    /// assert_eq!(blockchain.chain()[1].transactions().len(), 2);
+    /// assert_eq!(blockchain.pending().len(), 1); // the third transaction waits for the next block
+    /// ```
+    pub fn mine_pending(&mut self) -> Result<Vec<Transaction>, MiningError> {
+        // the sort is stable, so the transactions with the same fee keep their order
+        self.transactions.sort_by_key(|transaction| std::cmp::Reverse(transaction.fee()));
+
+        let (mut count, excluded) = self.select_pending();
+
+        // the size of the header, which depends on the optional fields of the block, and of the hash
+        let empty_block_size = self.next_block(self.index.saturating_add(1), Vec::new(), self.difficulty).size_bytes();
+
+        let coinbase = loop {
+            let coinbase = self.miner.as_ref().map(|miner| {
+                let fees = self.transactions[..count].iter().fold(0, |fees: Amount, t| fees.saturating_add(t.fee()));
+
+                Transaction::coinbase(miner.clone(), self.current_reward().saturating_add(fees))
+            });
+
+            let size = empty_block_size
+                + coinbase.as_ref().map_or(0, |coinbase| coinbase.size_bytes())
+                + self.transactions[..count].iter().map(|t| t.size_bytes()).sum::<usize>();
+
+            if size <= self.max_block_bytes || count == 0 {
+                break coinbase;
+            }
+
+            count -= 1;
+        };
+
+        let mut transactions = Vec::with_capacity(count + 1);
+
+        transactions.extend(coinbase);
+
+        transactions.extend_from_slice(&self.transactions[..count]);
+
+        self.add_block(transactions)?;
+
+        self.transactions.drain(..count);
+
+        Ok(excluded)
+    }
+
+    /// Moves to the front of the pending transactions the ones to put in the next block, up to `transactions_per_block`,
+    /// and returns how many they are, along with the double-spends removed from the pending transactions,
+    /// i.e. the transactions which would make their sender spend more than its balance, see `mine_pending()`;
+    /// the pending transactions must be sorted in the order they are mined.
+    /// 
+    /// A transaction is selected only if its nonce is the next nonce of its sender, so when a transaction is selected
+    /// the following transactions of the sender can be selected too, and the pending transactions are scanned again.
+    fn select_pending(&mut self) -> (usize, Vec<Transaction>) {
+        // the balance of every sender in the chain, not the balance its account claims
+        let mut balances: HashMap<String, Balance> = HashMap::new();
+
+        for transaction in &self.transactions {
+            if let Some(sender) = &transaction.sender {
+                balances.entry(sender.address()).or_insert_with(|| self.spendable_balance_of(sender));
+            }
+        }
+
+        let mut spent: HashMap<String, Amount> = HashMap::new();
+        let mut next_nonces: HashMap<String, u64> = HashMap::new();
+        let mut selected = Vec::new();
+        let mut excluded = Vec::new();
+
+        let mut waiting = std::mem::take(&mut self.transactions);
+
+        loop {
+            let before = selected.len();
+            let mut pending = waiting.into_iter();
+            waiting = Vec::new();
+
+            while selected.len() < self.transactions_per_block {
+                let transaction = match pending.next() {
+                    Some(transaction) => transaction,
+                    None => break,
+                };
+
+                if let Some(sender) = &transaction.sender {
+                    let address = sender.address();
+                    let next_nonce = next_nonces.entry(address.clone()).or_insert_with(|| self.mined_next_nonce(sender));
+
+                    if transaction.nonce() != *next_nonce {
+                        waiting.push(transaction);
+
+                        continue;
+                    }
+
+                    let cost = cost(&transaction);
+                    let spent = spent.entry(address.clone()).or_insert(0);
+
+                    if i128::from(spent.saturating_add(cost)) > i128::from(balances[&address]) {
+                        excluded.push(transaction);
+
+                        continue;
+                    }
+
+                    *spent += cost;
+                    *next_nonce += 1;
+                }
+
+                selected.push(transaction);
+            }
+
+            waiting.extend(pending);
+
+            if selected.len() == before || selected.len() == self.transactions_per_block {
+                break;
+            }
+        }
+
+        let count = selected.len();
+
+        self.transactions = selected;
+        self.transactions.extend(waiting);
+
+        (count, excluded)
+    }
+
+    /// Returns the nonce the next transaction of the given account should have to be mined,
+    /// which is the greatest nonce of the account in the chain plus one, or `0` without transactions in the chain.
+    fn mined_next_nonce(&self, account: &Account) -> u64 {
+        let public_key = account.public_key();
+
+        self.transactions()
+            .filter(|transaction| is_sender(transaction, &public_key))
+            .map(|transaction| transaction.nonce() + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// This method mines `n` blocks back to back without the pending transactions, e.g. to generate
+    /// a long chain for testing or benchmarking; every block is mined with the difficulty returned by `next_difficulty()`.
+    /// 
+    /// If a miner is set, every block contains only a coinbase transaction rewarding the miner with `current_reward()`,
+    /// otherwise the blocks are empty. The pending transactions are left waiting.
+    /// 
+    /// The method returns a `Result<(), MiningError>`: if a block isn't mined, see `add_block()`,
+    /// the error is returned, and the blocks mined before it are kept.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::account::Account;
+    /// let mut blockchain = BlockChain::new_with_difficulty(2, 4);
+    /// blockchain.set_miner(Account::new("Bea", "Tran", "b3a_tran").unwrap());
+    /// 
+    /// blockchain.mine_n_empty(3).unwrap();
+    /// 
+    /// assert_eq!(blockchain.height(), 3);
+    /// assert!(blockchain.chain()[1..].iter().all(|block| block.transactions()[0].is_coinbase()));
+    /// ```
+    pub fn mine_n_empty(&mut self, n: usize) -> Result<(), MiningError> {
+        for _ in 0..n {
+            let coinbase = self.miner.as_ref().map(|miner| Transaction::coinbase(miner.clone(), self.current_reward()));
+
+            self.add_block(coinbase.into_iter().collect())?;
+        }
+
+        Ok(())
+    }
+
+    /// This method sets the maximum size in bytes, returned by `Block::size_bytes()`,
+    /// of the blocks mined with `mine_pending()`; by default the size of the blocks isn't limited.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let mut uri = Account::new("Uri", "Vale", "ur1_v4l3").unwrap();
+    /// let vera = Account::new("Vera", "Wolf", "vera.wolf.pw").unwrap();
+    /// uri.add_money(10);
+    /// 
+    /// let mut blockchain = BlockChain::with_genesis_allocations(5, 4, vec![(uri.clone(), 10)]);
+    /// blockchain.set_max_block_bytes(300); // too small for a transaction
+    /// 
+    /// blockchain.add_pending(Transaction::new(uri, vera, 1, "ur1_v4l3").unwrap()).unwrap();
+    /// blockchain.mine_pending().unwrap();
+    /// 
+    /// assert!(blockchain.chain()[1].transactions().is_empty());
+    /// assert_eq!(blockchain.pending().len(), 1);
+    /// ```
+    pub fn set_max_block_bytes(&mut self, max_block_bytes: usize) {
+        self.max_block_bytes = max_block_bytes;
+    }
+
+    /// This method sets the maximum number of blocks that `replace_chain()` can remove from the end of the chain:
+    /// the blocks buried under more than `max_reorg_depth` blocks are final, so a candidate chain forking before them
+    /// is rejected, even if it has more work; by default there's no limit.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut blockchain = BlockChain::new_with_difficulty(2, 4);
+    /// blockchain.set_max_reorg_depth(1);
+    /// 
+    /// let mut fork = blockchain.clone();
+    /// fork.mine_n_empty(3).unwrap();
+    /// 
+    /// blockchain.mine_n_empty(2).unwrap();
+    /// 
+    /// assert!(!blockchain.replace_chain(fork.chain())); // the fork would remove 2 blocks
+    /// ```
+    pub fn set_max_reorg_depth(&mut self, max_reorg_depth: usize) {
+        self.max_reorg_depth = max_reorg_depth;
+    }
+
+    /// This method sets the coinbase maturity, i.e. the number of blocks that must be put in the chain
+    /// after a block before the coinbase rewards of the block can be spent; by default the maturity is `0`,
+    /// so the rewards can be spent right away. The allocations of the genesis block are always spendable.
+    /// 
+    /// With a maturity, the pending transactions are also checked against `spendable_balance_of()`,
+    /// so the senders must have their funds in the chain, and a transaction spending an immature reward
+    /// is rejected with a `ValidationError::ImmatureCoinbase` error.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::transaction::{Transaction, ValidationError};
+    /// # use blockchain::account::Account;
+    /// let mut miner = Account::new("Mia", "Roth", "m1a_r0th").unwrap();
+    /// let nils = Account::new("Nils", "Berg", "nils.berg.pw").unwrap();
+    /// 
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// blockchain.set_miner(miner.clone());
+    /// blockchain.set_block_reward(50);
+    /// blockchain.set_coinbase_maturity(2);
+    /// 
+    /// blockchain.mine_pending().unwrap();
+    /// miner.add_money(50);
+    /// 
+    /// let transaction = Transaction::new(miner, nils, 10, "m1a_r0th").unwrap();
+    /// 
+    /// assert_eq!(blockchain.add_pending(transaction), Err(ValidationError::ImmatureCoinbase));
+    /// ```
+    pub fn set_coinbase_maturity(&mut self, coinbase_maturity: usize) {
+        self.coinbase_maturity = coinbase_maturity;
+    }
+
+    /// This method sets the maximum amount of a transaction, i.e. of the sum of its outputs, as a sanity limit:
+    /// a transaction over the limit is rejected with a `ValidationError::AmountTooLarge` error;
+    /// by default there's no limit. The coinbase transactions aren't limited.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::transaction::{Transaction, ValidationError};
+    /// # use blockchain::account::Account;
+    /// let mut gus = Account::new("Gus", "Orr", "gu5_0rr").unwrap();
+    /// let hana = Account::new("Hana", "Sato", "h4n4.s4t0").unwrap();
+    /// gus.add_money(100);
+    /// 
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// blockchain.set_max_tx_amount(50);
+    /// 
+    /// let transaction = Transaction::new(gus, hana, 60, "gu5_0rr").unwrap();
+    /// 
+    /// assert_eq!(blockchain.add_pending(transaction), Err(ValidationError::AmountTooLarge));
+    /// ```
+    pub fn set_max_tx_amount(&mut self, max_tx_amount: Amount) {
+        self.max_tx_amount = max_tx_amount;
+    }
+
+    /// This method sets the minimum fee of the transactions put in the pending transactions, against spam:
+    /// a transaction with a lower fee is rejected by `add_pending()` with a `ValidationError::FeeTooLow` error;
+    /// by default there's no minimum fee. The coinbase transactions don't pay fees, so they aren't checked.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::transaction::{Transaction, ValidationError};
+    /// # use blockchain::account::Account;
+    /// let mut ivo = Account::new("Ivo", "Pratt", "1v0_pr4tt").unwrap();
+    /// let jun = Account::new("Jun", "Mori", "jun.mori.pw").unwrap();
+    /// ivo.add_money(100);
+    /// 
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// blockchain.set_min_fee(2);
+    /// 
+    /// let transaction = Transaction::new_with_fee(ivo, jun, 10, 1, "1v0_pr4tt").unwrap();
+    /// 
+    /// assert_eq!(blockchain.add_pending(transaction), Err(ValidationError::FeeTooLow));
+    /// ```
+    pub fn set_min_fee(&mut self, min_fee: Amount) {
+        self.min_fee = min_fee;
+    }
+
+    /// This method sets the maximum length of the memo of a transaction, in bytes, see `Transaction::with_memo()`:
+    /// a transaction with a longer memo is rejected with a `ValidationError::MemoTooLong` error;
+    /// the default maximum length is `DEFAULT_MAX_MEMO_LEN`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::transaction::{Transaction, ValidationError};
+    /// # use blockchain::account::Account;
+    /// let mut kit = Account::new("Kit", "Noble", "k1t_n0bl3").unwrap();
+    /// let lev = Account::new("Lev", "Orlov", "lev.orlov.pw").unwrap();
+    /// kit.add_money(100);
+    /// 
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// blockchain.set_max_memo_len(8);
+    /// 
+    /// let transaction = Transaction::new(kit, lev, 10, "k1t_n0bl3").unwrap().with_memo("Dinner on Friday");
+    /// 
+    /// assert_eq!(blockchain.add_pending(transaction), Err(ValidationError::MemoTooLong));
+    /// ```
+    pub fn set_max_memo_len(&mut self, max_memo_len: usize) {
+        self.max_memo_len = max_memo_len;
+    }
+
+    /// This method sets the time after which a pending transaction expires, see `evict_expired()`;
+    /// the default is `DEFAULT_MEMPOOL_TTL`, i.e. 2 weeks.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// use std::time::Duration;
+    /// 
+    /// let mut blockchain = BlockChain::new_with_difficulty(2, 4);
+    /// blockchain.set_mempool_ttl(Duration::from_secs(60 * 60));
+    /// 
+    /// assert_eq!(blockchain.evict_expired(chrono::Utc::now()), 0);
+    /// ```
+    pub fn set_mempool_ttl(&mut self, mempool_ttl: Duration) {
+        self.mempool_ttl = mempool_ttl;
+    }
+
+    /// This method returns the pending transactions, waiting to be put in a new block.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let blockchain = BlockChain::new_with_difficulty(2, 4);
+    /// 
+    /// assert!(blockchain.pending().is_empty());
+    /// ```
+    pub fn pending(&self) -> &[Transaction] {
+        &self.transactions
+    }
+
+    /// This method drops the pending transactions older than the time set with `set_mempool_ttl()` at the given time,
+    /// according to the time of the transactions, and returns how many transactions were dropped.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// use std::time::Duration;
+    /// 
+    /// let mut ezra = Account::new("Ezra", "Lund", "3zr4_lund").unwrap();
+    /// let fay = Account::new("Fay", "Moss", "f4y.m0ss").unwrap();
+    /// ezra.add_money(10);
+    /// 
+    /// let mut blockchain = BlockChain::with_genesis_allocations(2, 4, vec![(ezra.clone(), 10)]);
+    /// blockchain.set_mempool_ttl(Duration::from_secs(60 * 60));
+    /// blockchain.add_pending(Transaction::new(ezra, fay, 5, "3zr4_lund").unwrap()).unwrap();
+    /// 
+    /// let tomorrow = chrono::Utc::now() + chrono::Duration::days(1);
+    /// 
+    /// assert_eq!(blockchain.evict_expired(tomorrow), 1);
+    /// assert!(blockchain.pending().is_empty());
+    /// ```
+    pub fn evict_expired(&mut self, now: DateTime<Utc>) -> usize {
+        // a TTL too large for `chrono` never expires
+        let ttl = match chrono::Duration::from_std(self.mempool_ttl) {
+            Ok(ttl) => ttl,
+            Err(_) => return 0,
+        };
+
+        let before = self.transactions.len();
+
+        self.transactions.retain(|transaction| now.signed_duration_since(transaction.time()) <= ttl);
+
+        before - self.transactions.len()
+    }
+
+    /// This method mines a new `Block` with the given transactions, linking it to the last block of the chain,
+    /// and puts it in the chain; the block is mined with the difficulty returned by `next_difficulty()`.
+    /// 
+    /// The method returns a `Result<(), MiningError>`, leaving the chain untouched on error:
+    /// - if no nonce satisfies the difficulty, a `MiningError::Exhausted` error is returned
+    /// - if the chain already has `usize::MAX` as index, a `MiningError::IndexOverflow` error is returned
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// assert_eq!(blockchain.chain()[1].prev_hash(), blockchain.chain()[0].hash());
+    /// ```
+    pub fn add_block(&mut self, transactions: Vec<Transaction>) -> Result<(), MiningError> {
+        let index = self.index.checked_add(1).ok_or(MiningError::IndexOverflow)?;
+        let difficulty = self.next_difficulty();
+
+        let new_block = self.mine_next_block(index, transactions, difficulty, u128::MAX)?;
+
+        self.retarget(index, difficulty);
+        self.index = index;
+        self.push_block(new_block);
+
+        Ok(())
+    }
+
+    /// This method checks the given transactions and mines a new `Block` with them, like `add_block()`,
+    /// trying at most `max_nonce + 1` nonces; the chain is changed only if the block is mined.
+    /// 
+    /// The method returns a `Result<(), Error>`:
+    /// - if a transaction isn't valid, an `Error::InvalidTransaction` error is returned
+    /// - if no nonce up to `max_nonce` satisfies the difficulty, an `Error::Mining` error is returned
+    /// - if the chain already has `usize::MAX` as index, an `Error::Chain(ChainError::BadIndex)` error is returned
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::transaction::{Transaction, ValidationError};
+    /// # use blockchain::account::Account;
+    /// # use blockchain::Error;
+    /// let ada = Account::new("Ada", "Byron", "4d4_byr0n").unwrap();
+    /// let ben = Account::new("Ben", "Cole", "ben_cole_pw").unwrap();
+    /// 
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// 
+    /// assert!(blockchain.try_add_block(Vec::new(), u128::MAX).is_ok());
+    /// 
+    /// let overspending = Transaction::new(ada, ben, 5, "4d4_byr0n").unwrap(); // ada has no money
+    /// let result = blockchain.try_add_block(vec![overspending], u128::MAX);
+    /// 
+    /// assert!(matches!(result, Err(Error::InvalidTransaction(ValidationError::InvalidAmount))));
+    /// assert_eq!(blockchain.chain().len(), 2);
+    /// ```
+    pub fn try_add_block(&mut self, transactions: Vec<Transaction>, max_nonce: u128) -> Result<(), Error> {
+        for transaction in &transactions {
+            transaction.validate(transaction.hash())?;
+        }
+
+        let index = self.index.checked_add(1).ok_or(ChainError::BadIndex)?;
+        let difficulty = self.next_difficulty();
+
+        let new_block = self.mine_next_block(index, transactions, difficulty, max_nonce)?;
+
+        self.retarget(index, difficulty);
+        self.index = index;
+        self.push_block(new_block);
+
+        Ok(())
+    }
+
+    /// Sets the difficulty the block at the given height is mined with,
+    /// calling the callbacks registered with `on_retarget()` if the difficulty changes.
+    fn retarget(&mut self, height: usize, difficulty: usize) {
+        let old_difficulty = self.difficulty;
+        self.difficulty = difficulty;
+
+        if difficulty != old_difficulty {
+            let event = RetargetEvent { height, old_difficulty, new_difficulty: difficulty };
+
+            for callback in &mut self.observers.on_retarget {
+                callback(&event);
+            }
+        }
+    }
+
+    /// Mines the block after the last block of the chain, produced by the miner of the chain, if any, see `Block::producer()`.
+    fn mine_next_block(&self, index: usize, transactions: Vec<Transaction>, difficulty: usize, max_nonce: u128) -> Result<Block, MiningError> {
+        self.next_block(index, transactions, difficulty).mine_up_to_with(max_nonce, self.proof_of_work)
+    }
+
+    /// Generates the next block of the chain with the given transactions, which is not mined yet,
+    /// for the chain identifier of the chain and with the miner as producer, if any.
+    fn next_block(&self, index: usize, transactions: Vec<Transaction>, difficulty: usize) -> Block {
+        let block = Block::unmined_on_chain(index, self.tip_hash(), transactions, difficulty, self.chain_id, self.now());
+
+        match &self.miner {
+            Some(miner) => block.with_producer(miner.public_key()),
+            None => block,
+        }
+    }
+
+    /// This method returns an iterator over the blocks of the chain, starting from the genesis block.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut blockchain = BlockChain::new_with_difficulty(2, 4);
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// let indexes: Vec<usize> = blockchain.blocks().map(|block| block.index()).collect();
+    /// 
+    /// assert_eq!(indexes, vec![0, 1]);
+    /// ```
+    pub fn blocks(&self) -> impl Iterator<Item = &Block> {
+        self.chain.iter()
+    }
+
+    /// This method returns the block with the given index, or `None` if the chain has no such block.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut blockchain = BlockChain::new_with_difficulty(2, 4);
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// assert_eq!(blockchain.block_by_index(1).unwrap().index(), 1);
+    /// assert!(blockchain.block_by_index(2).is_none());
+    /// ```
+    pub fn block_by_index(&self, index: usize) -> Option<&Block> {
+        self.chain.get(index)
+    }
+
+    /// This method returns the block with the given hash, or `None` if the chain has no such block.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut blockchain = BlockChain::new_with_difficulty(2, 4);
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// let block = blockchain.block_by_index(1).unwrap();
+    /// 
+    /// assert_eq!(blockchain.block_by_hash(&block.hash()), Some(block));
+    /// 
+    /// let genesis = blockchain.block_by_hash(&block.prev_hash()).unwrap(); // following the link to the previous block
+    /// 
+    /// assert_eq!(genesis.index(), 0);
+    /// assert!(blockchain.block_by_hash(&[1; 64]).is_none());
+    /// ```
+    pub fn block_by_hash(&self, hash: &[u8; 64]) -> Option<&Block> {
+        self.chain.iter().find(|block| block.hash_ref() == hash)
+    }
+
+    /// This method returns the blocks produced by the account with the given address, see `Block::producer()`
+    /// and `Account::address()`, starting from the oldest one.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::account::Account;
+    /// let vera = Account::new("Vera", "Wolf", "v3r4_w0lf").unwrap();
+    /// let wes = Account::new("Wes", "Young", "wes.young.pw").unwrap();
+    /// 
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// blockchain.set_miner(vera.clone());
+    /// blockchain.mine_n_empty(2).unwrap();
+    /// 
+    /// assert_eq!(blockchain.blocks_by_producer(&vera.address()).len(), 2);
+    /// assert!(blockchain.blocks_by_producer(&wes.address()).is_empty());
+    /// ```
+    pub fn blocks_by_producer(&self, address: &str) -> Vec<&Block> {
+        self.chain
+            .iter()
+            .filter(|block| block.producer().is_some_and(|producer| Account::address_of_key(&producer) == address))
+            .collect()
+    }
+
+    /// This method returns the number of confirmations of the transaction with the given identifier,
+    /// i.e. the number of blocks from the block containing the transaction to the tip, both included,
+    /// so a transaction in the tip has 1 confirmation; it returns `None` if the transaction isn't in the chain,
+    /// e.g. while it's still pending.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let mut olga = Account::new("Olga", "Prieto", "olg4_pr13t0").unwrap();
+    /// let pavel = Account::new("Pavel", "Quist", "pavel.quist").unwrap();
+    /// 
+    /// let mut blockchain = BlockChain::with_genesis_allocations(5, 4, vec![(olga.clone(), 10)]);
+    /// olga.add_money(10);
+    /// 
+    /// let transaction = Transaction::new(olga, pavel, 3, "olg4_pr13t0").unwrap();
+    /// let id = transaction.id();
+    /// 
+    /// assert_eq!(blockchain.confirmations(&id), None);
+    /// 
+    /// blockchain.add_block(vec![transaction]).unwrap();
+    /// 
+    /// assert_eq!(blockchain.confirmations(&id), Some(1));
+    /// 
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// assert_eq!(blockchain.confirmations(&id), Some(2));
+    /// ```
+    pub fn confirmations(&self, tx_id: &[u8; 64]) -> Option<usize> {
+        // the recent transactions are looked up more often, so the search starts from the tip
+        self.chain
+            .iter()
+            .rev()
+            .find(|block| block.transactions().iter().any(|transaction| transaction.id() == *tx_id))
+            .map(|block| self.height() - block.index() + 1)
+    }
+
+    /// This method returns a block locator, i.e. the hashes of some blocks of the chain, from the tip to the genesis block,
+    /// to tell a peer which blocks the chain has, see `blocks_after()`: the blocks `tip`, `tip - 1`, `tip - 2`, `tip - 4`,
+    /// `tip - 8` and so on, and the genesis block, so the locator has about `log2(height)` hashes.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut blockchain = BlockChain::new_with_difficulty(2, 0);
+    /// blockchain.mine_n_empty(5).unwrap();
+    /// 
+    /// let indexes: Vec<usize> = blockchain.locator()
+    ///     .iter()
+    ///     .map(|hash| blockchain.block_by_hash(hash).unwrap().index())
+    ///     .collect();
+    /// 
+    /// assert_eq!(indexes, vec![5, 4, 3, 1, 0]);
+    /// ```
+    pub fn locator(&self) -> Vec<[u8; 64]> {
+        let tip = self.height();
+
+        let mut locator: Vec<[u8; 64]> = std::iter::once(0)
+            .chain(std::iter::successors(Some(1usize), |step| step.checked_mul(2)))
+            .take_while(|step| *step <= tip)
+            .map(|step| self.chain[tip - step].hash())
+            .collect();
+
+        if tip != 0 && !tip.is_power_of_two() {
+            locator.push(self.chain[0].hash());
+        }
+
+        locator
+    }
+
+    /// This method returns the blocks of the chain after the first block of the given locator, see `locator()`,
+    /// which is also in the chain, e.g. to send a peer the blocks it's missing;
+    /// if no block of the locator is in the chain, the whole chain is returned, starting from the genesis block.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut blockchain = BlockChain::new_with_difficulty(2, 0);
+    /// let peer = blockchain.clone();
+    /// 
+    /// blockchain.mine_n_empty(3).unwrap();
+    /// 
+    /// let missing = blockchain.blocks_after(&peer.locator());
+    /// 
+    /// assert_eq!(missing, &blockchain.chain()[1..]);
+    /// ```
+    pub fn blocks_after(&self, locator: &[[u8; 64]]) -> &[Block] {
+        let known = locator.iter().find_map(|hash| self.chain.iter().position(|block| block.hash_ref() == hash));
+
+        match known {
+            Some(position) => &self.chain[position + 1..],
+            None => &self.chain,
+        }
+    }
+
+    /// This method returns the blocks whose time is between `start` and `end` (both included), in chain order.
+    /// 
+    /// Since the time of every block of a valid chain comes after the time of the previous block,
+    /// the blocks are found with a binary search.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut blockchain = BlockChain::new_with_difficulty(2, 4);
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// let chain = blockchain.chain();
+    /// 
+    /// assert_eq!(blockchain.blocks_between(chain[1].time(), chain[2].time()).len(), 2);
+    /// assert!(blockchain.blocks_between(chain[2].time(), chain[1].time()).is_empty());
+    /// ```
+    pub fn blocks_between(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<&Block> {
+        let first = self.chain.partition_point(|block| block.time() < start);
+        let last = self.chain.partition_point(|block| block.time() <= end);
+
+        self.chain[first..last.max(first)].iter().collect()
+    }
+
+    /// This method returns an iterator over the transactions in the chain, in block order;
+    /// the pending transactions aren't included.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let mut rhea = Account::new("Rhea", "Dunn", "rh34_dunn").unwrap();
+    /// let saul = Account::new("Saul", "Ortiz", "saul_0rt1z").unwrap();
+    /// rhea.add_money(10);
+    /// 
+    /// let mut blockchain = BlockChain::with_genesis_allocations(2, 4, vec![(rhea.clone(), 10)]);
+    /// 
+    /// for (nonce, amount) in [1, 2, 3].iter().enumerate() {
+    ///     blockchain.add_pending(Transaction::new(rhea.clone(), saul.clone(), *amount, "rh34_dunn").unwrap().with_nonce(nonce as u64)).unwrap();
+    /// }
+    /// 
+    /// blockchain.mine_pending().unwrap();
+    /// blockchain.mine_pending().unwrap();
+    /// 
+    /// let total: usize = blockchain.blocks().map(|block| block.transactions().len()).sum();
+    /// 
+    /// assert_eq!(blockchain.transactions().count(), total);
+    /// assert_eq!(blockchain.transactions().count(), 4); // the allocation in the genesis block and 3 transfers
+    /// ```
+    pub fn transactions(&self) -> impl Iterator<Item = &Transaction> {
+        self.chain.iter().flat_map(|block| block.transactions())
+    }
+
+    /// This method returns the balance of the given account, computed from the transactions in the chain:
+    /// the amounts sent by the account (and their fees) are subtracted, and the amounts received are added
+    /// (including the rewards of the coinbase transactions), so a transaction from the account
+    /// to itself doesn't change its balance.
+    /// 
+    /// The pending transactions aren't counted, since they aren't in the chain yet.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::account::Account;
+    /// let mut gwen = Account::new("Gwen", "Stacy", "gw3n_st4cy").unwrap();
+    /// let mut peter = Account::new("Peter", "Parker", "with_great_power").unwrap();
+    /// gwen.add_money(100);
+    /// 
+    /// let mut blockchain = BlockChain::with_genesis_allocations(1, 4, vec![(gwen.clone(), 100)]);
+    /// blockchain.push_transaction(&mut gwen, &mut peter, 40, "gw3n_st4cy");
+    /// blockchain.push_transaction(&mut peter, &mut gwen, 15, "with_great_power");
+    /// 
+    /// assert_eq!(blockchain.balance_of(&peter), 25); // 40 - 15 = 25
+    /// assert_eq!(blockchain.balance_of(&gwen), 75); // 100 allocated in the genesis block - 40 + 15 = 75
+    /// ```
+    pub fn balance_of(&self, account: &Account) -> Balance {
+        let public_key = account.public_key();
+
+        self.transactions()
+            .fold(0, |balance, transaction| {
+                let mut balance = balance;
+
+                if is_sender(transaction, &public_key) {
+                    balance -= cost(transaction) as Balance;
+                }
+
+                for (receiver, amount) in transaction.outputs() {
+                    if receiver.public_key() == public_key {
+                        balance += *amount as Balance;
+                    }
+                }
+
+                balance
+            })
+    }
+
+    /// This method returns the balance of the given account that can be spent, i.e. the balance returned by
+    /// `balance_of()` without the coinbase rewards which aren't mature yet (see `set_coinbase_maturity()`).
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::account::Account;
+    /// let miner = Account::new("Ola", "Lind", "0l4_l1nd").unwrap();
+    /// 
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// blockchain.set_miner(miner.clone());
+    /// blockchain.set_block_reward(50);
+    /// blockchain.set_coinbase_maturity(2);
+    /// 
+    /// blockchain.mine_pending().unwrap();
+    /// blockchain.mine_pending().unwrap();
+    /// 
+    /// assert_eq!(blockchain.balance_of(&miner), 100);
+    /// assert_eq!(blockchain.spendable_balance_of(&miner), 0);
+    /// 
+    /// blockchain.mine_pending().unwrap();
+    /// 
+    /// assert_eq!(blockchain.spendable_balance_of(&miner), 50); // the reward of the block #1 is mature
+    /// ```
+    pub fn spendable_balance_of(&self, account: &Account) -> Balance {
+        let public_key = account.public_key();
+
+        let immature: Amount = self.chain
+            .iter()
+            .skip(1)
+            .filter(|block| self.height() - block.index() < self.coinbase_maturity)
+            .flat_map(|block| block.transactions())
+            .filter(|transaction| transaction.is_coinbase())
+            .flat_map(|transaction| transaction.outputs())
+            .filter(|(receiver, _)| receiver.public_key() == public_key)
+            .map(|(_, amount)| amount)
+            .sum();
+
+        self.balance_of(account) - immature as Balance
+    }
+
+    /// This method returns the balance of the given account returned by `balance_of()`, minus what the account
+    /// spends in the pending transactions, i.e. their amounts plus their fees, so that a wallet doesn't
+    /// spend twice the same money; the money received in the pending transactions isn't counted.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let mut cleo = Account::new("Cleo", "Marsh", "cl30_m4rsh").unwrap();
+    /// let dev = Account::new("Dev", "Nair", "dev.nair.pw").unwrap();
+    /// 
+    /// let mut blockchain = BlockChain::with_genesis_allocations(5, 4, vec![(cleo.clone(), 10)]);
+    /// cleo.add_money(10);
+    /// 
+    /// blockchain.add_pending(Transaction::new_with_fee(cleo.clone(), dev, 4, 1, "cl30_m4rsh").unwrap()).unwrap();
+    /// 
+    /// assert_eq!(blockchain.balance_of(&cleo), 10);
+    /// assert_eq!(blockchain.available_balance(&cleo), 5);
+    /// ```
+    pub fn available_balance(&self, account: &Account) -> Balance {
+        let public_key = account.public_key();
+
+        let pending = self.transactions
+            .iter()
+            .filter(|transaction| is_sender(transaction, &public_key))
+            .fold(0, |spent: Amount, transaction| spent.saturating_add(cost(transaction)));
+
+        self.balance_of(account) - pending as Balance
+    }
+
+    /// This method replays every block of the chain from the genesis block, applying in order the coinbase credits
+    /// and the debits and credits of the transactions, and returns the final balance of every account, indexed by its address.
+    /// 
+    /// The replay doesn't depend on any state kept by the chain, so it can be compared with the snapshots
+    /// updated as the blocks are added; the balances are the ones returned by `balance_of()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::account::Account;
+    /// let bo = Account::new("Bo", "Lind", "b0_l1nd_pw").unwrap();
+    /// 
+    /// let mut blockchain = BlockChain::with_genesis_allocations(5, 4, vec![(bo.clone(), 30)]);
+    /// blockchain.set_miner(bo.clone());
+    /// blockchain.set_block_reward(5);
+    /// blockchain.mine_pending().unwrap();
+    /// 
+    /// assert_eq!(blockchain.replay()[&bo.address()], 35);
+    /// ```
+    pub fn replay(&self) -> HashMap<String, Balance> {
+        let mut balances = HashMap::new();
+
+        for block in &self.chain {
+            apply_block(&mut balances, block);
+        }
+
+        balances
+    }
+
+    /// This method computes a `BalanceSnapshot` with the balances of all the accounts in the chain,
+    /// computed like `balance_of()`, so that the balances can be looked up without scanning the chain again.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::account::Account;
+    /// let xavi = Account::new("Xavi", "Young", "x4v1_y0ung").unwrap();
+    /// 
+    /// let blockchain = BlockChain::with_genesis_allocations(5, 4, vec![(xavi.clone(), 30)]);
+    /// let snapshot = blockchain.snapshot();
+    /// 
+    /// assert_eq!(snapshot.height(), 0);
+    /// assert_eq!(blockchain.balance_of_snapshot(&snapshot, &xavi), Some(30));
+    /// ```
+    pub fn snapshot(&self) -> BalanceSnapshot {
+        let mut snapshot = BalanceSnapshot {
+            height: 0,
+            tip: self.chain[0].hash(),
+            balances: HashMap::new(),
+        };
+
+        apply_block(&mut snapshot.balances, &self.chain[0]);
+
+        self.update_snapshot(&mut snapshot);
+
+        snapshot
+    }
+
+    /// This method updates the given snapshot with the blocks added to the chain after it was computed;
+    /// if the block the snapshot was computed up to isn't in the chain anymore,
+    /// because the chain was replaced, the snapshot is computed again.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::account::Account;
+    /// let miner = Account::new("Yusuf", "Zane", "yusuf_z4n3").unwrap();
+    /// 
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// blockchain.set_miner(miner.clone());
+    /// blockchain.set_block_reward(10);
+    /// 
+    /// let mut snapshot = blockchain.snapshot();
+    /// blockchain.mine_pending().unwrap();
+    /// 
+    /// assert_eq!(blockchain.balance_of_snapshot(&snapshot, &miner), None); // the snapshot is outdated
+    /// 
+    /// blockchain.update_snapshot(&mut snapshot);
+    /// 
+    /// assert_eq!(blockchain.balance_of_snapshot(&snapshot, &miner), Some(10));
+    /// ```
+    pub fn update_snapshot(&self, snapshot: &mut BalanceSnapshot) {
+        if self.chain.get(snapshot.height).map(|block| block.hash()) != Some(snapshot.tip) {
+            *snapshot = self.snapshot();
+
+            return;
+        }
+
+        for block in &self.chain[snapshot.height + 1..] {
+            apply_block(&mut snapshot.balances, block);
+        }
+
+        snapshot.height = self.height();
+        snapshot.tip = self.chain[snapshot.height].hash();
+    }
+
+    /// This method returns the balance of the given account in the snapshot, which is the same as
+    /// `balance_of()` but doesn't scan the chain; if blocks were added to the chain after the snapshot
+    /// was computed, the snapshot is outdated and `None` is returned, since `update_snapshot()` must be called first.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::account::Account;
+    /// let zed = Account::new("Zed", "Amos", "z3d_4m0s").unwrap();
+    /// 
+    /// let blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// let snapshot = blockchain.snapshot();
+    /// 
+    /// assert_eq!(blockchain.balance_of_snapshot(&snapshot, &zed), Some(0)); // accounts with no transactions have no money
+    /// ```
+    pub fn balance_of_snapshot(&self, snapshot: &BalanceSnapshot, account: &Account) -> Option<Balance> {
+        if snapshot.height != self.height() || snapshot.tip != *self.chain[snapshot.height].hash_ref() {
+            return None;
+        }
+
+        Some(snapshot.balances.get(&account.address()).copied().unwrap_or(0))
+    }
+
+    /// This method returns, in chain order, all the transactions where the given account
+    /// is the sender or one of the receivers, including the coinbase transactions rewarding the account as a miner.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let mut tara = Account::new("Tara", "Quill", "t4r4_qu1ll").unwrap();
+    /// let uma = Account::new("Uma", "Patel", "uma_patel_pw").unwrap();
+    /// let victor = Account::new("Victor", "Hale", "v1ct0r.h4l3").unwrap();
+    /// tara.add_money(10);
+    /// 
+    /// let mut blockchain = BlockChain::with_genesis_allocations(2, 4, vec![(tara.clone(), 10)]);
+    /// blockchain.set_miner(uma.clone());
+    /// 
+    /// blockchain.add_pending(Transaction::new(tara.clone(), uma.clone(), 1, "t4r4_qu1ll").unwrap()).unwrap();
+    /// blockchain.add_pending(Transaction::new(tara.clone(), victor.clone(), 2, "t4r4_qu1ll").unwrap().with_nonce(1)).unwrap();
+    /// blockchain.mine_pending().unwrap();
+    /// blockchain.mine_pending().unwrap();
+    /// 
+    /// assert_eq!(blockchain.history_of(&tara).len(), 3); // the allocation in the genesis block and 2 transfers
+    /// assert_eq!(blockchain.history_of(&uma).len(), 3); // 2 coinbase transactions and 1 transfer
+    /// assert_eq!(blockchain.history_of(&victor).len(), 1);
+    /// ```
+    pub fn history_of(&self, account: &Account) -> Vec<&Transaction> {
+        let public_key = account.public_key();
+
+        self.transactions().filter(|transaction| involves(transaction, &public_key)).collect()
+    }
+
+    /// This method returns the unspent outputs of the transactions in the chain, the oldest ones first.
+    /// 
+    /// Since the transactions don't say which outputs they spend, every transaction spends the outputs
+    /// received by its sender in order, the oldest ones first, until the amount and the fee are covered;
+    /// the last output spent can be spent only in part, and then only the remaining amount is unspent.
+    /// The money added to the accounts outside of the chain isn't counted, so a sender can spend
+    /// more than its outputs, and then all of its outputs are spent.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let mut cleo = Account::new("Cleo", "Dorn", "cl30_d0rn").unwrap();
+    /// let dirk = Account::new("Dirk", "Esch", "dirk.esch.pw").unwrap();
+    /// 
+    /// let mut blockchain = BlockChain::with_genesis_allocations(5, 4, vec![(cleo.clone(), 10)]);
+    /// cleo.add_money(10);
+    /// 
+    /// assert_eq!(blockchain.utxo_set()[0].amount, 10);
+    /// 
+    /// blockchain.add_block(vec![Transaction::new(cleo, dirk.clone(), 4, "cl30_d0rn").unwrap()]).unwrap();
+    /// 
+    /// let utxos = blockchain.utxo_set();
+    /// 
+    /// assert_eq!(utxos.len(), 2);
+    /// assert_eq!(utxos[0].amount, 6); // the rest of the allocation
+    /// assert_eq!((utxos[1].owner.as_str(), utxos[1].amount), (dirk.address().as_str(), 4));
+    /// ```
+    pub fn utxo_set(&self) -> Vec<Utxo> {
+        let mut utxos: Vec<Utxo> = Vec::new();
+
+        for transaction in self.transactions() {
+            if let Some(sender) = &transaction.sender {
+                let address = sender.address();
+                let mut to_spend = cost(transaction);
+
+                for utxo in utxos.iter_mut().filter(|utxo| utxo.owner == address) {
+                    if to_spend == 0 {
+                        break;
+                    }
+
+                    let spent = utxo.amount.min(to_spend);
+
+                    utxo.amount -= spent;
+                    to_spend -= spent;
+                }
+
+                utxos.retain(|utxo| utxo.amount > 0);
+            }
+
+            for (output_index, (receiver, amount)) in transaction.outputs().iter().enumerate() {
+                utxos.push(Utxo {
+                    tx_hash: transaction.hash(),
+                    output_index,
+                    owner: receiver.address(),
+                    amount: *amount,
+                });
+            }
+        }
+
+        utxos
+    }
+
+    /// This method returns the height of the chain, which is the index of its last block,
+    /// so a chain with only the genesis block has a height of `0`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// assert_eq!(blockchain.height(), 1);
+    /// ```
+    pub fn height(&self) -> usize {
+        self.chain.len() - 1
+    }
+
+    /// This method returns the last block of the chain; since the chain always has the genesis block,
+    /// there is always a last block.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// assert_eq!(blockchain.tip().index(), blockchain.height());
+    /// assert_eq!(blockchain.tip(), &blockchain.chain()[1]);
+    /// ```
+    pub fn tip(&self) -> &Block {
+        &self.chain[self.chain.len() - 1]
+    }
+
+    /// This method returns the hash of the last block of the chain, returned by `tip()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// let genesis = blockchain.tip_hash();
+    /// 
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// assert_eq!(blockchain.tip().prev_hash(), genesis);
+    /// assert_eq!(blockchain.tip_hash(), blockchain.tip().hash());
+    /// ```
+    pub fn tip_hash(&self) -> [u8; 64] {
+        self.tip().hash()
+    }
+
+    /// This method drops the transactions of the blocks with an index lower than `height`,
+    /// keeping their headers and their Merkle roots, so that the chain is still valid
+    /// but takes less space, e.g. for a light node.
+    /// 
+    /// The balances computed from the chain, like `balance_of()`, don't count the pruned transactions anymore,
+    /// so a `BalanceSnapshot` should be taken with `snapshot()` before pruning.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::account::Account;
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// blockchain.set_miner(Account::new("Mina", "Row", "m1na_r0w").unwrap());
+    /// 
+    /// blockchain.mine_pending().unwrap();
+    /// blockchain.mine_pending().unwrap();
+    /// 
+    /// blockchain.prune_before(2);
+    /// 
+    /// assert!(blockchain.is_valid());
+    /// assert!(blockchain.chain()[1].is_pruned());
+    /// assert_eq!(blockchain.transactions().count(), 1); // only the coinbase of the block #2
+    /// ```
+    pub fn prune_before(&mut self, height: usize) {
+        for block in self.chain.iter_mut().take(height) {
+            block.prune();
+        }
+    }
+
+    /// This method returns the total work of the chain, an estimate of the number of hashes
+    /// computed to mine its blocks: a block with a difficulty of `d` bits takes `2^d` hashes on average.
+    /// 
+    /// The work saturates to `u128::MAX`, which is reached by any chain with a block with a difficulty of at least 128 bits.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// assert_eq!(blockchain.total_work(), 16 + 16);
+    /// ```
+    pub fn total_work(&self) -> u128 {
+        chain_work(&self.chain)
+    }
+
+    /// This method returns the total money supply issued by the chain, which is the sum of the rewards
+    /// of the coinbase transactions; the fees are paid by the senders, so they're not counted.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::account::Account;
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// blockchain.set_miner(Account::new("Wade", "Xu", "w4d3_xu").unwrap());
+    /// blockchain.set_block_reward(8);
+    /// blockchain.set_halving_interval(1);
+    /// 
+    /// blockchain.mine_pending().unwrap();
+    /// blockchain.mine_pending().unwrap();
+    /// 
+    /// assert_eq!(blockchain.total_supply(), 4 + 2); // the first reward is already halved
+    /// ```
+    pub fn total_supply(&self) -> Amount {
+        let (issued, fees) = self.transactions()
+            .fold((0, 0), |(issued, fees): (Amount, Amount), transaction| if transaction.is_coinbase() {
+                (issued.saturating_add(transaction.amount()), fees)
+            } else {
+                (issued, fees.saturating_add(transaction.fee()))
+            });
+
+        issued.saturating_sub(fees)
+    }
+
+    /// This method sets how far in the future the time of a block can be, compared to the current time,
+    /// for the chain to be valid; the default is `DEFAULT_MAX_FUTURE_DRIFT`, i.e. 2 hours.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// use std::time::Duration;
+    /// 
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// blockchain.set_max_future_drift(Duration::from_secs(10 * 60));
+    /// 
+    /// assert!(blockchain.is_valid());
+    /// ```
+    pub fn set_max_future_drift(&mut self, max_future_drift: Duration) {
+        self.max_future_drift = max_future_drift;
+    }
+
+    /// This method checks if the chain is valid exactly like `is_valid()`, but the hashes
+    /// and the proofs of work of the blocks are verified in parallel, which is faster for long chains.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// assert!(blockchain.is_valid_parallel());
+    /// ```
+    pub fn is_valid_parallel(&self) -> bool {
+        is_valid_chain_parallel(&self.chain, self.now(), self.max_future_drift, self.chain_id, self.proof_of_work)
+    }
+
+    /// This method checks if the chain is valid: the first block must be a genesis block,
+    /// with index `0` and a hash of the previous block made of zeros, and every block must be valid,
+    /// must have the right index and must be linked to the hash of the previous block;
+    /// moreover, the time of every block must be after the time of the previous block,
+    /// and can't be ahead of the current time by more than the maximum drift set with `set_max_future_drift()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// assert!(blockchain.is_valid());
+    /// ```
+    pub fn is_valid(&self) -> bool {
+        self.validation_errors().next().is_none()
+    }
+
+    /// This method checks the chain like `is_valid()`, returning an iterator over the defects of the chain,
+    /// found while the blocks are scanned from the genesis block: the iterator can be stopped at the first defect,
+    /// or collected to find all of them.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// assert_eq!(blockchain.validation_errors().count(), 0);
+    /// ```
+    pub fn validation_errors(&self) -> impl Iterator<Item = ChainDefect> + '_ {
+        chain_defects(&self.chain, self.now(), self.max_future_drift, self.chain_id, self.proof_of_work)
+    }
+
+    /// This method puts a block mined somewhere else, e.g. received from a peer, at the end of the chain,
+    /// without mining it again; the block is accepted only if it's linked to the last block of the chain,
+    /// with the next index, and if it passes all the checks of `submit_external_block()`, transactions included.
+    /// 
+    /// The pending transactions put in the block are removed from the pending transactions.
+    /// 
+    /// The method returns a `Result<(), ChainError>`, and if the block isn't put in the chain the chain is left untouched:
+    /// - if the hash of the previous block isn't the hash of the last block of the chain, a `ChainError::BadLink` error is returned
+    /// - if the index of the block isn't the index of the last block plus one, a `ChainError::BadIndex` error is returned
+    /// - if the block is rejected by any other check of `submit_external_block()`, e.g. if it isn't later than the last block,
+    ///   its difficulty is too low or a transaction isn't valid, a `ChainError::InvalidBlock` error is returned
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::{BlockChain, ChainError};
+    /// let mut blockchain = BlockChain::new_with_difficulty(2, 4);
+    /// let mut peer = blockchain.clone();
+    /// 
+    /// peer.add_block(Vec::new()).unwrap();
+    /// let block = peer.chain()[1].clone();
+    /// 
+    /// assert_eq!(blockchain.append_block(block.clone()), Ok(()));
+    /// assert_eq!(blockchain.append_block(block), Err(ChainError::BadLink)); // the block is already in the chain
+    /// assert_eq!(blockchain.chain(), peer.chain());
+    /// ```
+    pub fn append_block(&mut self, block: Block) -> Result<(), ChainError> {
+        let tip = self.tip();
+
+        if block.prev_hash() != *tip.hash_ref() {
+            return Err(ChainError::BadLink);
+        }
+
+        // the index can't wrap around, even if the chain is as long as it can be
+        if self.height().checked_add(1) != Some(block.index()) {
+            return Err(ChainError::BadIndex);
+        }
+
+        self.check_next_block(&block).map_err(|_| ChainError::InvalidBlock)?;
+
+        self.connect_block(block);
+
+        Ok(())
+    }
+
+    /// This method puts a block mined somewhere else at the end of the chain, like `append_block()`,
+    /// but it also checks the transactions of the block against the state of the chain,
+    /// so it's the way to put in the chain the blocks received from the peers.
+    /// 
+    /// The method returns a `Result<(), RejectReason>`, and if the block isn't put in the chain the chain is left untouched:
+    /// - if the block is mined for another chain, see `new_with_chain_id()`, a `RejectReason::WrongChain` error is returned
+    /// - if the index isn't the next index, a `RejectReason::WrongIndex` error is returned
+    /// - if the hash of the previous block isn't the hash of the last block of the chain, a `RejectReason::BadPrevHash` error is returned
+    /// - if the version of the block is older than `BLOCK_VERSION`, a `RejectReason::OutdatedVersion` error is returned,
+    ///   since the older blocks don't hash their proof of work, see `PROOF_OF_WORK_VERSION`, and don't need the canonical order,
+    ///   see `CANONICAL_ORDER_VERSION`, so a new block can't skip them by claiming an older version
+    /// - if the block doesn't verify, its target byte isn't `DEFAULT_TARGET` or its difficulty is lower than `next_difficulty()`,
+    ///   a `RejectReason::InsufficientWork` error is returned
+    /// - if the block isn't later than the last block, or it's too far in the future, a `RejectReason::BadTimestamp` error is returned
+    /// - if a transaction isn't valid, its nonce doesn't follow the last nonce of the sender, its sender never received money
+    ///   in the chain (not even in the genesis block) or spends more than its balance in the chain,
+    ///   or the coinbase transactions issue more than the block reward plus the fees,
+    ///   a `RejectReason::InvalidTransaction` error is returned
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::{BlockChain, RejectReason};
+    /// let mut blockchain = BlockChain::new_with_difficulty(2, 4);
+    /// let mut peer = blockchain.clone();
+    /// 
+    /// peer.add_block(Vec::new()).unwrap();
+    /// let block = peer.chain()[1].clone();
+    /// 
+    /// assert_eq!(blockchain.submit_external_block(block.clone()), Ok(()));
+    /// assert_eq!(blockchain.submit_external_block(block), Err(RejectReason::WrongIndex)); // the block is already in the chain
+    /// assert_eq!(blockchain.chain(), peer.chain());
+    /// ```
+    pub fn submit_external_block(&mut self, block: Block) -> Result<(), RejectReason> {
+        self.check_next_block(&block)?;
+
+        self.connect_block(block);
+
+        Ok(())
+    }
+
+    /// Checks a block which would be put at the end of the chain, see `submit_external_block()`;
+    /// every block checked with this method can become the tip of the chain.
+    fn check_next_block(&self, block: &Block) -> Result<(), RejectReason> {
+        let tip = self.tip();
+
+        if block.chain_id() != self.chain_id {
+            return Err(RejectReason::WrongChain);
+        }
+
+        if block.index() != self.height() + 1 {
+            return Err(RejectReason::WrongIndex);
+        }
+
+        if block.prev_hash() != *tip.hash_ref() {
+            return Err(RejectReason::BadPrevHash);
+        }
+
+        if block.version() < BLOCK_VERSION {
+            return Err(RejectReason::OutdatedVersion);
+        }
+
+        if !block.verify_with(self.proof_of_work) || block.target() != DEFAULT_TARGET || block.difficulty() < self.next_difficulty() {
+            return Err(RejectReason::InsufficientWork);
+        }
+
+        if block.time() <= tip.time() || !on_time(block, self.now(), self.max_future_drift) {
+            return Err(RejectReason::BadTimestamp);
+        }
+
+        if !block.has_required_order() {
+            return Err(RejectReason::NonCanonicalOrder);
+        }
+
+        self.check_block_transactions(block).map_err(RejectReason::InvalidTransaction)
+    }
+
+    /// Checks the transactions of a block which would be put at the end of the chain, see `check_block_on()`.
+    fn check_block_transactions(&self, block: &Block) -> Result<(), ValidationError> {
+        self.check_block_on(&Ledger::of(&self.chain), block)
+    }
+
+    /// Checks the transactions of a block which would be put after the blocks replayed by the `Ledger`:
+    /// every transaction must be valid, its sender must have received money before, and the nonces of every sender
+    /// must follow the last nonce of the sender, without gaps; the transactions of a sender can't spend more than
+    /// its balance before the block, without the immature rewards, and the coinbase transactions can't issue
+    /// more than the block reward plus the fees.
+    fn check_block_on(&self, ledger: &Ledger, block: &Block) -> Result<(), ValidationError> {
+        // a repeated transaction is a replay, even if it's a coinbase transaction, which has no nonce
+        if !block.has_unique_transactions() {
+            return Err(ValidationError::InvalidNonce);
+        }
+
+        let mut issued: Amount = 0;
+        let mut fees: Amount = 0;
+
+        for (i, transaction) in block.transactions().iter().enumerate() {
+            let sender = match &transaction.sender {
+                Some(sender) => sender,
+                None => {
+                    issued = issued.saturating_add(transaction.amount());
+
+                    continue;
+                },
+            };
+
+            transaction.validate(transaction.hash())?;
+            self.check_amount(transaction)?;
+            self.check_memo(transaction)?;
+            self.check_chain_id(transaction)?;
+
+            let public_key = sender.public_key();
+
+            // a sender which never received money in the chain, not even in the genesis block, doesn't exist
+            let known = ledger.knows(&public_key)
+                || block.transactions()[..i].iter().any(|t| involves(t, &public_key));
+
+            if !known {
+                return Err(ValidationError::UnknownSender);
+            }
+
+            fees = fees.saturating_add(transaction.fee());
+        }
+
+        ledger.check_nonces(block)?;
+        ledger.check_spends(block, self.coinbase_maturity)?;
+
+        if issued > self.reward_at(block.index()).saturating_add(fees) {
+            return Err(ValidationError::InvalidAmount);
+        }
+
+        Ok(())
+    }
+
+    /// Puts a block already checked at the end of the chain, removing its transactions from the pending transactions.
+    fn connect_block(&mut self, block: Block) {
+        self.transactions.retain(|pending| block.transactions().iter().all(|mined| mined.hash() != pending.hash()));
+
+        self.difficulty = block.difficulty();
+        self.index = self.chain.len();
+        self.push_block(block);
+    }
+
+    /// This method registers a callback, called with every block put at the end of the chain,
+    /// e.g. to update a wallet or an index when a block is mined or received from a peer;
+    /// the blocks put in the chain by `replace_chain()` are passed to the callbacks registered with `on_reorg()` instead.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use std::sync::{Arc, Mutex};
+    /// let mut blockchain = BlockChain::new_with_difficulty(2, 4);
+    /// 
+    /// let indices = Arc::new(Mutex::new(Vec::new()));
+    /// let seen = Arc::clone(&indices);
+    /// blockchain.on_block(Box::new(move |block| seen.lock().unwrap().push(block.index())));
+    /// 
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// assert_eq!(*indices.lock().unwrap(), vec![1, 2]);
+    /// ```
+    pub fn on_block(&mut self, callback: BlockCallback) {
+        self.observers.on_block.push(callback);
+    }
+
+    /// This method registers a callback, called when `replace_chain()` replaces the chain,
+    /// with the blocks removed from the chain and the blocks added to it,
+    /// i.e. the blocks of the two chains after the last block they have in common.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use std::sync::{Arc, Mutex};
+    /// let mut blockchain = BlockChain::new_with_difficulty(2, 4);
+    /// 
+    /// let mut fork = blockchain.clone();
+    /// fork.add_block(Vec::new()).unwrap();
+    /// fork.add_block(Vec::new()).unwrap();
+    /// 
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// let swapped = Arc::new(Mutex::new((0, 0)));
+    /// let seen = Arc::clone(&swapped);
+    /// blockchain.on_reorg(Box::new(move |removed, added| *seen.lock().unwrap() = (removed.len(), added.len())));
+    /// 
+    /// assert!(blockchain.replace_chain(fork.chain()));
+    /// assert_eq!(*swapped.lock().unwrap(), (1, 2));
+    /// ```
+    pub fn on_reorg(&mut self, callback: ReorgCallback) {
+        self.observers.on_reorg.push(callback);
+    }
+
+    /// This method registers a callback, called when the difficulty adjustment changes the difficulty
+    /// of a block mined by the chain, e.g. with `add_block()` or `mine_pending()`, see `set_difficulty_adjustment()`,
+    /// with a `RetargetEvent` describing the change.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::{BlockChain, RetargetEvent};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use std::time::Duration;
+    /// let mut blockchain = BlockChain::new_with_difficulty(2, 1);
+    /// blockchain.set_difficulty_adjustment(2, Duration::from_secs(3600)); // the blocks are mined much faster than this
+    /// 
+    /// let events = Arc::new(Mutex::new(Vec::new()));
+    /// let seen = Arc::clone(&events);
+    /// blockchain.on_retarget(Box::new(move |event| seen.lock().unwrap().push(*event)));
+    /// 
+    /// blockchain.mine_n_empty(4).unwrap();
+    /// 
+    /// assert_eq!(events.lock().unwrap()[0], RetargetEvent { height: 4, old_difficulty: 1, new_difficulty: 2 });
+    /// ```
+    pub fn on_retarget(&mut self, callback: RetargetCallback) {
+        self.observers.on_retarget.push(callback);
+    }
+
+    /// This method sets the `ChainStore` the blocks are written to: the blocks of the chain which aren't
+    /// in the store yet are appended to it, so the store must be empty or start with the first blocks of the chain,
+    /// and then every block put in the chain is appended to the store, without writing the whole chain again.
+    /// When the chain is replaced, the blocks after the fork are replaced in the store too.
+    /// 
+    /// The errors of the store while the blocks are put in the chain are printed to `stderr`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::store::InMemoryChainStore;
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// 
+    /// blockchain.set_store(Box::new(InMemoryChainStore::new())).unwrap();
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// ```
+    pub fn set_store(&mut self, store: Box<dyn ChainStore + Send + Sync>) -> io::Result<()> {
+        let len = store.len().min(self.chain.len());
+
+        self.store = Store(Some(store));
+
+        self.sync_store(len)
+    }
+
+    /// This method sets the `Clock` the blockchain takes the time from, instead of the system clock:
+    /// the new blocks are mined at the time of the clock, and the blocks are validated against it,
+    /// e.g. to check that they aren't too far in the future. A clone of the blockchain shares the same clock.
+    /// 
+    /// Since every block must come after the previous one, the time of the clock must move forward between two blocks.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::clock::FixedClock;
+    /// use chrono::{Duration, Utc};
+    /// use std::sync::Arc;
+    /// 
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// blockchain.set_clock(Arc::new(FixedClock::new(Utc::now() - Duration::days(1))));
+    /// 
+    /// assert!(!blockchain.is_valid()); // the blocks are in the future of the clock
+    /// ```
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = ChainClock(clock);
+    }
+
+    /// Returns the current time of the `Clock` of the blockchain.
+    fn now(&self) -> DateTime<Utc> {
+        self.clock.0.now()
+    }
+
+    /// Makes the store match the chain, keeping the first `fork` blocks of the store,
+    /// which must be the same as in the chain, and writing the blocks after them.
+    fn sync_store(&mut self, fork: usize) -> io::Result<()> {
+        if let Some(store) = &mut self.store.0 {
+            if store.len() > fork {
+                store.truncate(fork)?;
+            }
+
+            for block in &self.chain[store.len()..] {
+                store.append_block(block)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Like `sync_store()`, but the error is printed to `stderr`.
+    fn sync_store_or_report(&mut self, fork: usize) {
+        if let Err(e) = self.sync_store(fork) {
+            eprintln!("Can't write the blocks to the store: {}", e);
+        }
+    }
+
+    /// Puts a block at the end of the chain, writes it to the store, and calls the callbacks registered with `on_block()`.
+    fn push_block(&mut self, block: Block) {
+        self.chain.push(block);
+
+        self.sync_store_or_report(self.chain.len() - 1);
+
+        let block = &self.chain[self.chain.len() - 1];
+
+        for callback in &mut self.observers.on_block {
+            callback(block);
+        }
+    }
+
+    /// This method accepts a block received from a peer, possibly out of order:
+    /// - if the block can be put at the end of the chain with `append_block()`, it's put in the chain
+    ///   and `AcceptOutcome::Appended` is returned
+    /// - if the parent of the block isn't in the chain, the block is kept as an orphan,
+    ///   and `AcceptOutcome::Orphan` is returned; when the parent arrives, the orphan is put in the chain too
+    /// - otherwise the block isn't valid, and `AcceptOutcome::Rejected` is returned
+    /// 
+    /// At most `MAX_ORPHANS` orphans are kept, and the orphans must be valid blocks by themselves.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::{BlockChain, AcceptOutcome};
+    /// let mut blockchain = BlockChain::new_with_difficulty(2, 4);
+    /// let mut peer = blockchain.clone();
+    /// 
+    /// peer.add_block(Vec::new()).unwrap();
+    /// peer.add_block(Vec::new()).unwrap();
+    /// 
+    /// assert_eq!(blockchain.accept_block(peer.chain()[2].clone()), AcceptOutcome::Orphan); // the parent is missing
+    /// assert_eq!(blockchain.accept_block(peer.chain()[1].clone()), AcceptOutcome::Appended);
+    /// 
+    /// assert_eq!(blockchain.chain(), peer.chain()); // the orphan was put in the chain after its parent
+    /// ```
+    pub fn accept_block(&mut self, block: Block) -> AcceptOutcome {
+        if self.append_block(block.clone()).is_ok() {
+            self.connect_orphans();
+
+            return AcceptOutcome::Appended;
+        }
+
+        let orphan = self.block_by_hash(&block.prev_hash()).is_none()
+            && block.verify_with(self.proof_of_work)
+            && self.orphans.iter().all(|orphan| orphan.hash_ref() != block.hash_ref());
+
+        if !orphan {
+            return AcceptOutcome::Rejected;
+        }
+
+        if self.orphans.len() == MAX_ORPHANS {
+            self.orphans.remove(0);
+        }
+
+        self.orphans.push(block);
+
+        AcceptOutcome::Orphan
+    }
+
+    /// This method returns the orphan blocks, waiting for their parent to be put in the chain.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let blockchain = BlockChain::new_with_difficulty(2, 4);
+    /// 
+    /// assert!(blockchain.orphans().is_empty());
+    /// ```
+    pub fn orphans(&self) -> &[Block] {
+        &self.orphans
+    }
+
+    /// Puts in the chain the orphans whose parent is the last block of the chain, until none is left,
+    /// and drops the orphans which are already in the chain.
+    fn connect_orphans(&mut self) {
+        loop {
+            let chain = &self.chain;
+            self.orphans.retain(|orphan| chain.iter().all(|block| block.hash_ref() != orphan.hash_ref()));
+
+            let tip = self.tip_hash();
+
+            match self.orphans.iter().position(|orphan| orphan.prev_hash() == tip) {
+                Some(position) => {
+                    let orphan = self.orphans.remove(position);
+
+                    // an orphan which isn't valid is dropped
+                    let _ = self.append_block(orphan);
+                },
+                None => break,
+            }
+        }
+    }
+
+    /// This method replaces the chain with the candidate chain, following the heaviest chain rule:
+    /// the candidate is accepted only if it has more total work than the current chain
+    /// (see `total_work()`), if it's valid and if it starts with the same genesis block;
+    /// the candidate is replayed from the genesis block, and its blocks must pass the checks of the transactions
+    /// of `submit_external_block()`, i.e. the signatures, the balances, the nonces and the coinbase transactions.
+    /// The difficulty of every block is checked too: like in `next_difficulty()`, it must be at least the difficulty
+    /// of the block before it (the difficulty the chain was generated with, for the block #1), adjusted every `adjustment_window` blocks,
+    /// so the work of the candidate can't be inflated with many blocks easier to mine than the blocks of the chain.
+    /// The candidate is rejected as well if it would remove more blocks than the maximum, see `set_max_reorg_depth()`.
+    /// 
+    /// When the candidate has the same work as the current chain, the tie is broken by the hashes of the last blocks:
+    /// the candidate is accepted only if the hash of its last block, read as a big-endian integer, is smaller;
+    /// so the nodes receiving two chains with the same work keep the same chain, whatever order they arrive in.
+    /// 
+    /// The method returns `true` if the chain was replaced, otherwise the current chain is left untouched.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut blockchain = BlockChain::new_with_difficulty(2, 4);
+    /// 
+    /// let mut fork = blockchain.clone(); // the fork has the same genesis block
+    /// fork.add_block(Vec::new()).unwrap();
+    /// fork.add_block(Vec::new()).unwrap();
+    /// 
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// assert!(!fork.replace_chain(blockchain.chain())); // the chain has less work than the fork
+    /// assert!(blockchain.replace_chain(fork.chain()));
+    /// 
+    /// assert_eq!(blockchain.chain(), fork.chain());
+    /// ```
+    pub fn replace_chain(&mut self, candidate: Vec<Block>) -> bool {
+        let heavier = candidate.last().is_some_and(|tip| {
+            let (work, current_work) = (chain_work(&candidate), self.total_work());
+
+            work > current_work || (work == current_work && tip.hash_ref() < self.tip().hash_ref())
+        });
+
+        // the blocks after the last block in common are swapped
+        let fork = self.chain
+            .iter()
+            .zip(&candidate)
+            .take_while(|(block, other)| block.hash_ref() == other.hash_ref())
+            .count();
+
+        if !heavier
+            || candidate[0] != self.chain[0]
+            || self.chain.len() - fork > self.max_reorg_depth
+            || !is_valid_chain(&candidate, self.now(), self.max_future_drift, self.chain_id, self.proof_of_work)
+            || !self.replays(&candidate) {
+            return false;
+        }
+
+        self.index = candidate.len() - 1;
+        let removed = std::mem::replace(&mut self.chain, candidate);
+
+        self.sync_store_or_report(fork);
+
+        for callback in &mut self.observers.on_reorg {
+            callback(&removed[fork..], &self.chain[fork..]);
+        }
+
+        self.connect_orphans();
+
+        true
+    }
+
+    /// Replays a chain from its genesis block, checking the difficulty of every block and its transactions
+    /// against the balances and the nonces of the blocks before it, see `check_block_on()`.
+    fn replays(&self, chain: &[Block]) -> bool {
+        let mut ledger = Ledger::default();
+
+        chain.iter().enumerate().all(|(i, block)| {
+            // the genesis block has no block reward to check the allocations with,
+            // and it can be mined with another difficulty than the chain
+            let valid = i == 0 || {
+                let difficulty = if i == 1 { self.initial_difficulty } else { chain[i - 1].difficulty() };
+
+                block.difficulty() >= self.difficulty_after(&chain[..i], difficulty) && self.check_block_on(&ledger, block).is_ok()
+            };
+
+            ledger.apply(block);
+
+            valid
+        })
+    }
+
+    /// This method removes the last block of the chain and returns it, e.g. to drop an invalid tip;
+    /// the genesis block is never removed, so `None` is returned if it's the only block of the chain.
+    /// 
+    /// Like with `replace_chain()`, the block is removed from the store, the callbacks registered with `on_reorg()`
+    /// are called with the removed block, and its transactions aren't put back in the pending transactions;
+    /// the balances are computed from the chain, so they don't count the removed block anymore.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// assert_eq!(blockchain.rollback().map(|block| block.index()), Some(1));
+    /// assert_eq!(blockchain.rollback(), None); // the genesis block is left in the chain
+    /// assert_eq!(blockchain.height(), 0);
+    /// ```
+    pub fn rollback(&mut self) -> Option<Block> {
+        if self.chain.len() < 2 {
+            return None;
+        }
+
+        let block = self.chain.pop()?;
+        self.index = self.chain.len() - 1;
+
+        self.sync_store_or_report(self.chain.len());
+
+        for callback in &mut self.observers.on_reorg {
+            callback(std::slice::from_ref(&block), &[]);
+        }
+
+        Some(block)
+    }
+    
+    /// This method saves the blockchain to the file at the given path, as JSON.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// let path = std::env::temp_dir().join("blockchain_save_to_file.json");
+    /// 
+    /// blockchain.save_to_file(&path).unwrap();
+    /// 
+    /// assert_eq!(BlockChain::load_from_file(&path).unwrap(), blockchain);
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn save_to_file(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string(self)?;
+
+        fs::write(path, json)
+    }
+
+    /// This method loads a blockchain from the file at the given path, saved with `save_to_file()`.
+    /// 
+    /// The function returns a `Result<BlockChain, LoadError>`:
+    /// - if the file can't be read, a `LoadError::Io` error is returned
+    /// - if the file doesn't contain a blockchain, a `LoadError::Parse` error is returned,
+    ///   which has the line and the column where the parsing failed
+    /// - if the loaded chain isn't valid, a `LoadError::Defect` error is returned with the first defect of the chain,
+    ///   see `validation_errors()`, which has the index of the block and why it isn't valid
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::{BlockChain, LoadError};
+    /// let path = std::env::temp_dir().join("blockchain_load_from_file.json");
+    /// 
+    /// std::fs::write(&path, "not a blockchain").unwrap();
+    /// 
+    /// match BlockChain::load_from_file(&path) {
+    ///     Err(LoadError::Parse(e)) => assert_eq!(e.line(), 1),
+    ///     _ => panic!("the file isn't a blockchain"),
+    /// }
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn load_from_file(path: &Path) -> Result<Self, LoadError> {
+        let json = fs::read_to_string(path).map_err(LoadError::Io)?;
+
+        let blockchain: BlockChain = serde_json::from_str(&json).map_err(LoadError::Parse)?;
+
+        let defect = blockchain.validation_errors().next();
+
+        match defect {
+            Some(defect) => Err(LoadError::Defect(defect)),
+            None => Ok(blockchain),
+        }
+    }
+
+    /// This method writes the blocks of the chain as JSON Lines, i.e. one block per line,
+    /// so that the chain is written block by block instead of as a single JSON document,
+    /// and new blocks can be appended to the file later.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// let mut jsonl = Vec::new();
+    /// blockchain.export_jsonl(&mut jsonl).unwrap();
+    /// 
+    /// assert_eq!(jsonl.iter().filter(|&&byte| byte == b'\n').count(), 2);
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn export_jsonl<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        for block in &self.chain {
+            serde_json::to_writer(&mut writer, block)?;
+
+            writer.write_all(b"\n")?;
+        }
+
+        writer.flush()
+    }
+
+    /// This method reads the blocks written with `export_jsonl()`, one block per line,
+    /// and replaces the chain with them; the empty lines are skipped. The settings of the blockchain,
+    /// like the difficulty adjustment, and the pending transactions aren't changed.
+    /// 
+    /// The method returns a `Result<(), LoadError>`, and if an error is returned the chain is left untouched:
+    /// - if a line can't be read, a `LoadError::Io` error is returned
+    /// - if a line doesn't contain a block, a `LoadError::Parse` error is returned
+    /// - if there are no blocks, or the chain of the blocks isn't valid, a `LoadError::Corrupt` error is returned
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::{BlockChain, LoadError};
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// 
+    /// assert!(matches!(blockchain.import_jsonl("not a block\n".as_bytes()), Err(LoadError::Parse(_))));
+    /// assert!(matches!(blockchain.import_jsonl("".as_bytes()), Err(LoadError::Corrupt)));
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn import_jsonl<R: BufRead>(&mut self, reader: R) -> Result<(), LoadError> {
+        let mut chain = Vec::new();
+
+        for line in reader.lines() {
+            let line = line.map_err(LoadError::Io)?;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            chain.push(serde_json::from_str(&line).map_err(LoadError::Parse)?);
+        }
+
+        if chain.is_empty() || !is_valid_chain(&chain, self.now(), self.max_future_drift, self.chain_id, self.proof_of_work) {
+            return Err(LoadError::Corrupt);
+        }
+
+        self.index = chain.len() - 1;
+        self.chain = chain;
+
+        self.sync_store_or_report(0);
+
+        Ok(())
+    }
+
+    /// This method returns the `HeaderChain` with the headers of the blocks of the chain,
+    /// e.g. to send to a light client.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// 
+    /// assert_eq!(blockchain.header_chain().headers()[0].hash, blockchain.chain()[0].hash());
+    /// ```
+    pub fn header_chain(&self) -> HeaderChain {
+        HeaderChain::with_proof_of_work(self.chain.iter().map(Block::header).collect(), self.proof_of_work)
+    }
+
+    /// This method returns the index and the hash of every block of the chain, from the genesis block to the tip,
+    /// as a compact summary of the chain, e.g. for an audit, which can be checked with `verify_hash_chain()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// let pairs = blockchain.hash_chain();
+    /// 
+    /// assert_eq!(pairs, vec![(0, blockchain.chain()[0].hash()), (1, blockchain.chain()[1].hash())]);
+    /// ```
+    pub fn hash_chain(&self) -> Vec<(usize, [u8; 64])> {
+        self.chain.iter().map(|block| (block.index(), block.hash())).collect()
+    }
+
+    /// This method checks a summary returned by `hash_chain()` against the chain, using only the hashes:
+    /// the pairs must have the indexes of the blocks in order from the genesis block, the hashes of the blocks,
+    /// and every block must be linked to the hash of the previous pair; the blocks aren't mined again.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// let mut pairs = blockchain.hash_chain();
+    /// 
+    /// assert!(blockchain.verify_hash_chain(&pairs));
+    /// 
+    /// pairs.swap(0, 1);
+    /// 
+    /// assert!(!blockchain.verify_hash_chain(&pairs));
+    /// ```
+    pub fn verify_hash_chain(&self, pairs: &[(usize, [u8; 64])]) -> bool {
+        pairs.len() == self.chain.len()
+            && pairs.iter().zip(&self.chain).enumerate().all(|(i, ((index, hash), block))| {
+                let linked = i.checked_sub(1).is_none_or(|previous| block.prev_hash() == pairs[previous].1);
+
+                *index == i && block.hash_ref() == hash && linked
+            })
+    }
+
+    /// This method returns the identifier of the chain, since the `chain_id` field isn't `pub`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// 
+    /// assert_eq!(blockchain.chain_id(), 0);
+    /// ```
+    pub fn chain_id(&self) -> u32 {
+        self.chain_id
+    }
+
+    /// This method returns the proof of work function the blocks of the chain are mined and verified with,
+    /// see `BlockChainBuilder::proof_of_work()`; it's `ProofOfWork::Sha512` unless the chain is built with another one.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::hasher::ProofOfWork;
+    /// let blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// 
+    /// assert_eq!(blockchain.proof_of_work(), ProofOfWork::Sha512);
+    /// ```
+    pub fn proof_of_work(&self) -> ProofOfWork {
+        self.proof_of_work
+    }
+
+    /// This method returns the `chain` of the blockchain, since this field isn't `pub`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let blockchain = BlockChain::new(8);
+    /// 
+    /// assert_eq!(blockchain.chain().len(), 1); // the blockchain starts with the genesis block
+    /// ```
+    pub fn chain(&self) -> Vec<Block> {
+        self.chain.clone()
+    }
+
+    /// This method returns a read-only `ChainView` of the blockchain, which can be handed out,
+    /// e.g. to a plugin, without letting it change the chain.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// let view = blockchain.view();
+    /// 
+    /// assert_eq!(view.height(), 1);
+    /// assert_eq!(view.tip(), blockchain.tip());
+    /// ```
+    pub fn view(&self) -> ChainView<'_> {
+        ChainView { blockchain: self }
+    }
+}
+
+/// A callback registered with `BlockChain::on_block()`.
+pub type BlockCallback = Box<dyn FnMut(&Block) + Send + Sync>;
+
+/// A callback registered with `BlockChain::on_reorg()`, called with the removed blocks and the added blocks.
+pub type ReorgCallback = Box<dyn FnMut(&[Block], &[Block]) + Send + Sync>;
+
+/// A callback registered with `BlockChain::on_retarget()`.
+pub type RetargetCallback = Box<dyn FnMut(&RetargetEvent) + Send + Sync>;
+
+/// A change of the difficulty of the blocks mined by a `BlockChain`, passed to the callbacks
+/// registered with `BlockChain::on_retarget()`; it contains:
+/// - the height of the first block mined with the new difficulty
+/// - the difficulty of the previous block
+/// - the new difficulty
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetargetEvent {
+    pub height: usize,
+    pub old_difficulty: usize,
+    pub new_difficulty: usize,
+}
+
+/// The callbacks registered in a `BlockChain`: they don't take part in the comparisons,
+/// and a clone of the blockchain has no callbacks.
+#[derive(Default)]
+struct Observers {
+    on_block: Vec<BlockCallback>,
+    on_reorg: Vec<ReorgCallback>,
+    on_retarget: Vec<RetargetCallback>,
+}
+
+impl Clone for Observers {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl PartialEq for Observers {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl fmt::Debug for Observers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Observers")
+            .field("on_block", &self.on_block.len())
+            .field("on_reorg", &self.on_reorg.len())
+            .field("on_retarget", &self.on_retarget.len())
+            .finish()
+    }
+}
+
+/// The `ChainStore` of a `BlockChain`, if any: like the `Observers`, it doesn't take part in the comparisons,
+/// and a clone of the blockchain has no store, so that the blocks aren't written twice.
+#[derive(Default)]
+struct Store(Option<Box<dyn ChainStore + Send + Sync>>);
+
+impl Clone for Store {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl PartialEq for Store {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl fmt::Debug for Store {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Store").field(&self.0.as_ref().map(|store| store.len())).finish()
+    }
+}
+
+/// A structure to configure a new `BlockChain`, returned by `BlockChain::builder()`, instead of passing
+/// all the parameters to a constructor; the chain is generated, with its genesis block, by `build()`.
+/// 
+/// Unless they're changed, the chain has `DEFAULT_TRANSACTIONS_PER_BLOCK` transactions per block,
+/// the blocks are mined with `DEFAULT_DIFFICULTY`, there's no block reward, no halving, the identifier
+/// of the chain is `0`, the genesis block is mined at the current time and it doesn't pre-fund any account.
+/// 
+/// # Example
+/// ```
+/// # use blockchain::blockchain::BlockChain;
+/// # use blockchain::account::Account;
+/// let zoe = Account::new("Zoe", "Marsh", "z03_m4rsh").unwrap();
+/// 
+/// let blockchain = BlockChain::builder()
+///     .difficulty(4)
+///     .tx_per_block(2)
+///     .block_reward(50)
+///     .halving_interval(100)
+///     .allocate(zoe.clone(), 30)
+///     .build().unwrap();
+/// 
+/// assert_eq!(blockchain.balance_of(&zoe), 30);
+/// assert_eq!(blockchain.current_reward(), 50);
+/// assert_eq!(blockchain.chain()[0].difficulty(), 4);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockChainBuilder {
+    difficulty: usize,
+    transactions_per_block: usize,
+    block_reward: Amount,
+    halving_interval: usize,
+    chain_id: u32,
+    genesis_time: Option<DateTime<Utc>>,
+    allocations: Vec<(Account, Amount)>,
+    proof_of_work: ProofOfWork,
+}
+
+impl Default for BlockChainBuilder {
+    fn default() -> Self {
+        Self {
+            difficulty: DEFAULT_DIFFICULTY,
+            transactions_per_block: DEFAULT_TRANSACTIONS_PER_BLOCK,
+            block_reward: 0,
+            halving_interval: 0,
+            chain_id: 0,
+            genesis_time: None,
+            allocations: Vec::new(),
+            proof_of_work: ProofOfWork::Sha512,
+        }
+    }
+}
+
+impl BlockChainBuilder {
+    /// This method sets the difficulty the blocks are mined with, including the genesis block.
+    pub fn difficulty(mut self, difficulty: usize) -> Self {
+        self.difficulty = difficulty;
+        self
+    }
+
+    /// This method sets the number of transactions per block, see `BlockChain::set_transactions_per_block()`.
+    pub fn tx_per_block(mut self, transactions_per_block: usize) -> Self {
+        self.transactions_per_block = transactions_per_block;
+        self
+    }
+
+    /// This method sets the block reward, see `BlockChain::set_block_reward()`.
+    pub fn block_reward(mut self, block_reward: Amount) -> Self {
+        self.block_reward = block_reward;
+        self
+    }
+
+    /// This method sets the halving interval of the block reward, see `BlockChain::set_halving_interval()`.
+    pub fn halving_interval(mut self, halving_interval: usize) -> Self {
+        self.halving_interval = halving_interval;
+        self
+    }
+
+    /// This method sets the identifier of the chain, see `BlockChain::new_with_chain_id()`.
+    pub fn chain_id(mut self, chain_id: u32) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    /// This method sets the time the genesis block is mined at.
+    pub fn genesis_time(mut self, genesis_time: DateTime<Utc>) -> Self {
+        self.genesis_time = Some(genesis_time);
+        self
+    }
+
+    /// This method sets the proof of work function the blocks are mined and verified with, including the genesis block,
+    /// e.g. the memory-hard `ProofOfWork::Scrypt`; the blocks received from the peers must be mined with it too.
+    pub fn proof_of_work(mut self, proof_of_work: ProofOfWork) -> Self {
+        self.proof_of_work = proof_of_work;
+        self
+    }
+
+    /// This method pre-funds the given account in the genesis block, see `BlockChain::with_genesis_allocations()`;
+    /// the allocations are put in the genesis block in the order they're added.
+    pub fn allocate(mut self, account: Account, amount: Amount) -> Self {
+        self.allocations.push((account, amount));
+        self
+    }
+
+    /// This method mines the genesis block and generates the `BlockChain`.
+    /// 
+    /// The method returns a `Result<BlockChain, MiningError>`: if no nonce satisfies the difficulty,
+    /// a `MiningError::Exhausted` error is returned.
+    pub fn build(self) -> Result<BlockChain, MiningError> {
+        let allocations = self.allocations
+            .into_iter()
+            .map(|(account, amount)| Transaction::coinbase(account, amount))
+            .collect();
+
+        let genesis_block = Block::unmined_on_chain(
+            0,
+            [0; 64],
+            allocations,
+            self.difficulty,
+            self.chain_id,
+            self.genesis_time.unwrap_or_else(Utc::now),
+        ).mine_up_to_with(u128::MAX, self.proof_of_work)?;
+
+        let mut blockchain = BlockChain::with_genesis_block(self.transactions_per_block, self.difficulty, genesis_block);
+        blockchain.proof_of_work = self.proof_of_work;
+        blockchain.set_block_reward(self.block_reward);
+        blockchain.set_halving_interval(self.halving_interval);
+
+        Ok(blockchain)
+    }
+}
+
+/// A read-only view of a `BlockChain`, returned by `BlockChain::view()`: it borrows the blockchain
+/// and has only the methods reading it, so whoever holds a `ChainView` can't change the chain.
+/// 
+/// The private field of the struct is the borrowed `BlockChain`.
+#[derive(Debug, Clone, Copy)]
+pub struct ChainView<'a> {
+    blockchain: &'a BlockChain,
+}
+
+impl<'a> ChainView<'a> {
+    /// This method returns the height of the chain, see `BlockChain::height()`.
+    pub fn height(&self) -> usize {
+        self.blockchain.height()
+    }
+
+    /// This method returns the last block of the chain, see `BlockChain::tip()`.
+    pub fn tip(&self) -> &'a Block {
+        self.blockchain.tip()
+    }
+
+    /// This method returns the balance of the given account, see `BlockChain::balance_of()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::account::Account;
+    /// let quinn = Account::new("Quinn", "Rask", "qu1nn_r4sk").unwrap();
+    /// 
+    /// let blockchain = BlockChain::with_genesis_allocations(5, 4, vec![(quinn.clone(), 12)]);
+    /// 
+    /// assert_eq!(blockchain.view().balance_of(&quinn), 12);
+    /// ```
+    pub fn balance_of(&self, account: &Account) -> Balance {
+        self.blockchain.balance_of(account)
+    }
+
+    /// This method returns the block with the given index, see `BlockChain::block_by_index()`.
+    pub fn block_by_index(&self, index: usize) -> Option<&'a Block> {
+        self.blockchain.block_by_index(index)
+    }
+}
+
+/// The `Clock` of a `BlockChain`, which is the `SystemClock` by default: it doesn't take part in the comparisons,
+/// and a clone of the blockchain shares the same clock.
+#[derive(Clone)]
+struct ChainClock(Arc<dyn Clock>);
+
+impl Default for ChainClock {
+    fn default() -> Self {
+        Self(Arc::new(SystemClock))
+    }
+}
+
+impl PartialEq for ChainClock {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl fmt::Debug for ChainClock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ChainClock").field(&self.0.now()).finish()
+    }
+}
+
+/// The outcome of `BlockChain::accept_block()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AcceptOutcome {
+    Appended,
+    Orphan,
+    Rejected,
+}
+
+/// An enum to handle errors generated while putting a `Block` in a `BlockChain` with `BlockChain::append_block()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChainError {
+    BadLink,
+    BadIndex,
+    InvalidBlock,
+}
+
+impl fmt::Display for ChainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::BadLink => write!(f, "The block isn't linked to the last block of the chain."),
+            Self::BadIndex => write!(f, "The block doesn't have the next index."),
+            Self::InvalidBlock => write!(f, "Invalid block."),
+        }
+    }
+}
+
+impl error::Error for ChainError {}
+
+/// An enum to handle the reasons a `Block` is rejected by `BlockChain::submit_external_block()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectReason {
+    WrongChain,
+    WrongIndex,
+    BadPrevHash,
+    OutdatedVersion,
+    InsufficientWork,
+    BadTimestamp,
+    NonCanonicalOrder,
+    InvalidTransaction(ValidationError),
+}
+
+impl fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::WrongChain => write!(f, "The block is mined for another chain."),
+            Self::WrongIndex => write!(f, "The block doesn't have the next index."),
+            Self::BadPrevHash => write!(f, "The block isn't linked to the last block of the chain."),
+            Self::OutdatedVersion => write!(f, "The version of the block is outdated."),
+            Self::InsufficientWork => write!(f, "The proof of work of the block isn't valid."),
+            Self::BadTimestamp => write!(f, "Invalid block time."),
+            Self::NonCanonicalOrder => write!(f, "The transactions of the block aren't in the canonical order."),
+            Self::InvalidTransaction(e) => write!(f, "Invalid transaction: {}", e),
+        }
+    }
+}
+
+impl error::Error for RejectReason {}
+
+/// A defect of a chain, returned by `BlockChain::validation_errors()`;
+/// every defect but `MissingGenesis` has the index of the block in the chain:
+/// - `MissingGenesis` if the first block isn't a genesis block
+/// - `InvalidBlock` if the hash or the proof of work of the block isn't valid, see `Block::verify()`
+/// - `WrongIndex` if the index of the block isn't its position in the chain
+/// - `FutureBlock` if the block is too far ahead of the current time
+/// - `BadLink` if the block isn't linked to the hash of the previous block
+/// - `BadTimestamp` if the block doesn't come after the previous block
+/// - `WrongChain` if the block, or a transaction of the block, is made for another chain
+/// - `DuplicateTransaction` if a transaction is in the block more than once, see `Block::has_unique_transactions()`
+/// - `NonCanonicalOrder` if the transactions of the block aren't in the canonical order required by its version, see `Block::is_canonically_ordered()`
+/// - `InvalidSignature` if a transaction of the block isn't signed by its sender, see `Block::verify_signatures_batch()`
+/// - `InsufficientFunds` if the sender of a transaction of the block spends more than its balance after the previous blocks
+/// - `WrongTarget` if the block isn't mined with `DEFAULT_TARGET`, see `Block::target()`
+/// - `OutdatedVersion` if the block has an older version than the previous block, see `Block::version()`:
+///   only the blocks of the old storage before the first newer block keep the rules of their version, e.g. the order of their transactions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChainDefect {
+    MissingGenesis,
+    InvalidBlock(usize),
+    WrongIndex(usize),
+    FutureBlock(usize),
+    BadLink(usize),
+    BadTimestamp(usize),
+    WrongChain(usize),
+    DuplicateTransaction(usize),
+    NonCanonicalOrder(usize),
+    InvalidSignature(usize),
+    InsufficientFunds(usize),
+    WrongTarget(usize),
+    OutdatedVersion(usize),
+}
+
+impl fmt::Display for ChainDefect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::MissingGenesis => write!(f, "The chain doesn't start with a genesis block."),
+            Self::InvalidBlock(index) => write!(f, "The block #{} isn't valid.", index),
+            Self::WrongIndex(index) => write!(f, "The block #{} has the wrong index.", index),
+            Self::FutureBlock(index) => write!(f, "The block #{} is too far in the future.", index),
+            Self::BadLink(index) => write!(f, "The block #{} isn't linked to the previous block.", index),
+            Self::BadTimestamp(index) => write!(f, "The block #{} doesn't come after the previous block.", index),
+            Self::WrongChain(index) => write!(f, "The block #{} is made for another chain.", index),
+            Self::DuplicateTransaction(index) => write!(f, "The block #{} has a duplicate transaction.", index),
+            Self::NonCanonicalOrder(index) => write!(f, "The transactions of the block #{} aren't in the canonical order.", index),
+            Self::InvalidSignature(index) => write!(f, "A transaction of the block #{} isn't signed by its sender.", index),
+            Self::InsufficientFunds(index) => write!(f, "A sender of the block #{} spends more than its balance.", index),
+            Self::WrongTarget(index) => write!(f, "The block #{} isn't mined with the default target.", index),
+            Self::OutdatedVersion(index) => write!(f, "The block #{} has an older version than the previous block.", index),
+        }
+    }
+}
+
+impl error::Error for ChainDefect {}
+
+/// A snapshot of the balances of all the accounts in a `BlockChain`, up to a block of the chain,
+/// computed by `BlockChain::snapshot()`.
+/// 
+/// The balances are indexed by the address of the accounts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BalanceSnapshot {
+    height: usize,
+    tip: [u8; 64],
+    balances: HashMap<String, Balance>,
+}
+
+impl BalanceSnapshot {
+    /// This method returns the index of the last block counted in the snapshot.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// assert_eq!(blockchain.snapshot().height(), 1);
+    /// ```
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// This method returns the balances of the accounts, indexed by their addresses,
+    /// since the `balances` field isn't `pub`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::account::Account;
+    /// let abe = Account::new("Abe", "Bell", "4b3_b3ll").unwrap();
+    /// 
+    /// let blockchain = BlockChain::with_genesis_allocations(5, 4, vec![(abe.clone(), 12)]);
+    /// 
+    /// assert_eq!(blockchain.snapshot().balances()[&abe.address()], 12);
+    /// ```
+    pub fn balances(&self) -> &HashMap<String, Balance> {
+        &self.balances
+    }
+}
+
+/// An unspent output of a transaction, returned by `BlockChain::utxo_set()`:
+/// - the hash of the transaction
+/// - the index of the output in the outputs of the transaction
+/// - the address of the receiver of the output
+/// - the amount of the output which isn't spent yet
+#[derive(Debug, Clone, PartialEq)]
+pub struct Utxo {
+    pub tx_hash: [u8; 64],
+    pub output_index: usize,
+    pub owner: String,
+    pub amount: Amount,
+}
+
+/// Adds the transactions of a block to the balances of a snapshot, like `BlockChain::balance_of()`.
+fn apply_block(balances: &mut HashMap<String, Balance>, block: &Block) {
+    for transaction in block.transactions() {
+        if let Some(sender) = &transaction.sender {
+            *balances.entry(sender.address()).or_insert(0) -= cost(transaction) as Balance;
+        }
+
+        for (receiver, amount) in transaction.outputs() {
+            *balances.entry(receiver.address()).or_insert(0) += *amount as Balance;
+        }
+    }
+}
+
+/// The balances and the nonces of the accounts after the first blocks of a chain, used to check
+/// the transactions of the next block against the state of the chain, see `BlockChain::check_block_on()`.
+#[derive(Debug, Default)]
+struct Ledger {
+    balances: HashMap<[u8; 32], Balance>,
+    next_nonces: HashMap<[u8; 32], u64>,
+    // the outputs of the coinbase transactions after the genesis block, with the index of their block
+    rewards: Vec<(usize, [u8; 32], Amount)>,
+}
+
+impl Ledger {
+    /// Replays the blocks of a chain, which aren't checked.
+    fn of(chain: &[Block]) -> Self {
+        let mut ledger = Ledger::default();
+
+        for block in chain {
+            ledger.apply(block);
+        }
+
+        ledger
+    }
+
+    /// Adds the transactions of a block to the balances and the nonces, like `BlockChain::balance_of()`.
+    fn apply(&mut self, block: &Block) {
+        for transaction in block.transactions() {
+            if let Some(sender) = &transaction.sender {
+                let public_key = sender.public_key();
+
+                *self.balances.entry(public_key).or_insert(0) -= cost(transaction) as Balance;
+
+                let next_nonce = self.next_nonces.entry(public_key).or_insert(0);
+                *next_nonce = (*next_nonce).max(transaction.nonce().saturating_add(1));
+            }
+
+            for (receiver, amount) in transaction.outputs() {
+                *self.balances.entry(receiver.public_key()).or_insert(0) += *amount as Balance;
+
+                if transaction.is_coinbase() && block.index() > 0 {
+                    self.rewards.push((block.index(), receiver.public_key(), *amount));
+                }
+            }
+        }
+    }
+
+    /// Checks if the account with the given public key was the sender or a receiver of a transaction.
+    fn knows(&self, public_key: &[u8; 32]) -> bool {
+        self.balances.contains_key(public_key)
+    }
+
+    /// Checks that the nonces of every sender in the block are the nonces following its last nonce, without gaps,
+    /// whatever the order of the transactions in the block.
+    fn check_nonces(&self, block: &Block) -> Result<(), ValidationError> {
+        let mut counts: HashMap<[u8; 32], u64> = HashMap::new();
+
+        for sender in block.transactions().iter().filter_map(|transaction| transaction.sender.as_ref()) {
+            *counts.entry(sender.public_key()).or_insert(0) += 1;
+        }
+
+        let mut used = HashSet::new();
+
+        for transaction in block.transactions() {
+            if let Some(sender) = &transaction.sender {
+                let public_key = sender.public_key();
+                let next_nonce = self.next_nonces.get(&public_key).copied().unwrap_or(0);
+
+                // the nonces are all different and they're as many as the transactions, so there's no gap
+                let in_range = transaction.nonce() >= next_nonce && transaction.nonce() - next_nonce < counts[&public_key];
+
+                if !in_range || !used.insert((public_key, transaction.nonce())) {
+                    return Err(ValidationError::InvalidNonce);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the transactions of every sender in the block don't spend more than its balance before the block,
+    /// which must not count the rewards which aren't mature in the block, see `BlockChain::set_coinbase_maturity()`;
+    /// the money received in the block can't be spent in the same block.
+    fn check_spends(&self, block: &Block, coinbase_maturity: usize) -> Result<(), ValidationError> {
+        let mut spent: HashMap<[u8; 32], Amount> = HashMap::new();
+
+        for transaction in block.transactions() {
+            if let Some(sender) = &transaction.sender {
+                let public_key = sender.public_key();
+
+                let spent = spent.entry(public_key).or_insert(0);
+                *spent = spent.saturating_add(cost(transaction));
+
+                let balance = i128::from(self.balances.get(&public_key).copied().unwrap_or(0));
+
+                if i128::from(*spent) > balance {
+                    return Err(ValidationError::InvalidAmount);
+                }
+
+                let immature: Amount = self.rewards
+                    .iter()
+                    .filter(|(index, receiver, _)| *receiver == public_key && block.index().saturating_sub(index + 1) < coinbase_maturity)
+                    .map(|(_, _, amount)| amount)
+                    .sum();
+
+                if i128::from(*spent) > balance - i128::from(immature) {
+                    return Err(ValidationError::ImmatureCoinbase);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns what the sender of a transaction pays, i.e. the amount of the transaction plus the fee.
+fn cost(transaction: &Transaction) -> Amount {
+    transaction.amount().saturating_add(transaction.fee())
+}
+
+/// Sums the work of the blocks of a chain, see `BlockChain::total_work()`.
+fn chain_work(chain: &[Block]) -> u128 {
+    chain
+        .iter()
+        .map(|block| 1_u128.checked_shl(block.difficulty() as u32).unwrap_or(u128::MAX))
+        .fold(0, u128::saturating_add)
+}
+
+/// Checks if the account with the given public key is the sender of the transaction.
+fn is_sender(transaction: &Transaction, public_key: &[u8; 32]) -> bool {
+    transaction.sender.as_ref().is_some_and(|sender| sender.public_key() == *public_key)
+}
+
+/// Checks if the account with the given public key is the sender or one of the receivers of the transaction.
+fn involves(transaction: &Transaction, public_key: &[u8; 32]) -> bool {
+    is_sender(transaction, public_key) || transaction.outputs().iter().any(|(receiver, _)| receiver.public_key() == *public_key)
+}
+
+/// Checks if a chain is valid: every block must be valid, must have the right index,
+/// must be linked to the hash of the previous block and must come after it,
+/// can't be more than `max_future_drift` ahead of the current time `now`, and must be made for the chain with the given identifier
+/// and mined with its proof of work function.
+fn is_valid_chain(chain: &[Block], now: DateTime<Utc>, max_future_drift: Duration, chain_id: u32, proof_of_work: ProofOfWork) -> bool {
+    chain_defects(chain, now, max_future_drift, chain_id, proof_of_work).next().is_none()
+}
+
+/// Returns the defects of the chain, checking the blocks one by one, see `BlockChain::validation_errors()`.
+fn chain_defects(chain: &[Block], now: DateTime<Utc>, max_future_drift: Duration, chain_id: u32, proof_of_work: ProofOfWork) -> impl Iterator<Item = ChainDefect> + '_ {
+    let genesis = if has_genesis(chain) {
+        None
+    } else {
+        Some(ChainDefect::MissingGenesis)
+    };
+
+    // the balances are replayed while the blocks are checked
+    let mut ledger = Ledger::default();
+
+    genesis.into_iter().chain(
+        chain
+            .iter()
+            .enumerate()
+            .flat_map(move |(i, block)| {
+                let defects = block_defects(chain, i, &ledger, now, max_future_drift, chain_id, proof_of_work);
+
+                ledger.apply(block);
+
+                defects
+            })
+    )
+}
+
+/// Returns the defects of the block in the given position of the chain, comparing it with the previous block,
+/// and checking its transactions against the balances of the `Ledger` replaying the blocks before it.
+fn block_defects(chain: &[Block], index: usize, ledger: &Ledger, now: DateTime<Utc>, max_future_drift: Duration, chain_id: u32, proof_of_work: ProofOfWork) -> Vec<ChainDefect> {
+    let block = &chain[index];
+    let mut defects = Vec::new();
+
+    if !block.verify_hashes_with(proof_of_work) {
+        defects.push(ChainDefect::InvalidBlock(index));
+    }
+
+    // the target changes which hashes satisfy the proof of work, so it's the same for every block
+    if block.target() != DEFAULT_TARGET {
+        defects.push(ChainDefect::WrongTarget(index));
+    }
+
+    if !block.verify_signatures_batch() {
+        defects.push(ChainDefect::InvalidSignature(index));
+    }
+
+    // the coinbase maturity isn't known without the settings of the chain, so only the balances are checked
+    if ledger.check_spends(block, 0).is_err() {
+        defects.push(ChainDefect::InsufficientFunds(index));
+    }
+
+    if block.index() != index {
+        defects.push(ChainDefect::WrongIndex(index));
+    }
+
+    if !on_time(block, now, max_future_drift) {
+        defects.push(ChainDefect::FutureBlock(index));
+    }
+
+    if !on_chain(block, chain_id) {
+        defects.push(ChainDefect::WrongChain(index));
+    }
+
+    if !block.has_unique_transactions() {
+        defects.push(ChainDefect::DuplicateTransaction(index));
+    }
+
+    if !block.has_required_order() {
+        defects.push(ChainDefect::NonCanonicalOrder(index));
+    }
+
+    if let Some(previous) = index.checked_sub(1).map(|i| &chain[i]) {
+        if block.prev_hash() != *previous.hash_ref() {
+            defects.push(ChainDefect::BadLink(index));
+        }
+
+        if block.time() <= previous.time() {
+            defects.push(ChainDefect::BadTimestamp(index));
+        }
+
+        // an older version would exempt a new block from the rules of the newer versions
+        if block.version() < previous.version() {
+            defects.push(ChainDefect::OutdatedVersion(index));
+        }
+    }
+
+    defects
+}
+
+/// Like `is_valid_chain()`, but the blocks are verified in parallel,
+/// and only the links between them are checked sequentially.
+fn is_valid_chain_parallel(chain: &[Block], now: DateTime<Utc>, max_future_drift: Duration, chain_id: u32, proof_of_work: ProofOfWork) -> bool {
+    has_genesis(chain)
+        && chain.par_iter().enumerate().all(|(i, block)| is_valid_block(block, i, now, max_future_drift, chain_id, proof_of_work))
+        && is_linked(chain)
+        && is_funded(chain)
+}
+
+/// Checks if the first block of the chain is a genesis block, which isn't linked to any block.
+fn has_genesis(chain: &[Block]) -> bool {
+    chain.first().is_some_and(|genesis| genesis.index() == 0 && genesis.prev_hash() == [0; 64])
+}
+
+/// Checks if a block, regardless of the other blocks, is valid in the given position of the chain.
+fn is_valid_block(block: &Block, index: usize, now: DateTime<Utc>, max_future_drift: Duration, chain_id: u32, proof_of_work: ProofOfWork) -> bool {
+    block.verify_with(proof_of_work)
+        && block.target() == DEFAULT_TARGET
+        && block.index() == index
+        && on_time(block, now, max_future_drift)
+        && on_chain(block, chain_id)
+        && block.has_unique_transactions()
+        && block.has_required_order()
+}
+
+/// Checks if a block and its signed transactions are made for the chain with the given identifier.
+fn on_chain(block: &Block, chain_id: u32) -> bool {
+    block.chain_id() == chain_id
+        && block.transactions().iter().all(|transaction| transaction.is_coinbase() || transaction.chain_id() == chain_id)
+}
+
+/// Checks if the time of a block isn't ahead of the current time by more than the maximum drift.
+fn on_time(block: &Block, now: DateTime<Utc>, max_future_drift: Duration) -> bool {
+    // a negative difference means the block is in the past
+    (block.time() - now).to_std().map_or(true, |ahead| ahead <= max_future_drift)
+}
+
+/// Checks if the senders of the transactions of every block of the chain have the money they spend,
+/// replaying the balances from the genesis block, see `Ledger::check_spends()`.
+fn is_funded(chain: &[Block]) -> bool {
+    let mut ledger = Ledger::default();
+
+    chain.iter().all(|block| {
+        let funded = ledger.check_spends(block, 0).is_ok();
+
+        ledger.apply(block);
+
+        funded
+    })
+}
+
+/// Checks if every block of the chain is linked to the previous block and comes after it,
+/// without an older version than the previous block.
+fn is_linked(chain: &[Block]) -> bool {
+    chain.windows(2).all(|pair| {
+        pair[1].prev_hash() == *pair[0].hash_ref() && pair[1].time() > pair[0].time() && pair[1].version() >= pair[0].version()
+    })
+}
+
+/// An enum to handle errors generated while loading a `BlockChain` from a file.
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    Parse(serde_json::Error),
+    Corrupt,
+    Defect(ChainDefect),
+}
+
+#[cfg(feature = "serde")]
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Can't read the file: {}", e),
+            Self::Parse(e) => write!(f, "Can't parse the blockchain: {}", e),
+            Self::Corrupt => write!(f, "The blockchain isn't valid."),
+            Self::Defect(defect) => write!(f, "The blockchain isn't valid: {}", defect),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl error::Error for LoadError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use crate::transaction::TxError;
+
+    #[test]
+    fn every_output_is_credited() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let first = Account::new("First", "Receiver", "first_password").unwrap();
+        let second = Account::new("Second", "Receiver", "second_password").unwrap();
+        sender.add_money(10);
+
+        let mut blockchain = BlockChain::with_genesis_allocations(1, 4, vec![(sender.clone(), 10)]);
+
+        let transaction = Transaction::new_with_outputs(sender.clone(), vec![(first.clone(), 2), (second.clone(), 3)], 0, "sender_password").unwrap();
+
+        blockchain.add_pending(transaction).unwrap();
+        blockchain.mine_pending().unwrap();
+
+        assert_eq!(blockchain.balance_of(&first), 2);
+        assert_eq!(blockchain.balance_of(&second), 3);
+        assert_eq!(blockchain.balance_of(&sender), 5);
+    }
+
+    #[test]
+    fn unlinked_chain_is_not_valid() {
+        let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+        blockchain.add_block(Vec::new()).unwrap();
+
+        assert!(blockchain.is_valid());
+
+        blockchain.chain[1] = Block::new_with_difficulty(1, [1; 64], Vec::new(), 4);
+
+        assert!(!blockchain.is_valid());
+    }
+
+    #[test]
+    fn linked_genesis_is_not_valid() {
+        let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+
+        // a genesis block linked to a fake block, and a valid chain on top of it
+        let genesis = Block::new_with_difficulty(0, [1; 64], Vec::new(), 4);
+        let block = Block::new_with_difficulty(1, genesis.hash(), Vec::new(), 4);
+
+        blockchain.chain = vec![genesis, block];
+
+        assert!(!blockchain.is_valid());
+        assert!(!blockchain.is_valid_parallel());
+    }
+
+    #[test]
+    fn pruned_chain_is_valid() {
+        let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+        blockchain.set_miner(Account::new("Pru", "Ning", "pru_ning").unwrap());
+
+        for _ in 0..3 {
+            blockchain.mine_pending().unwrap();
+        }
+
+        let pruned: Vec<[u8; 64]> = blockchain.chain[..2].iter().flat_map(|block| block.transactions()).map(|t| t.hash()).collect();
+
+        blockchain.prune_before(2);
+
+        assert!(blockchain.is_valid());
+        assert!(blockchain.is_valid_parallel());
+        assert!(blockchain.transactions().all(|t| !pruned.contains(&t.hash())));
+        assert_eq!(blockchain.transactions().count(), 2);
+    }
+
+    #[test]
+    fn empty_blocks_are_mined_back_to_back() {
+        let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+        blockchain.add_block(Vec::new()).unwrap();
+
+        let height = blockchain.height();
+
+        blockchain.mine_n_empty(4).unwrap();
+
+        assert_eq!(blockchain.height(), height + 4);
+        assert!(blockchain.chain[height + 1..].iter().all(|block| block.transactions().is_empty()));
+        assert!(blockchain.is_valid());
+    }
+
+    #[test]
+    fn block_on_the_wrong_parent_is_not_appended() {
+        let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+        blockchain.add_block(Vec::new()).unwrap();
+
+        let stale = Block::new_with_difficulty(2, blockchain.chain[0].hash(), Vec::new(), 4);
+        let misplaced = Block::new_with_difficulty(3, blockchain.chain[1].hash(), Vec::new(), 4);
+
+        assert_eq!(blockchain.append_block(stale), Err(ChainError::BadLink));
+        assert_eq!(blockchain.append_block(misplaced), Err(ChainError::BadIndex));
+        assert_eq!(blockchain.height(), 1);
+
+        let block = Block::new_with_difficulty(2, blockchain.chain[1].hash(), Vec::new(), 4);
+
+        assert_eq!(blockchain.append_block(block), Ok(()));
+    }
+
+    #[test]
+    fn parallel_validation_matches_sequential() {
+        let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+
+        for _ in 0..4 {
+            blockchain.add_block(Vec::new()).unwrap();
+        }
+
+        assert!(blockchain.is_valid());
+        assert!(blockchain.is_valid_parallel());
+
+        let mut unlinked = blockchain.clone();
+        unlinked.chain[2] = Block::new_with_difficulty(2, [1; 64], Vec::new(), 4);
+
+        let mut misplaced = blockchain.clone();
+        misplaced.chain.swap(1, 3);
+
+        for tampered in [unlinked, misplaced].iter() {
+            assert!(!tampered.is_valid());
+            assert!(!tampered.is_valid_parallel());
+        }
+    }
+
+    #[test]
+    fn block_is_capped_by_size() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(100);
+
+        let mut blockchain = BlockChain::with_genesis_allocations(10, 4, vec![(sender.clone(), 100)]);
+
+        for nonce in 0..5 {
+            let outputs = vec![(receiver.clone(), 1); 10];
+
+            blockchain.add_pending(Transaction::new_with_outputs(sender.clone(), outputs, 0, "sender_password").unwrap().with_nonce(nonce)).unwrap();
+        }
+
+        let size: usize = blockchain.pending()[..3].iter().map(|t| t.size_bytes()).sum();
+        blockchain.set_max_block_bytes(blockchain.next_block(1, Vec::new(), 4).size_bytes() + size);
+        blockchain.mine_pending().unwrap();
+
+        assert_eq!(blockchain.chain[1].transactions().len(), 3);
+        assert!(blockchain.chain[1].size_bytes() <= blockchain.max_block_bytes);
+        assert_eq!(blockchain.pending().len(), 2);
+    }
+
+    #[test]
+    fn snapshot_matches_balance_of() {
+        let mut first = Account::new("First", "Test", "first_password").unwrap();
+        let second = Account::new("Second", "Test", "second_password").unwrap();
+        let third = Account::new("Third", "Test", "third_password").unwrap();
+        let miner = Account::new("Miner", "Test", "miner_password").unwrap();
+        first.add_money(10);
+
+        let mut blockchain = BlockChain::with_genesis_allocations(2, 0, vec![(first.clone(), 10)]);
+        blockchain.set_miner(miner.clone());
+        blockchain.set_block_reward(5);
+
+        blockchain.add_pending(Transaction::new_with_fee(first.clone(), second.clone(), 3, 1, "first_password").unwrap()).unwrap();
+        blockchain.add_pending(Transaction::new_with_outputs(first.clone(), vec![(second.clone(), 1), (third.clone(), 2)], 0, "first_password").unwrap().with_nonce(1)).unwrap();
+        blockchain.mine_pending().unwrap();
+        blockchain.mine_pending().unwrap();
+
+        let snapshot = blockchain.snapshot();
+
+        for account in [first, second, third, miner].iter() {
+            assert_eq!(blockchain.balance_of_snapshot(&snapshot, account), Some(blockchain.balance_of(account)));
+        }
+    }
+
+    #[test]
+    fn orphans_are_connected_in_any_order() {
+        let mut blockchain = BlockChain::new_with_difficulty(2, 4);
+        let mut peer = blockchain.clone();
+
+        for _ in 0..3 {
+            peer.add_block(Vec::new()).unwrap();
+        }
+
+        assert_eq!(blockchain.accept_block(peer.chain[3].clone()), AcceptOutcome::Orphan);
+        assert_eq!(blockchain.accept_block(peer.chain[2].clone()), AcceptOutcome::Orphan);
+        assert_eq!(blockchain.accept_block(peer.chain[3].clone()), AcceptOutcome::Rejected); // already kept
+        assert_eq!(blockchain.accept_block(peer.chain[1].clone()), AcceptOutcome::Appended);
+
+        assert_eq!(blockchain.chain, peer.chain);
+        assert!(blockchain.orphans.is_empty());
+    }
+
+    #[test]
+    fn backwards_block_is_not_valid() {
+        let genesis = Block::mined_at(0, [0; 64], Vec::new(), 0, Utc::now());
+        let block = Block::mined_at(1, genesis.hash(), Vec::new(), 0, genesis.time() - chrono::Duration::seconds(1));
+
+        assert!(!is_valid_chain(&[genesis, block], Utc::now(), DEFAULT_MAX_FUTURE_DRIFT, 0, ProofOfWork::Sha512));
+    }
+
+    #[test]
+    fn future_block_is_not_valid() {
+        let genesis = Block::mined_at(0, [0; 64], Vec::new(), 0, Utc::now());
+        let block = Block::mined_at(1, genesis.hash(), Vec::new(), 0, genesis.time() + chrono::Duration::hours(3));
+        let chain = [genesis, block];
+
+        assert!(!is_valid_chain(&chain, Utc::now(), DEFAULT_MAX_FUTURE_DRIFT, 0, ProofOfWork::Sha512));
+        assert!(is_valid_chain(&chain, Utc::now(), Duration::from_secs(4 * 60 * 60), 0, ProofOfWork::Sha512));
+    }
+
+    #[test]
+    fn highest_fee_is_mined_first() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let mut other = Account::new("Other", "Test", "other_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        let miner = Account::new("Miner", "Test", "miner_password").unwrap();
+        sender.add_money(10);
+        other.add_money(10);
+
+        let mut blockchain = BlockChain::with_genesis_allocations(1, 0, vec![(sender.clone(), 10), (other.clone(), 10)]);
+        blockchain.set_miner(miner.clone());
+        blockchain.set_block_reward(50);
+
+        // the senders are different, since the transactions of a sender are mined in the order of their nonces
+        let low_fee = Transaction::new_with_fee(sender, receiver.clone(), 1, 1, "sender_password").unwrap();
+        let high_fee = Transaction::new_with_fee(other, receiver, 1, 5, "other_password").unwrap();
+
+        blockchain.add_pending(low_fee.clone()).unwrap();
+        blockchain.add_pending(high_fee.clone()).unwrap();
+
+        blockchain.mine_pending().unwrap();
+
+        assert_eq!(blockchain.chain[1].transactions()[1], high_fee);
+        assert_eq!(blockchain.pending(), &[low_fee]);
+        assert_eq!(blockchain.balance_of(&miner), 55);
+    }
+
+    #[test]
+    fn mined_transactions_are_sorted_canonically() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(10);
+
+        let mut blockchain = BlockChain::with_genesis_allocations(2, 0, vec![(sender.clone(), 10)]);
+        blockchain.set_miner(receiver.clone());
+
+        let older = Transaction::new_with_fee(sender.clone(), receiver.clone(), 1, 1, "sender_password").unwrap();
+        let newer = Transaction::new_with_fee(sender, receiver, 1, 5, "sender_password").unwrap().with_nonce(1);
+
+        blockchain.add_pending(older.clone()).unwrap();
+        blockchain.add_pending(newer.clone()).unwrap();
+
+        blockchain.mine_pending().unwrap();
+
+        // the fees decide which transactions are mined, not their order in the block, where they're sorted by nonce
+        let expected = [older, newer];
+
+        assert!(blockchain.chain[1].transactions()[0].is_coinbase());
+        assert_eq!(&blockchain.chain[1].transactions()[1..], &expected[..]);
+    }
+
+    #[test]
+    fn ties_are_broken_by_the_smaller_tip_hash() {
+        let genesis = BlockChain::new_with_difficulty(5, 4);
+
+        let mut first = genesis.clone();
+        first.add_block(Vec::new()).unwrap();
+
+        let mut second = genesis.clone();
+        second.add_block(Vec::new()).unwrap();
+
+        let mut one_way = genesis.clone();
+        assert!(one_way.replace_chain(first.chain()));
+        one_way.replace_chain(second.chain());
+
+        let mut other_way = genesis;
+        assert!(other_way.replace_chain(second.chain()));
+        other_way.replace_chain(first.chain());
+
+        let smaller = first.tip_hash().min(second.tip_hash());
+
+        assert_eq!(one_way.chain(), other_way.chain());
+        assert_eq!(one_way.tip_hash(), smaller);
+    }
+
+    #[test]
+    fn invalid_longer_chain_is_rejected() {
+        let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+
+        let mut fork = blockchain.clone();
+        fork.add_block(Vec::new()).unwrap();
+        fork.add_block(Vec::new()).unwrap();
+        fork.chain[2] = Block::new_with_difficulty(2, [1; 64], Vec::new(), 4);
+
+        assert!(!blockchain.replace_chain(fork.chain()));
+        assert_eq!(blockchain.chain().len(), 1);
+    }
+
+    #[test]
+    fn heavier_chain_wins_over_longer_chain() {
+        let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+
+        let mut heavy = blockchain.clone();
+        heavy.difficulty = 8;
+        heavy.add_block(Vec::new()).unwrap();
+
+        blockchain.difficulty = 1;
+
+        for _ in 0..3 {
+            blockchain.add_block(Vec::new()).unwrap();
+        }
+
+        let light = blockchain.chain();
+
+        assert!(blockchain.replace_chain(heavy.chain()));
+        assert_eq!(blockchain.chain(), heavy.chain());
+        assert!(!blockchain.replace_chain(light));
+        assert!(!blockchain.replace_chain(heavy.chain())); // the same work keeps the current chain
+    }
+
+    #[test]
+    fn reward_floors_to_zero() {
+        let mut blockchain = BlockChain::new_with_difficulty(5, 0);
+        blockchain.set_block_reward(Amount::MAX);
+        blockchain.set_halving_interval(1);
+
+        assert_eq!(blockchain.reward_at(63), 1);
+        assert_eq!(blockchain.reward_at(64), 0);
+        assert_eq!(blockchain.reward_at(usize::MAX), 0);
+    }
+
+    #[test]
+    fn supply_follows_halving_schedule() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(10);
+
+        let mut blockchain = BlockChain::with_genesis_allocations(1, 0, vec![(sender.clone(), 10)]);
+        blockchain.set_miner(Account::new("Miner", "Test", "miner_password").unwrap());
+        blockchain.set_block_reward(50);
+        blockchain.set_halving_interval(2);
+
+        blockchain.add_pending(Transaction::new_with_fee(sender, receiver, 1, 5, "sender_password").unwrap()).unwrap();
+
+        for _ in 0..5 {
+            blockchain.mine_pending().unwrap();
+        }
+
+        let expected: Amount = 10 + (1..=5).map(|index| blockchain.reward_at(index)).sum::<Amount>(); // with the allocation of the sender
+
+        assert_eq!(blockchain.height(), 5);
+        assert_eq!(blockchain.total_supply(), expected);
+    }
+
+    #[test]
+    fn spent_output_leaves_the_utxo_set() {
+        let mut miner = Account::new("Miner", "Test", "miner_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+
+        let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+        blockchain.set_miner(miner.clone());
+        blockchain.set_block_reward(50);
+        blockchain.mine_pending().unwrap();
+
+        let reward = blockchain.chain[1].transactions()[0].hash();
+
+        assert!(blockchain.utxo_set().iter().any(|utxo| utxo.tx_hash == reward));
+
+        miner.add_money(50);
+        blockchain.set_miner(receiver.clone());
+        blockchain.add_pending(Transaction::new(miner, receiver.clone(), 50, "miner_password").unwrap()).unwrap();
+        blockchain.mine_pending().unwrap();
+
+        let utxos = blockchain.utxo_set();
+
+        assert!(utxos.iter().all(|utxo| utxo.tx_hash != reward));
+        assert!(utxos.iter().all(|utxo| utxo.owner == receiver.address()));
+        assert_eq!(utxos.iter().map(|utxo| utxo.amount).sum::<Amount>(), 100);
+    }
+
+    #[test]
+    fn larger_blocks_are_mined_after_raising_the_cap() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(10);
+
+        let mut blockchain = BlockChain::with_genesis_allocations(2, 4, vec![(sender.clone(), 10)]);
+
+        for nonce in 0..6 {
+            blockchain.add_pending(Transaction::new(sender.clone(), receiver.clone(), 1, "sender_password").unwrap().with_nonce(nonce)).unwrap();
+        }
+
+        blockchain.mine_pending().unwrap();
+        blockchain.set_transactions_per_block(4);
+        blockchain.mine_pending().unwrap();
+
+        assert_eq!(blockchain.chain[1].transactions().len(), 2);
+        assert_eq!(blockchain.chain[2].transactions().len(), 4);
+        assert!(blockchain.pending().is_empty());
+        assert!(blockchain.is_valid());
+    }
+
+    #[test]
+    fn double_spend_is_excluded_from_the_block() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(10);
+
+        let mut blockchain = BlockChain::with_genesis_allocations(5, 4, vec![(sender.clone(), 10)]);
+        blockchain.add_block(vec![Transaction::coinbase(sender.clone(), 5)]).unwrap();
+
+        let first = Transaction::new(sender.clone(), receiver.clone(), 6, "sender_password").unwrap();
+        let second = Transaction::new(sender, receiver, 7, "sender_password").unwrap().with_nonce(1);
+
+        blockchain.add_pending(first.clone()).unwrap();
+        blockchain.add_pending(second.clone()).unwrap();
+
+        blockchain.rollback(); // the sender has only 10 again, so the two transactions spend the same money
+
+        let excluded = blockchain.mine_pending().unwrap();
+
+        assert_eq!(blockchain.chain[1].transactions(), &[first]);
+        assert_eq!(excluded, vec![second]);
+        assert!(blockchain.pending().is_empty());
+    }
+
+    #[test]
+    fn reward_is_spent_once_mature() {
+        let mut miner = Account::new("Miner", "Test", "miner_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+
+        let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+        blockchain.set_miner(miner.clone());
+        blockchain.set_block_reward(50);
+        blockchain.set_coinbase_maturity(3);
+
+        blockchain.mine_pending().unwrap();
+        miner.add_money(50);
+
+        let transaction = Transaction::new(miner.clone(), receiver.clone(), 30, "miner_password").unwrap();
+
+        assert_eq!(blockchain.add_pending(transaction.clone()), Err(ValidationError::ImmatureCoinbase));
+
+        blockchain.mine_pending().unwrap();
+        blockchain.mine_pending().unwrap();
+
+        assert_eq!(blockchain.add_pending(transaction.clone()), Err(ValidationError::ImmatureCoinbase));
+
+        blockchain.mine_pending().unwrap();
+
+        // the reward of the block #1 is mature, but the other rewards aren't
+        assert_eq!(blockchain.add_pending(transaction), Ok(()));
+        assert_eq!(
+            blockchain.add_pending(Transaction::new(miner, receiver, 30, "miner_password").unwrap().with_nonce(1)),
+            Err(ValidationError::ImmatureCoinbase),
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn chain_round_trips_through_jsonl() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(10);
+
+        let mut blockchain = BlockChain::with_genesis_allocations(5, 4, vec![(sender.clone(), 10)]);
+        blockchain.add_block(vec![Transaction::new(sender, receiver, 1, "sender_password").unwrap()]).unwrap();
+        blockchain.add_block(Vec::new()).unwrap();
+        blockchain.add_block(Vec::new()).unwrap();
+
+        let mut jsonl = Vec::new();
+        blockchain.export_jsonl(&mut jsonl).unwrap();
+
+        let mut imported = BlockChain::new_with_difficulty(5, 4);
+        imported.import_jsonl(&jsonl[..]).unwrap();
+
+        assert_eq!(imported.chain(), blockchain.chain());
+        assert_eq!(imported.height(), 3);
+    }
+
+    #[test]
+    fn appended_block_is_observed_once() {
+        let mut blockchain = BlockChain::new_with_difficulty(2, 4);
+        let mut peer = blockchain.clone();
+        peer.add_block(Vec::new()).unwrap();
+
+        let calls = std::sync::Arc::new(std::sync::Mutex::new(0));
+        let counter = std::sync::Arc::clone(&calls);
+        blockchain.on_block(Box::new(move |_| *counter.lock().unwrap() += 1));
+
+        assert!(blockchain.append_block(peer.chain[1].clone()).is_ok());
+        assert!(blockchain.append_block(peer.chain[1].clone()).is_err());
+
+        assert_eq!(*calls.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn chains_of_easier_blocks_are_rejected() {
+        let mut blockchain = BlockChain::new_with_difficulty(5, 8);
+        let mut easier = blockchain.clone();
+
+        blockchain.add_block(Vec::new()).unwrap();
+
+        // the blocks of the fork claim the work of their difficulty, but the chain requires a higher one
+        easier.set_difficulty(1);
+
+        for _ in 0..300 {
+            easier.add_block(Vec::new()).unwrap();
+        }
+
+        assert!(easier.total_work() > blockchain.total_work());
+        assert!(!blockchain.replace_chain(easier.chain()));
+        assert_eq!(blockchain.height(), 1);
+
+        // the difficulty adjustment is followed when replaying the chain
+        let mut blockchain = BlockChain::new_with_difficulty(5, 2);
+        blockchain.set_difficulty_adjustment(2, Duration::from_secs(600));
+
+        let mut adjusted = blockchain.clone();
+        let mut unadjusted = blockchain.clone();
+        unadjusted.set_difficulty_adjustment(0, Duration::from_secs(600));
+
+        for _ in 0..4 {
+            adjusted.add_block(Vec::new()).unwrap();
+            unadjusted.add_block(Vec::new()).unwrap();
+        }
+
+        // the blocks are mined way faster than 10 minutes each, so the block #4 requires a difficulty of 3
+        assert_eq!(adjusted.tip().difficulty(), 3);
+        assert!(!blockchain.replace_chain(unadjusted.chain()));
+        assert!(blockchain.replace_chain(adjusted.chain()));
+    }
+
+    #[test]
+    fn chain_with_free_genesis_is_valid() {
+        let mut blockchain = BlockChain::new_with_genesis_difficulty(5, 0, 8);
+        blockchain.add_block(Vec::new()).unwrap();
+        blockchain.add_block(Vec::new()).unwrap();
+
+        assert!(blockchain.chain[1..].iter().all(|block| block.difficulty() == 8));
+        assert!(blockchain.is_valid());
+        assert!(blockchain.is_valid_parallel());
+    }
+
+    #[test]
+    fn every_defect_is_reported() {
+        let mut blockchain = BlockChain::new_with_difficulty(5, 0);
+        let genesis = blockchain.chain[0].clone();
+
+        let backwards = Block::mined_at(1, genesis.hash(), Vec::new(), 0, genesis.time() - chrono::Duration::seconds(1));
+        let unlinked = Block::mined_at(2, [1; 64], Vec::new(), 0, genesis.time() + chrono::Duration::seconds(1));
+        blockchain.chain.extend(vec![backwards, unlinked]);
+
+        let defects: Vec<ChainDefect> = blockchain.validation_errors().collect();
+
+        assert_eq!(defects, vec![ChainDefect::BadTimestamp(1), ChainDefect::BadLink(2)]);
+        assert!(!blockchain.is_valid());
+    }
+
+    #[test]
+    fn external_block_is_rejected_for_every_reason() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        let miner = Account::new("Miner", "Test", "miner_password").unwrap();
+        sender.add_money(10);
+
+        let mut blockchain = BlockChain::with_genesis_allocations(5, 4, vec![(sender.clone(), 10)]);
+        let tip = blockchain.tip().clone();
+        let later = tip.time() + chrono::Duration::seconds(1);
+
+        let wrong_index = Block::mined_at(2, tip.hash(), Vec::new(), 4, later);
+        assert_eq!(blockchain.submit_external_block(wrong_index), Err(RejectReason::WrongIndex));
+
+        let unlinked = Block::mined_at(1, [1; 64], Vec::new(), 4, later);
+        assert_eq!(blockchain.submit_external_block(unlinked), Err(RejectReason::BadPrevHash));
+
+        let easy = Block::mined_at(1, tip.hash(), Vec::new(), 0, later);
+        assert_eq!(blockchain.submit_external_block(easy), Err(RejectReason::InsufficientWork));
+
+        let backwards = Block::mined_at(1, tip.hash(), Vec::new(), 4, tip.time());
+        assert_eq!(blockchain.submit_external_block(backwards), Err(RejectReason::BadTimestamp));
+
+        let future = Block::mined_at(1, tip.hash(), Vec::new(), 4, Utc::now() + chrono::Duration::days(1));
+        assert_eq!(blockchain.submit_external_block(future), Err(RejectReason::BadTimestamp));
+
+        let overspent = Transaction::new(sender.clone(), receiver.clone(), 50, "sender_password").unwrap();
+        let overspending = Block::mined_at(1, tip.hash(), vec![overspent], 4, later);
+        assert_eq!(blockchain.submit_external_block(overspending), Err(RejectReason::InvalidTransaction(ValidationError::InvalidAmount)));
+
+        let inflated = Block::mined_at(1, tip.hash(), vec![Transaction::coinbase(miner, 1)], 4, later);
+        assert_eq!(blockchain.submit_external_block(inflated), Err(RejectReason::InvalidTransaction(ValidationError::InvalidAmount)));
+
+        let spent = Transaction::new(sender, receiver, 5, "sender_password").unwrap();
+        let replayed = Block::mined_at(1, tip.hash(), vec![spent.clone(), spent.clone()], 4, later);
+        assert_eq!(blockchain.submit_external_block(replayed), Err(RejectReason::InvalidTransaction(ValidationError::InvalidNonce)));
+
+        assert_eq!(blockchain.height(), 0);
+
+        let valid = Block::mined_at(1, tip.hash(), vec![spent], 4, later);
+        assert_eq!(blockchain.submit_external_block(valid), Ok(()));
+        assert!(blockchain.is_valid());
+    }
+
+    #[test]
+    fn forged_targets_are_rejected() {
+        let mut blockchain = BlockChain::new_with_difficulty(2, 12);
+        let time = blockchain.tip().time() + chrono::Duration::seconds(1);
+
+        // the target is hashed, so it can't be set to the first byte of the hash after the block is mined
+        let mut header = Block::mined_at(1, blockchain.tip_hash(), Vec::new(), 4, time).header();
+        header.target = header.hash[0];
+        header.difficulty = 12;
+
+        assert_eq!(blockchain.clone().submit_external_block(Block::from_header(&header)), Err(RejectReason::InsufficientWork));
+
+        // a block mined with another target has as much work, but only the default target is accepted
+        let other_target = Block::new_with_target(1, blockchain.tip_hash(), Vec::new(), 12, 0x45);
+
+        assert!(other_target.verify());
+        assert_eq!(blockchain.clone().submit_external_block(other_target), Err(RejectReason::InsufficientWork));
+
+        // the older blocks don't hash the target, so they get 8 bits of work for free
+        header.version = crate::block::PROOF_OF_WORK_VERSION - 1;
+
+        let forged = (0..)
+            .map(|nonce| {
+                let mut header = header.clone();
+                header.nonce = nonce;
+                header.hash = Block::finalize_with_nonce(&Block::from_header(&header).header_digest_prefix(), nonce);
+                header.target = header.hash[0];
+
+                Block::from_header(&header)
+            })
+            .find(Block::verify)
+            .unwrap();
+
+        assert!(forged.nonce() < 1 << 10);
+        assert_eq!(blockchain.clone().submit_external_block(forged.clone()), Err(RejectReason::OutdatedVersion));
+
+        blockchain.chain.push(forged);
+
+        assert!(blockchain.validation_errors().any(|defect| defect == ChainDefect::WrongTarget(1)));
+        assert!(!blockchain.is_valid());
+        assert!(!blockchain.is_valid_parallel());
+    }
+
+    #[test]
+    fn nonce_gap_is_deferred() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(100);
+
+        let mut blockchain = BlockChain::with_genesis_allocations(5, 0, vec![(sender.clone(), 100)]);
+
+        blockchain.add_pending(Transaction::new(sender.clone(), receiver.clone(), 1, "sender_password").unwrap()).unwrap();
+        blockchain.mine_pending().unwrap();
+
+        let second = Transaction::new(sender.clone(), receiver.clone(), 2, "sender_password").unwrap().with_nonce(2);
+        blockchain.add_pending(second.clone()).unwrap();
+        blockchain.mine_pending().unwrap();
+
+        // the nonce 1 is missing, so the transaction waits
+        assert!(blockchain.tip().transactions().is_empty());
+        assert_eq!(blockchain.pending(), &[second][..]);
+
+        let first = Transaction::new(sender, receiver, 3, "sender_password").unwrap().with_nonce(1);
+        blockchain.add_pending(first).unwrap();
+        blockchain.mine_pending().unwrap();
+
+        let nonces: Vec<u64> = blockchain.tip().transactions().iter().map(Transaction::nonce).collect();
+
+        assert_eq!(nonces.len(), 2);
+        assert!(nonces.contains(&1) && nonces.contains(&2));
+        assert!(blockchain.pending().is_empty());
+    }
+
+    #[test]
+    fn replay_matches_balance_of() {
+        let mut alice = Account::new("Alice", "Test", "alice_password").unwrap();
+        let bob = Account::new("Bob", "Test", "bob_password").unwrap();
+        let carol = Account::new("Carol", "Test", "carol_password").unwrap();
+        let miner = Account::new("Miner", "Test", "miner_password").unwrap();
+
+        let mut blockchain = BlockChain::with_genesis_allocations(5, 0, vec![(alice.clone(), 100), (bob.clone(), 20)]);
+        blockchain.set_miner(miner.clone());
+        blockchain.set_block_reward(50);
+        alice.add_money(100);
+
+        blockchain.add_pending(Transaction::new_with_fee(alice.clone(), bob.clone(), 30, 2, "alice_password").unwrap()).unwrap();
+        blockchain.add_pending(Transaction::new_with_fee(alice.clone(), carol.clone(), 10, 1, "alice_password").unwrap().with_nonce(1)).unwrap();
+        blockchain.mine_pending().unwrap();
+        blockchain.mine_pending().unwrap();
+
+        let balances = blockchain.replay();
+
+        for account in &[alice, bob, carol, miner] {
+            assert_eq!(balances[&account.address()], blockchain.balance_of(account));
+        }
+
+        assert_eq!(balances.len(), 4);
+    }
+
+    #[test]
+    fn pending_spend_reduces_only_the_available_balance() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+
+        let mut blockchain = BlockChain::with_genesis_allocations(5, 0, vec![(sender.clone(), 50)]);
+        sender.add_money(50);
+
+        assert_eq!(blockchain.available_balance(&sender), 50);
+
+        blockchain.add_pending(Transaction::new(sender.clone(), receiver.clone(), 20, "sender_password").unwrap()).unwrap();
+
+        assert_eq!(blockchain.balance_of(&sender), 50);
+        assert_eq!(blockchain.available_balance(&sender), 30);
+        assert_eq!(blockchain.available_balance(&receiver), 0);
+
+        blockchain.mine_pending().unwrap();
+
+        assert_eq!(blockchain.balance_of(&sender), 30);
+        assert_eq!(blockchain.available_balance(&sender), 30);
+    }
+
+    #[test]
+    fn amounts_over_the_limit_are_rejected() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let first = Account::new("First", "Receiver", "first_password").unwrap();
+        let second = Account::new("Second", "Receiver", "second_password").unwrap();
+        sender.add_money(100);
+
+        let mut blockchain = BlockChain::with_genesis_allocations(5, 0, vec![(sender.clone(), 100)]);
+        blockchain.set_max_tx_amount(10);
+
+        // every output is under the limit, but their sum isn't
+        let split = Transaction::new_with_outputs(sender.clone(), vec![(first.clone(), 6), (second.clone(), 6)], 0, "sender_password").unwrap();
+        assert_eq!(blockchain.add_pending(split), Err(ValidationError::AmountTooLarge));
+
+        let at_limit = Transaction::new_with_outputs(sender, vec![(first, 5), (second, 5)], 1, "sender_password").unwrap();
+        assert_eq!(blockchain.add_pending(at_limit), Ok(()));
+    }
+
+    #[test]
+    fn small_amounts_add_up_exactly() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(crate::amount::from_coins(100.0).unwrap());
+
+        let tenth = crate::amount::from_coins(0.1).unwrap();
+        let outputs = vec![(receiver.clone(), tenth); 1000];
+
+        let mut blockchain = BlockChain::new_with_difficulty(5, 0);
+        blockchain.add_block(vec![Transaction::new_with_outputs(sender, outputs, 0, "sender_password").unwrap()]).unwrap();
+
+        // a thousand tenths don't make a hundred in f64
+        assert_ne!((0..1000).map(|_| 0.1).sum::<f64>(), 100.0);
+        assert_eq!(blockchain.balance_of(&receiver), 100 * crate::amount::UNITS_PER_COIN as Balance);
+    }
+
+    #[test]
+    fn coinbase_value_is_reward_plus_fees() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        let miner = Account::new("Miner", "Test", "miner_password").unwrap();
+        sender.add_money(100);
+
+        let mut blockchain = BlockChain::with_genesis_allocations(5, 0, vec![(sender.clone(), 100)]);
+        blockchain.set_miner(miner);
+        blockchain.set_block_reward(50);
+
+        blockchain.add_pending(Transaction::new_with_fee(sender.clone(), receiver.clone(), 10, 2, "sender_password").unwrap()).unwrap();
+        blockchain.add_pending(Transaction::new_with_fee(sender, receiver, 10, 3, "sender_password").unwrap().with_nonce(1)).unwrap();
+
+        let reward = blockchain.current_reward();
+        blockchain.mine_pending().unwrap();
+
+        assert_eq!(blockchain.tip().coinbase_value(), Some(reward + 2 + 3));
+        assert_eq!(blockchain.chain()[0].coinbase_value(), None);
+    }
+
+    #[test]
+    fn only_old_transactions_expire() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(100);
+
+        let mut blockchain = BlockChain::with_genesis_allocations(5, 0, vec![(sender.clone(), 100)]);
+        blockchain.set_mempool_ttl(Duration::from_millis(10));
+
+        let old = Transaction::new(sender.clone(), receiver.clone(), 10, "sender_password").unwrap();
+        blockchain.add_pending(old.clone()).unwrap();
+
+        std::thread::sleep(Duration::from_millis(20));
+
+        let fresh = Transaction::new(sender, receiver, 10, "sender_password").unwrap().with_nonce(1);
+        blockchain.add_pending(fresh.clone()).unwrap();
+
+        assert_eq!(blockchain.evict_expired(fresh.time()), 1);
+        assert_eq!(blockchain.pending(), &[fresh][..]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn tampered_file_is_corrupt() {
+        let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+        blockchain.add_block(Vec::new()).unwrap();
+        blockchain.chain[1] = Block::new_with_difficulty(1, [1; 64], Vec::new(), 4);
+
+        let path = std::env::temp_dir().join("blockchain_tampered_file_is_corrupt.json");
+
+        blockchain.save_to_file(&path).unwrap();
+
+        assert!(matches!(BlockChain::load_from_file(&path), Err(LoadError::Defect(ChainDefect::BadLink(1)))));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn other_chains_are_rejected() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(10);
+
+        let mut first = BlockChain::builder().tx_per_block(5).difficulty(4).chain_id(1).allocate(sender.clone(), 10).build().unwrap();
+        first.add_block(Vec::new()).unwrap();
+
+        let mut jsonl = Vec::new();
+        first.export_jsonl(&mut jsonl).unwrap();
+
+        let mut second = BlockChain::new_with_chain_id(5, 4, 2);
+
+        assert!(matches!(second.import_jsonl(&jsonl[..]), Err(LoadError::Corrupt)));
+        assert_eq!(second.height(), 0);
+
+        let bound = Transaction::new(sender, receiver, 5, "sender_password").unwrap().with_chain_id(1);
+
+        assert_eq!(second.add_pending(bound.clone()), Err(ValidationError::WrongChain));
+        assert_eq!(first.add_pending(bound), Ok(()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn load_error_names_the_corrupt_block() {
+        let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+        blockchain.mine_n_empty(4).unwrap();
+
+        let block = &blockchain.chain[3];
+        let mut hash = block.hash();
+        hash[63] ^= 1;
+
+        blockchain.chain[3] = Block::from_parts(3, block.prev_hash(), Vec::new(), block.difficulty(), block.nonce(), block.time(), hash);
+
+        let path = std::env::temp_dir().join("blockchain_load_error_names_the_corrupt_block.json");
+
+        blockchain.save_to_file(&path).unwrap();
+
+        let error = BlockChain::load_from_file(&path).unwrap_err();
+
+        assert!(matches!(error, LoadError::Defect(ChainDefect::InvalidBlock(3))));
+        assert_eq!(error.to_string(), "The blockchain isn't valid: The block #3 isn't valid.");
+    }
+
+    #[test]
+    fn fees_under_the_minimum_are_rejected() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(100);
+
+        let mut blockchain = BlockChain::with_genesis_allocations(5, 0, vec![(sender.clone(), 100)]);
+        blockchain.set_min_fee(3);
+
+        let cheap = Transaction::new_with_fee(sender.clone(), receiver.clone(), 10, 2, "sender_password").unwrap();
+        assert_eq!(blockchain.add_pending(cheap), Err(ValidationError::FeeTooLow));
+
+        let at_minimum = Transaction::new_with_fee(sender, receiver.clone(), 10, 3, "sender_password").unwrap();
+        assert_eq!(blockchain.add_pending(at_minimum), Ok(()));
+
+        assert_eq!(blockchain.add_pending(Transaction::coinbase(receiver, 5)), Ok(()));
+    }
+
+    #[test]
+    fn blocks_between_are_in_the_range() {
+        let mut blockchain = BlockChain::new_with_difficulty(5, 0);
+        blockchain.mine_n_empty(5).unwrap();
+
+        let (start, end) = (blockchain.chain[1].time(), blockchain.chain[4].time());
+        let blocks = blockchain.blocks_between(start, end);
+
+        assert_eq!(blocks.iter().map(|block| block.index()).collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        assert!(blocks.iter().all(|block| start <= block.time() && block.time() <= end));
+        let after_tip = blockchain.tip().time() + chrono::Duration::seconds(1);
+        assert!(blockchain.blocks_between(after_tip, after_tip + chrono::Duration::days(1)).is_empty());
+    }
+
+    #[test]
+    fn average_block_time_of_evenly_spaced_blocks() {
+        let time = Utc.timestamp_opt(1_600_000_000, 0).unwrap();
+        let clock = Arc::new(crate::clock::MockClock::new(time));
+
+        let mut blockchain = BlockChain::new_with_clock(5, 0, clock.clone());
+
+        for seconds in [600, 600, 600, 60] {
+            clock.advance(chrono::Duration::seconds(seconds));
+            blockchain.add_block(Vec::new()).unwrap();
+        }
+
+        assert_eq!(blockchain.average_block_time(4), Some(Duration::from_secs(600 + 600 + 60) / 3));
+        assert_eq!(blockchain.average_block_time(100), Some(Duration::from_secs(465)));
+        assert_eq!(blockchain.average_block_time(1), None);
+    }
+
+    #[test]
+    fn rollback_restores_the_previous_tip() {
+        let miner = Account::new("Miner", "Test", "miner_password").unwrap();
+
+        let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+        blockchain.set_miner(miner.clone());
+        blockchain.set_block_reward(10);
+
+        blockchain.mine_n_empty(1).unwrap();
+        let tip = blockchain.tip().clone();
+
+        blockchain.mine_n_empty(1).unwrap();
+
+        assert_eq!(blockchain.balance_of(&miner), 20);
+        assert_eq!(blockchain.rollback().map(|block| block.index()), Some(2));
+        assert_eq!(blockchain.tip(), &tip);
+        assert_eq!(blockchain.balance_of(&miner), 10);
+        assert!(blockchain.is_valid());
+
+        blockchain.add_block(Vec::new()).unwrap();
+
+        assert_eq!(blockchain.chain[2].prev_hash(), tip.hash());
+    }
+
+    #[test]
+    fn memos_are_hashed_and_capped() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(100);
+
+        let plain = Transaction::new(sender.clone(), receiver.clone(), 10, "sender_password").unwrap();
+        let with_memo = plain.clone().with_memo("Invoice #42");
+
+        assert!(plain.verify_hash() && with_memo.verify_hash());
+        assert_ne!(plain.hash(), with_memo.hash());
+        assert_eq!(plain.clone().with_memo("Invoice #42").hash(), with_memo.hash());
+
+        let mut blockchain = BlockChain::with_genesis_allocations(5, 0, vec![(sender.clone(), 100)]);
+        blockchain.set_max_memo_len(11);
+
+        let oversized = plain.with_memo("Invoice #420").with_nonce(1);
+        assert_eq!(blockchain.add_pending(oversized), Err(ValidationError::MemoTooLong));
+        assert_eq!(blockchain.add_pending(with_memo), Ok(()));
+    }
+
+    #[test]
+    fn tampered_hash_chain_is_detected() {
+        let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+        blockchain.mine_n_empty(3).unwrap();
+
+        let pairs = blockchain.hash_chain();
+
+        assert!(blockchain.verify_hash_chain(&pairs));
+
+        let mut tampered = pairs.clone();
+        tampered[2].1[0] ^= 1;
+
+        assert!(!blockchain.verify_hash_chain(&tampered));
+
+        let mut renumbered = pairs.clone();
+        renumbered[3].0 = 4;
+
+        assert!(!blockchain.verify_hash_chain(&renumbered));
+        assert!(!blockchain.verify_hash_chain(&pairs[..3]));
+    }
+
+    #[test]
+    fn blocks_are_verified_with_their_own_difficulty() {
+        let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+        let block = Block::new_with_difficulty(1, blockchain.tip().hash(), Vec::new(), 6);
+
+        assert!(block.verify());
+        assert_eq!(blockchain.submit_external_block(block.clone()), Ok(()));
+
+        // the genesis block is mined with a lower difficulty than the difficulty of the chain
+        let stricter = BlockChain::new_with_genesis_difficulty(5, 4, 8);
+
+        assert_eq!(stricter.tip().difficulty(), 4);
+        assert!(stricter.is_valid());
+
+        #[cfg(feature = "serde")]
+        {
+            let received: Block = serde_json::from_str(&serde_json::to_string(&block).unwrap()).unwrap();
+
+            assert_eq!(received.difficulty(), 6);
+            assert!(received.verify());
+        }
+    }
+
+    #[test]
+    fn fee_estimate_is_the_median_of_the_recent_fees() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(1_000);
+
+        let mut blockchain = BlockChain::new_with_difficulty(5, 0);
+        blockchain.set_min_fee(2);
+
+        assert_eq!(blockchain.estimate_fee(), 2);
+
+        let mut nonce = 0;
+        let mut paying = |fee| {
+            nonce += 1;
+            Transaction::new_with_fee(sender.clone(), receiver.clone(), 10, fee, "sender_password").unwrap().with_nonce(nonce)
+        };
+
+        // the fees of an old block are out of the window
+        blockchain.add_block(vec![paying(100), paying(100), paying(100)]).unwrap();
+        blockchain.mine_n_empty(FEE_ESTIMATE_BLOCKS - 2).unwrap();
+
+        blockchain.add_block(vec![paying(4), paying(2), paying(7), Transaction::coinbase(receiver.clone(), 50)]).unwrap();
+        blockchain.add_block(vec![paying(9), paying(3)]).unwrap();
+
+        // the fees in the window are 2, 3, 4, 7 and 9
+        assert_eq!(blockchain.estimate_fee(), 4);
+
+        blockchain.set_min_fee(5);
+
+        assert_eq!(blockchain.estimate_fee(), 5);
+    }
+
+    #[test]
+    fn transactions_from_unknown_senders_are_rejected() {
+        let mut allocated = Account::new("Allocated", "Test", "allocated_password").unwrap();
+        let mut stranger = Account::new("Stranger", "Test", "stranger_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        allocated.add_money(10);
+        stranger.add_money(10); // the money of the stranger was added outside of the chain
+
+        let mut blockchain = BlockChain::with_genesis_allocations(5, 4, vec![(allocated.clone(), 10)]);
+        let later = blockchain.tip().time() + chrono::Duration::seconds(1);
+
+        let from_stranger = Transaction::new(stranger, receiver.clone(), 5, "stranger_password").unwrap();
+        let block = Block::mined_at(1, blockchain.tip().hash(), vec![from_stranger], 4, later);
+
+        assert_eq!(blockchain.submit_external_block(block), Err(RejectReason::InvalidTransaction(ValidationError::UnknownSender)));
+
+        let first_spend = Transaction::new(allocated, receiver, 5, "allocated_password").unwrap();
+        let block = Block::mined_at(1, blockchain.tip().hash(), vec![first_spend], 4, later);
+
+        assert_eq!(blockchain.submit_external_block(block), Ok(()));
+    }
+
+    #[test]
+    fn hashrate_of_a_chain_with_known_block_times() {
+        let time = Utc.timestamp_opt(1_600_000_000, 0).unwrap();
+        let clock = Arc::new(crate::clock::MockClock::new(time));
+
+        let mut blockchain = BlockChain::new_with_clock(5, 4, clock.clone());
+
+        for _ in 0..3 {
+            clock.advance(chrono::Duration::seconds(8));
+            blockchain.add_block(Vec::new()).unwrap();
+        }
+
+        // 2^4 hashes every 8 seconds
+        assert_eq!(blockchain.estimated_hashrate(), Some(2.0));
+
+        let mut instant = BlockChain::new_with_clock(5, 4, Arc::new(crate::clock::FixedClock::new(time)));
+        instant.chain.push(Block::mined_at(1, instant.tip().hash(), Vec::new(), 4, time));
+        instant.index = 1;
+
+        assert_eq!(instant.estimated_hashrate(), None);
+    }
+
+    #[test]
+    fn mined_blocks_record_the_difficulty_of_the_chain() {
+        let mut blockchain = BlockChain::new_with_difficulty(2, 2);
+
+        blockchain.add_block(Vec::new()).unwrap();
+        assert_eq!(blockchain.tip().difficulty(), 2);
+
+        blockchain.set_difficulty(5);
+        blockchain.mine_pending().unwrap();
+        assert_eq!(blockchain.tip().difficulty(), 5);
+
+        // the blocks are mined way faster than 10 minutes each, so the adjustment raises the difficulty
+        blockchain.set_difficulty_adjustment(2, Duration::from_secs(600));
+        blockchain.add_block(Vec::new()).unwrap();
+        blockchain.add_block(Vec::new()).unwrap();
+
+        assert_eq!(blockchain.difficulty(), 6);
+
+        let difficulties: Vec<usize> = blockchain.chain().iter().skip(1).map(Block::difficulty).collect();
+
+        assert_eq!(difficulties, vec![2, 5, 5, 6]);
+        assert!(blockchain.is_valid());
+    }
+
+    #[test]
+    fn duplicate_transactions_are_rejected() {
+        let miner = Account::new("Miner", "Test", "miner_password").unwrap();
+        let coinbase = Transaction::coinbase(miner, 1);
+
+        let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+        blockchain.set_block_reward(10);
+
+        let later = blockchain.tip().time() + chrono::Duration::seconds(1);
+        let duplicated = Block::mined_at(1, blockchain.tip().hash(), vec![coinbase.clone(), coinbase], 4, later);
+
+        // the two coinbase transactions issue less than the reward, but they're the same transaction
+        assert_eq!(
+            blockchain.submit_external_block(duplicated.clone()),
+            Err(RejectReason::InvalidTransaction(ValidationError::InvalidNonce)),
+        );
+
+        blockchain.chain.push(duplicated);
+        blockchain.index = 1;
+
+        assert_eq!(blockchain.validation_errors().collect::<Vec<_>>(), vec![ChainDefect::DuplicateTransaction(1)]);
+        assert!(!blockchain.is_valid());
+        assert!(!blockchain.is_valid_parallel());
+    }
+
+    #[test]
+    fn built_chain_has_the_configured_parameters() {
+        let alice = Account::new("Alice", "Test", "alice_password").unwrap();
+        let bob = Account::new("Bob", "Test", "bob_password").unwrap();
+        let time = Utc.timestamp_opt(1_600_000_000, 0).unwrap();
+
+        let blockchain = BlockChain::builder()
+            .difficulty(3)
+            .tx_per_block(7)
+            .block_reward(40)
+            .halving_interval(20)
+            .chain_id(9)
+            .genesis_time(time)
+            .allocate(alice.clone(), 100)
+            .allocate(bob.clone(), 25)
+            .build().unwrap();
+
+        let genesis = blockchain.tip();
+
+        assert_eq!(blockchain.difficulty(), 3);
+        assert_eq!(genesis.difficulty(), 3);
+        assert_eq!(blockchain.transactions_per_block, 7);
+        assert_eq!(blockchain.block_reward, 40);
+        assert_eq!(blockchain.halving_interval, 20);
+        assert_eq!(blockchain.chain_id(), 9);
+        assert_eq!(genesis.chain_id(), 9);
+        assert_eq!(genesis.time(), time);
+        assert_eq!(blockchain.balance_of(&alice), 100);
+        assert_eq!(blockchain.balance_of(&bob), 25);
+        assert!(blockchain.is_valid());
+    }
+
+    #[test]
+    fn forks_deeper_than_the_finality_are_rejected() {
+        let mut blockchain = BlockChain::new_with_difficulty(2, 4);
+        blockchain.add_block(Vec::new()).unwrap();
+        blockchain.set_max_reorg_depth(2);
+
+        let mut deep = blockchain.clone();
+        let mut shallow = blockchain.clone();
+
+        blockchain.mine_n_empty(3).unwrap();
+
+        // it forks 3 blocks from the tip
+        deep.mine_n_empty(5).unwrap();
+        assert!(!blockchain.replace_chain(deep.chain()));
+
+        // it forks 2 blocks from the tip
+        shallow.chain = blockchain.chain[..3].to_vec();
+        shallow.index = 2;
+        shallow.mine_n_empty(3).unwrap();
+
+        assert!(blockchain.replace_chain(shallow.chain()));
+        assert_eq!(blockchain.chain(), shallow.chain());
+    }
+
+    #[test]
+    fn locator_is_spaced_exponentially() {
+        let mut blockchain = BlockChain::new_with_difficulty(2, 0);
+        blockchain.mine_n_empty(20).unwrap();
+
+        let locator = blockchain.locator();
+        let indexes: Vec<usize> = locator.iter().map(|hash| blockchain.block_by_hash(hash).unwrap().index()).collect();
+
+        assert_eq!(indexes, vec![20, 19, 18, 16, 12, 4, 0]);
+
+        // a peer which is behind, and which forked after the block #12
+        let mut peer = blockchain.clone();
+        peer.chain.truncate(13);
+        peer.index = 12;
+        peer.mine_n_empty(2).unwrap();
+
+        assert_eq!(blockchain.blocks_after(&peer.locator()), &blockchain.chain[13..]);
+        assert!(blockchain.blocks_after(&locator).is_empty());
+        assert_eq!(blockchain.blocks_after(&[[1; 64]]).len(), 21);
+    }
+
+    #[test]
+    fn confirmations_count_the_blocks_up_to_the_tip() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+
+        let mut blockchain = BlockChain::with_genesis_allocations(5, 4, vec![(sender.clone(), 10)]);
+        sender.add_money(10);
+
+        let transaction = Transaction::new(sender, receiver, 5, "sender_password").unwrap();
+        let id = transaction.id();
+
+        blockchain.add_block(Vec::new()).unwrap();
+        blockchain.add_block(vec![transaction]).unwrap();
+
+        for k in 0..4 {
+            assert_eq!(blockchain.height() - 2, k);
+            assert_eq!(blockchain.confirmations(&id), Some(k + 1));
+
+            blockchain.add_block(Vec::new()).unwrap();
+        }
+
+        assert_eq!(blockchain.confirmations(&[1; 64]), None);
+    }
+
+    #[test]
+    fn blocks_with_a_skipped_index_are_rejected() {
+        let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+        let mut peer = blockchain.clone();
+        peer.add_block(Vec::new()).unwrap();
+
+        let skipped = Block::mine_on_chain(2, blockchain.tip_hash(), Vec::new(), 4, 0, peer.tip().time(), u128::MAX).unwrap();
+
+        assert_eq!(blockchain.append_block(skipped), Err(ChainError::BadIndex));
+        assert_eq!(blockchain.append_block(peer.tip().clone()), Ok(()));
+
+        // the index of a full chain can't wrap around to the genesis block
+        blockchain.index = usize::MAX;
+
+        assert!(matches!(blockchain.try_add_block(Vec::new(), u128::MAX), Err(Error::Chain(ChainError::BadIndex))));
+        assert_eq!(blockchain.add_block(Vec::new()), Err(MiningError::IndexOverflow));
+        assert_eq!(blockchain.chain.len(), 2);
+    }
+
+    #[test]
+    fn view_reflects_the_chain() {
+        let miner = Account::new("Miner", "Test", "miner_password").unwrap();
+
+        let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+        blockchain.set_miner(miner.clone());
+        blockchain.set_block_reward(10);
+        blockchain.mine_n_empty(3).unwrap();
+
+        // a view only needs a shared borrow, so many of them can be used at the same time
+        let view = blockchain.view();
+        let copy = view;
+
+        assert_eq!(view.height(), blockchain.height());
+        assert_eq!(view.tip(), blockchain.tip());
+        assert_eq!(view.balance_of(&miner), 30);
+        assert_eq!(copy.block_by_index(2), blockchain.block_by_index(2));
+        assert!(copy.block_by_index(4).is_none());
+
+        blockchain.mine_n_empty(1).unwrap();
+
+        assert_eq!(blockchain.view().height(), 4);
+        assert_eq!(blockchain.view().balance_of(&miner), 40);
+    }
+
+    #[test]
+    fn test_mode_chain_mines_instantly_and_validates() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+
+        let mut blockchain = BlockChain::test_mode(5);
+        blockchain.set_miner(sender.clone());
+        blockchain.set_block_reward(10);
+        blockchain.mine_n_empty(50).unwrap();
+        sender.add_money(500);
+
+        blockchain.add_block(vec![Transaction::new(sender, receiver.clone(), 5, "sender_password").unwrap()]).unwrap();
+
+        assert_eq!(blockchain.height(), 51);
+        assert!(blockchain.chain.iter().all(|block| block.difficulty() == 0 && block.nonce() == 0));
+        assert!(blockchain.chain.iter().all(|block| block.verify()));
+        assert!(blockchain.is_valid());
+        assert_eq!(blockchain.balance_of(&receiver), 5);
+    }
+
+    #[test]
+    fn transfers_are_queued_until_the_balance_runs_out() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+
+        let mut blockchain = BlockChain::with_genesis_allocations(5, 4, vec![(sender.clone(), 10)]);
+        sender.add_money(10);
+
+        assert!(blockchain.transfer(&mut sender, &receiver, 6, "sender_password").is_ok());
+        assert!(matches!(blockchain.transfer(&mut sender, &receiver, 5, "sender_password"), Err(Error::Transaction(TxError::InsufficientFunds))));
+        assert!(matches!(blockchain.transfer(&mut sender, &receiver, 4, "wrong_password"), Err(Error::Transaction(TxError::Unauthorized))));
+
+        assert_eq!(blockchain.pending().len(), 1);
+        assert_eq!(blockchain.pending()[0].amount(), 6);
+        assert_eq!(sender.balance(), 4);
+
+        // the nonces follow each other, so both transfers are mined
+        assert!(blockchain.transfer(&mut sender, &receiver, 4, "sender_password").is_ok());
+
+        blockchain.mine_pending().unwrap();
+
+        assert_eq!(blockchain.balance_of(&receiver), 10);
+        assert_eq!(blockchain.balance_of(&sender), 0);
+    }
+
+    #[test]
+    fn blocks_record_their_producer() {
+        let alice = Account::new("Alice", "Test", "alice_password").unwrap();
+        let bruno = Account::new("Bruno", "Test", "bruno_password").unwrap();
+
+        let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+        blockchain.add_block(Vec::new()).unwrap();
+
+        blockchain.set_miner(alice.clone());
+        blockchain.mine_n_empty(2).unwrap();
+        blockchain.mine_pending().unwrap();
+
+        blockchain.set_miner(bruno.clone());
+        blockchain.mine_pending().unwrap();
+
+        let indexes = |address: &str| -> Vec<usize> {
+            blockchain.blocks_by_producer(address).iter().map(|block| block.index()).collect()
+        };
+
+        assert_eq!(indexes(&alice.address()), vec![2, 3, 4]);
+        assert_eq!(indexes(&bruno.address()), vec![5]);
+        assert_eq!(blockchain.chain[1].producer(), None);
+        assert!(blockchain.is_valid());
+
+        // the producer is hashed with the block, so it can't be changed
+        let mut forged = blockchain.chain[5].header();
+        forged.producer = Some(alice.public_key());
+
+        assert!(blockchain.chain[5].header().verify());
+        assert!(!forged.verify());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn swapped_transactions_fail_the_merkle_check_on_import() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(10);
+
+        let transaction = Transaction::new(sender.clone(), receiver.clone(), 1, "sender_password").unwrap();
+        let swapped = Transaction::new(sender.clone(), receiver, 9, "sender_password").unwrap();
+
+        let mut blockchain = BlockChain::with_genesis_allocations(5, 4, vec![(sender.clone(), 10)]);
+        blockchain.add_block(vec![transaction]).unwrap();
+
+        let mut jsonl = Vec::new();
+        blockchain.export_jsonl(&mut jsonl).unwrap();
+
+        // the block keeps its Merkle root, like a pruned block, so the header still matches its hash
+        let lines: Vec<&str> = std::str::from_utf8(&jsonl).unwrap().lines().collect();
+        let mut block: serde_json::Value = serde_json::from_str(lines[1]).unwrap();
+        block["pruned_root"] = serde_json::json!(crate::block::hash_to_hex(&blockchain.chain[1].merkle_root()));
+
+        let kept = format!("{}\n{}\n", lines[0], block);
+
+        block["transactions"][0] = serde_json::to_value(&swapped).unwrap();
+
+        let altered = format!("{}\n{}\n", lines[0], block);
+
+        let mut imported = BlockChain::new_with_difficulty(5, 4);
+
+        assert!(matches!(imported.import_jsonl(altered.as_bytes()), Err(LoadError::Corrupt)));
+        assert_eq!(imported.height(), 0);
+
+        assert!(imported.import_jsonl(kept.as_bytes()).is_ok());
+        assert_eq!(imported.height(), 1);
+    }
+
+    #[test]
+    fn fast_blocks_produce_a_retarget_event() {
+        let mut blockchain = BlockChain::new_with_difficulty(5, 2);
+        blockchain.set_difficulty_adjustment(3, Duration::from_secs(3600));
+
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = Arc::clone(&events);
+        blockchain.on_retarget(Box::new(move |event| seen.lock().unwrap().push(*event)));
+
+        blockchain.mine_n_empty(5).unwrap();
+
+        assert!(events.lock().unwrap().is_empty());
+
+        blockchain.mine_n_empty(1).unwrap();
+
+        let events = events.lock().unwrap();
+
+        assert_eq!(*events, vec![RetargetEvent { height: 6, old_difficulty: 2, new_difficulty: 3 }]);
+        assert_eq!(blockchain.tip().difficulty(), 3);
+    }
+
+    #[test]
+    fn blocks_out_of_the_canonical_order_are_rejected() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+
+        let blockchain = BlockChain::with_genesis_allocations(5, 4, vec![(sender.clone(), 10)]);
+        sender.add_money(10);
+
+        let mut transactions = vec![
+            Transaction::new(sender.clone(), receiver.clone(), 1, "sender_password").unwrap(),
+            Transaction::new(sender, receiver, 2, "sender_password").unwrap().with_nonce(1),
+        ];
+        transactions.reverse();
+
+        // the blocks generated by the crate are sorted, so the block of a peer is mined from its parts
+        let time = blockchain.tip().time() + chrono::Duration::seconds(1);
+        let prefix = Block::from_parts(1, blockchain.tip_hash(), transactions.clone(), 4, 0, time, [0; 64]).header_digest_prefix();
+        let unordered = (0..)
+            .map(|nonce| Block::from_parts(1, blockchain.tip_hash(), transactions.clone(), 4, nonce, time, Block::finalize_with_nonce(&prefix, nonce)))
+            .find(Block::verify)
+            .unwrap();
+
+        assert!(!unordered.is_canonically_ordered());
+        assert_eq!(blockchain.clone().submit_external_block(unordered.clone()), Err(RejectReason::NonCanonicalOrder));
+        assert_eq!(blockchain.clone().append_block(unordered.clone()), Err(ChainError::InvalidBlock));
+
+        let mut forged = blockchain.clone();
+        forged.chain.push(unordered);
+
+        assert!(!forged.is_valid());
+        assert!(forged.validation_errors().any(|defect| defect == ChainDefect::NonCanonicalOrder(1)));
+
+        // the same transactions mined by the chain are sorted, so the block is accepted
+        let mut accepted = blockchain.clone();
+        accepted.add_block(transactions).unwrap();
+
+        assert!(accepted.tip().is_canonically_ordered());
+        assert!(accepted.is_valid());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn relabelled_blocks_cant_skip_the_canonical_order() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+
+        let blockchain = BlockChain::with_genesis_allocations(5, 4, vec![(sender.clone(), 10)]);
+        sender.add_money(10);
+
+        let mut transactions = vec![
+            Transaction::new(sender.clone(), receiver.clone(), 1, "sender_password").unwrap(),
+            Transaction::new(sender, receiver, 2, "sender_password").unwrap().with_nonce(1),
+        ];
+        transactions.reverse();
+
+        // the block is labelled with the first version, which doesn't need the canonical order, and mined again
+        let time = blockchain.tip().time() + chrono::Duration::seconds(1);
+        let mut json = serde_json::to_value(Block::from_parts(1, blockchain.tip_hash(), transactions, 4, 0, time, [0; 64])).unwrap();
+        json["version"] = 1.into();
+
+        let prefix = serde_json::from_value::<Block>(json.clone()).unwrap().header_digest_prefix();
+        let nonce = (0..).find(|&nonce| Block::meets_target(&Block::finalize_with_nonce(&prefix, nonce), 4)).unwrap();
+
+        json["nonce"] = (nonce as u64).into();
+        json["hash"] = crate::block::hash_to_hex(&Block::finalize_with_nonce(&prefix, nonce)).into();
+
+        let relabelled: Block = serde_json::from_value(json).unwrap();
+
+        assert_eq!(relabelled.version(), 1);
+        assert!(relabelled.verify());
+        assert!(!relabelled.is_canonically_ordered());
+        assert_eq!(blockchain.clone().submit_external_block(relabelled.clone()), Err(RejectReason::OutdatedVersion));
+
+        let mut candidate = blockchain.chain.clone();
+        candidate.push(relabelled);
+
+        assert!(!blockchain.clone().replace_chain(candidate.clone()));
+
+        let mut forged = blockchain.clone();
+        forged.chain = candidate;
+
+        assert_eq!(forged.validation_errors().collect::<Vec<_>>(), vec![ChainDefect::OutdatedVersion(1)]);
+        assert!(!forged.is_valid());
+        assert!(!forged.is_valid_parallel());
+    }
+
+    #[test]
+    fn pending_transactions_need_money_in_the_chain() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(100); // the balance of the account isn't money in the chain
+
+        let mut blockchain = BlockChain::with_genesis_allocations(5, 0, vec![(sender.clone(), 10)]);
+
+        let first = Transaction::new(sender.clone(), receiver.clone(), 6, "sender_password").unwrap();
+        let second = Transaction::new(sender.clone(), receiver.clone(), 5, "sender_password").unwrap().with_nonce(1);
+        let third = Transaction::new(sender, receiver, 4, "sender_password").unwrap().with_nonce(1);
+
+        assert_eq!(blockchain.add_pending(first), Ok(()));
+        assert_eq!(blockchain.add_pending(second), Err(ValidationError::InvalidAmount)); // 6 + 5 is more than 10
+        assert_eq!(blockchain.add_pending(third), Ok(()));
+    }
+
+    #[test]
+    fn double_spends_are_found_with_the_balance_in_the_chain() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+
+        let mut blockchain = BlockChain::with_genesis_allocations(5, 0, vec![(sender.clone(), 10)]);
+        blockchain.add_block(vec![Transaction::coinbase(sender.clone(), 5)]).unwrap();
+        sender.add_money(100); // the balance of the account doesn't limit what it spends in the block
+
+        let first = Transaction::new(sender.clone(), receiver.clone(), 6, "sender_password").unwrap();
+        let second = Transaction::new(sender, receiver, 7, "sender_password").unwrap().with_nonce(1);
+
+        blockchain.add_pending(first.clone()).unwrap();
+        blockchain.add_pending(second.clone()).unwrap();
+
+        blockchain.rollback();
+
+        assert_eq!(blockchain.mine_pending().unwrap(), vec![second]);
+        assert_eq!(blockchain.chain[1].transactions(), &[first]);
+    }
+
+    #[test]
+    fn external_blocks_are_checked_against_the_state_of_the_chain() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(1_000); // the balance the sender claims
+
+        let mut blockchain = BlockChain::with_genesis_allocations(5, 0, vec![(sender.clone(), 10)]);
+        let tip = blockchain.tip().clone();
+        let later = tip.time() + chrono::Duration::seconds(1);
+
+        let mut overspending = vec![
+            Transaction::new(sender.clone(), receiver.clone(), 6, "sender_password").unwrap(),
+            Transaction::new(sender.clone(), receiver.clone(), 6, "sender_password").unwrap().with_nonce(1),
+        ];
+        crate::block::sort_canonically(&mut overspending);
+        let block = Block::mined_at(1, tip.hash(), overspending, 0, later);
+        assert_eq!(blockchain.submit_external_block(block), Err(RejectReason::InvalidTransaction(ValidationError::InvalidAmount)));
+
+        let gap = Transaction::new(sender.clone(), receiver.clone(), 1, "sender_password").unwrap().with_nonce(1);
+        let block = Block::mined_at(1, tip.hash(), vec![gap], 0, later);
+        assert_eq!(blockchain.submit_external_block(block), Err(RejectReason::InvalidTransaction(ValidationError::InvalidNonce)));
+
+        let mut in_order = vec![
+            Transaction::new(sender.clone(), receiver.clone(), 4, "sender_password").unwrap(),
+            Transaction::new(sender, receiver, 6, "sender_password").unwrap().with_nonce(1),
+        ];
+        crate::block::sort_canonically(&mut in_order);
+        let block = Block::mined_at(1, tip.hash(), in_order, 0, later);
+        assert_eq!(blockchain.submit_external_block(block), Ok(()));
+    }
+
+    #[test]
+    fn accepted_blocks_are_checked_like_external_blocks() {
+        let miner = Account::new("Miner", "Test", "miner_password").unwrap();
+
+        let mut blockchain = BlockChain::new_with_difficulty(5, 0);
+        let tip = blockchain.tip().clone();
+
+        let inflated = Block::mined_at(1, tip.hash(), vec![Transaction::coinbase(miner.clone(), 1_000_000)], 0, tip.time() + chrono::Duration::seconds(1));
+        let child = Block::mined_at(2, inflated.hash(), Vec::new(), 0, tip.time() + chrono::Duration::seconds(2));
+
+        assert_eq!(blockchain.append_block(inflated.clone()), Err(ChainError::InvalidBlock));
+        assert_eq!(blockchain.accept_block(inflated.clone()), AcceptOutcome::Rejected); // the block reward is 0
+
+        assert_eq!(blockchain.accept_block(child), AcceptOutcome::Orphan);
+        blockchain.connect_orphans();
+
+        assert_eq!(blockchain.height(), 0);
+        assert_eq!(blockchain.balance_of(&miner), 0);
+    }
+
+    #[test]
+    fn candidate_chains_are_replayed_from_the_genesis_block() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(1_000);
+
+        let blockchain = BlockChain::with_genesis_allocations(5, 0, vec![(sender.clone(), 10)]);
+
+        let mut inflated = blockchain.clone();
+        inflated.add_block(vec![Transaction::coinbase(receiver.clone(), 1_000_000)]).unwrap(); // the block reward is 0
+        inflated.mine_n_empty(2).unwrap();
+
+        let mut overspending = blockchain.clone();
+        overspending.add_block(vec![Transaction::new(sender.clone(), receiver.clone(), 50, "sender_password").unwrap()]).unwrap();
+        overspending.mine_n_empty(2).unwrap();
+
+        let mut valid = blockchain.clone();
+        valid.add_block(vec![Transaction::new(sender, receiver, 5, "sender_password").unwrap()]).unwrap();
+        valid.mine_n_empty(2).unwrap();
+
+        let mut blockchain = blockchain;
+
+        assert!(!blockchain.replace_chain(inflated.chain()));
+        assert!(!blockchain.replace_chain(overspending.chain()));
+        assert_eq!(blockchain.height(), 0);
+
+        assert!(blockchain.replace_chain(valid.chain()));
+        assert_eq!(blockchain.height(), 3);
+    }
+
+    #[test]
+    fn forged_transfers_are_defects() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(10);
+
+        // the sender claims a balance it never received in the chain
+        let mut blockchain = BlockChain::new_with_difficulty(5, 0);
+        let genesis = blockchain.chain[0].clone();
+        let transaction = Transaction::new(sender, receiver, 5, "sender_password").unwrap();
+        blockchain.chain.push(Block::mined_at(1, genesis.hash(), vec![transaction], 0, genesis.time() + chrono::Duration::seconds(1)));
+
+        let defects: Vec<ChainDefect> = blockchain.validation_errors().collect();
+
+        assert_eq!(defects, vec![ChainDefect::InsufficientFunds(1)]);
+        assert!(!blockchain.is_valid());
+        assert!(!blockchain.is_valid_parallel());
+
+        #[cfg(feature = "serde")]
+        {
+            let mut jsonl = Vec::new();
+            blockchain.export_jsonl(&mut jsonl).unwrap();
+
+            let mut imported = BlockChain::new_with_difficulty(5, 0);
+
+            assert!(matches!(imported.import_jsonl(&jsonl[..]), Err(LoadError::Corrupt)));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn forged_signatures_are_defects() {
+        use sha2::{Digest, Sha512};
+
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(10);
+
+        let transaction = Transaction::new(sender.clone(), receiver.clone(), 5, "sender_password").unwrap();
+        let other = Transaction::new(sender.clone(), receiver, 1, "sender_password").unwrap();
+
+        // the signature of another transaction, with a hash matching it, so only the signature is wrong
+        let mut value = serde_json::to_value(&transaction).unwrap();
+        let signature = serde_json::to_value(&other).unwrap()["signature"].clone();
+        let mut hasher = Sha512::new();
+        hasher.update(transaction.signing_bytes());
+        hasher.update(crate::block::hash_from_hex(signature.as_str().unwrap()).unwrap());
+        value["signature"] = signature;
+        let mut hash = [0; 64];
+        hash.copy_from_slice(&hasher.finalize());
+        value["hash"] = serde_json::json!(crate::block::hash_to_hex(&hash));
+        let forged: Transaction = serde_json::from_value(value).unwrap();
+
+        assert!(forged.verify_hash());
+        assert!(!forged.verify_signature());
+
+        let mut blockchain = BlockChain::with_genesis_allocations(5, 0, vec![(sender.clone(), 10)]);
+        let genesis = blockchain.chain[0].clone();
+        blockchain.chain.push(Block::mined_at(1, genesis.hash(), vec![forged], 0, genesis.time() + chrono::Duration::seconds(1)));
+
+        let defects: Vec<ChainDefect> = blockchain.validation_errors().collect();
+
+        assert_eq!(defects, vec![ChainDefect::InvalidSignature(1)]);
+        assert!(!blockchain.chain[1].verify());
+        assert!(!blockchain.is_valid());
+        assert!(!blockchain.is_valid_parallel());
+    }
+
+    #[test]
+    fn memory_hard_chains_mine_and_verify_with_scrypt() {
+        let mut blockchain = BlockChain::builder().difficulty(2).proof_of_work(ProofOfWork::Scrypt).build().unwrap();
+        blockchain.add_block(Vec::new()).unwrap();
+
+        assert!(blockchain.chain.iter().all(|block| block.verify_with(ProofOfWork::Scrypt) && !block.verify()));
+        assert!(blockchain.is_valid());
+        assert!(blockchain.is_valid_parallel());
+        assert!(blockchain.header_chain().is_valid());
+
+        // the same block mined with SHA-512 and with scrypt, only the second one is accepted
+        let time = blockchain.tip().time() + chrono::Duration::seconds(1);
+        let unmined = Block::unmined_on_chain(2, blockchain.tip_hash(), Vec::new(), 2, 0, time);
+        let sha512 = unmined.clone().mine_up_to_with(u128::MAX, ProofOfWork::Sha512).unwrap();
+        let scrypt = unmined.mine_up_to_with(u128::MAX, ProofOfWork::Scrypt).unwrap();
+
+        assert_ne!(sha512.hash(), scrypt.hash());
+        assert_eq!(blockchain.submit_external_block(sha512), Err(RejectReason::InsufficientWork));
+        assert_eq!(blockchain.submit_external_block(scrypt), Ok(()));
+
+        // a chain mined with SHA-512 isn't valid with scrypt
+        let mut sha512_chain = BlockChain::new_with_difficulty(5, 2);
+        sha512_chain.add_block(Vec::new()).unwrap();
+        sha512_chain.proof_of_work = ProofOfWork::Scrypt;
+
+        assert!(!sha512_chain.is_valid());
+        assert!(!sha512_chain.is_valid_parallel());
+    }
+}
@@ -0,0 +1,123 @@
+//! The sources of the current time used to mine and to validate the blocks, behind the `Clock` trait,
+//! so that the time can be fixed or controlled in the tests, see `BlockChain::set_clock()`.
+
+use chrono::{DateTime, Duration, Utc};
+use std::sync::Mutex;
+
+/// A source of the current time.
+/// 
+/// # Example
+/// ```
+/// # use blockchain::clock::{Clock, FixedClock};
+/// use chrono::{TimeZone, Utc};
+/// 
+/// let time = Utc.with_ymd_and_hms(2021, 3, 14, 0, 0, 0).unwrap();
+/// let clock = FixedClock::new(time);
+/// 
+/// assert_eq!(clock.now(), time);
+/// ```
+pub trait Clock: Send + Sync {
+    /// Returns the current time of the clock.
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The `Clock` of the system, which returns `Utc::now()`; it's the clock used by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A `Clock` which always returns the same time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedClock {
+    time: DateTime<Utc>,
+}
+
+impl FixedClock {
+    /// Generates a new `FixedClock`, which always returns the given time.
+    pub fn new(time: DateTime<Utc>) -> Self {
+        Self { time }
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.time
+    }
+}
+
+/// A `Clock` which returns the time it's set to, and which can be moved forward by hand,
+/// e.g. to mine a block after another one in a test.
+/// 
+/// # Example
+/// ```
+/// # use blockchain::clock::{Clock, MockClock};
+/// use chrono::{Duration, TimeZone, Utc};
+/// 
+/// let time = Utc.with_ymd_and_hms(2021, 3, 14, 0, 0, 0).unwrap();
+/// let clock = MockClock::new(time);
+/// 
+/// clock.advance(Duration::minutes(10));
+/// 
+/// assert_eq!(clock.now(), time + Duration::minutes(10));
+/// ```
+#[derive(Debug)]
+pub struct MockClock {
+    time: Mutex<DateTime<Utc>>,
+}
+
+impl MockClock {
+    /// Generates a new `MockClock`, set to the given time.
+    pub fn new(time: DateTime<Utc>) -> Self {
+        Self { time: Mutex::new(time) }
+    }
+
+    /// This method sets the time of the clock.
+    pub fn set(&self, time: DateTime<Utc>) {
+        *self.time.lock().unwrap() = time;
+    }
+
+    /// This method moves the time of the clock forward by the given duration.
+    pub fn advance(&self, duration: Duration) {
+        *self.time.lock().unwrap() += duration;
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.time.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{block::Block, blockchain::BlockChain};
+    use chrono::TimeZone;
+    use std::sync::Arc;
+
+    #[test]
+    fn blocks_are_mined_at_the_time_of_the_clock() {
+        let time = Utc.timestamp_opt(1_600_000_000, 0).unwrap();
+        let clock = Arc::new(MockClock::new(time));
+
+        assert_eq!(Block::new_with_clock(1, [0; 64], Vec::new(), 4, clock.as_ref()).time(), time);
+
+        let mut blockchain = BlockChain::new_with_clock(5, 4, clock.clone());
+
+        clock.advance(Duration::seconds(30));
+        blockchain.add_block(Vec::new()).unwrap();
+
+        clock.advance(Duration::seconds(30));
+        blockchain.add_block(Vec::new()).unwrap();
+
+        let times: Vec<_> = blockchain.chain().iter().map(Block::time).collect();
+
+        assert_eq!(times, vec![time, time + Duration::seconds(30), time + Duration::seconds(60)]);
+        assert!(blockchain.is_valid());
+    }
+}
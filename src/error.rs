@@ -0,0 +1,118 @@
+use std::{fmt, error, io};
+use crate::{
+    account::AccountError,
+    block::MiningError,
+    transaction::{ValidationError, TxError},
+    blockchain::ChainError,
+};
+#[cfg(feature = "serde")]
+use crate::blockchain::LoadError;
+
+/// An enum to handle all the errors of the crate, so that the fallible operations
+/// can be handled with a single error type.
+/// 
+/// Every error of the other modules can be converted into an `Error`, so the `?` operator can be used:
+/// - a `MiningError` becomes an `Error::Mining` error
+/// - a `ValidationError` becomes an `Error::InvalidTransaction` error
+/// - a `TxError` becomes an `Error::Transaction` error
+/// - a `ChainError` becomes an `Error::Chain` error
+/// - an `AccountError` becomes an `Error::Account` error
+/// - an `io::Error` becomes an `Error::Io` error
+/// 
+/// # Example
+/// ```
+/// # use blockchain::Error;
+/// # use blockchain::block::{Block, MiningError};
+/// fn mine() -> Result<Block, Error> {
+///     let block = Block::try_new(1, [0; 64], Vec::new(), 512, 9)?; // 10 nonces can't satisfy 512 bits
+/// 
+///     Ok(block)
+/// }
+/// 
+/// assert!(matches!(mine(), Err(Error::Mining(MiningError::Exhausted))));
+/// ```
+#[derive(Debug)]
+pub enum Error {
+    Mining(MiningError),
+    InvalidTransaction(ValidationError),
+    Transaction(TxError),
+    Chain(ChainError),
+    Account(AccountError),
+    CorruptBlock,
+    Io(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Mining(e) => write!(f, "Can't mine the block: {}", e),
+            Self::InvalidTransaction(e) => write!(f, "Invalid transaction: {}", e),
+            Self::Transaction(e) => write!(f, "Can't create the transaction: {}", e),
+            Self::Chain(e) => write!(f, "Can't put the block in the chain: {}", e),
+            Self::Account(e) => write!(f, "Can't create the account: {}", e),
+            Self::CorruptBlock => write!(f, "Corrupt block."),
+            Self::Io(e) => write!(f, "I/O error: {}", e),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Mining(e) => Some(e),
+            Self::InvalidTransaction(e) => Some(e),
+            Self::Transaction(e) => Some(e),
+            Self::Chain(e) => Some(e),
+            Self::Account(e) => Some(e),
+            Self::CorruptBlock => None,
+            Self::Io(e) => Some(e),
+        }
+    }
+}
+
+impl From<MiningError> for Error {
+    fn from(e: MiningError) -> Self {
+        Self::Mining(e)
+    }
+}
+
+impl From<ValidationError> for Error {
+    fn from(e: ValidationError) -> Self {
+        Self::InvalidTransaction(e)
+    }
+}
+
+impl From<TxError> for Error {
+    fn from(e: TxError) -> Self {
+        Self::Transaction(e)
+    }
+}
+
+impl From<ChainError> for Error {
+    fn from(e: ChainError) -> Self {
+        Self::Chain(e)
+    }
+}
+
+impl From<AccountError> for Error {
+    fn from(e: AccountError) -> Self {
+        Self::Account(e)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<LoadError> for Error {
+    fn from(e: LoadError) -> Self {
+        match e {
+            LoadError::Io(e) => Self::Io(e),
+            LoadError::Parse(e) => Self::Io(e.into()),
+            LoadError::Corrupt | LoadError::Defect(_) => Self::CorruptBlock,
+        }
+    }
+}
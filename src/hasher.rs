@@ -0,0 +1,202 @@
+//! The hash functions used to hash the blocks, behind the `Hasher` trait,
+//! so that a chain can use a different proof of work function than SHA-512,
+//! e.g. the memory-hard `ScryptHasher`.
+
+use sha2::{Sha512, Digest};
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+/// A hash function producing 64-byte hashes.
+/// 
+/// The hashing is incremental: a `State` is created with `new()`, and it's fed with `update()`
+/// until the hash is returned by `finalize()`; the state is cloned while mining,
+/// so that the bytes of a block which don't change are hashed only once.
+/// 
+/// # Example
+/// ```
+/// # use blockchain::hasher::{Hasher, Sha512Hasher};
+/// let mut state = Sha512Hasher::new();
+/// Sha512Hasher::update(&mut state, b"block");
+/// Sha512Hasher::update(&mut state, b"chain");
+/// 
+/// assert_eq!(Sha512Hasher::finalize(state), Sha512Hasher::hash(b"blockchain"));
+/// ```
+pub trait Hasher {
+    type State: Clone;
+
+    /// Creates a new state, with no data hashed.
+    fn new() -> Self::State;
+
+    /// Feeds the state with some data.
+    fn update(state: &mut Self::State, data: &[u8]);
+
+    /// Returns the hash of all the data fed to the state.
+    fn finalize(state: Self::State) -> [u8; 64];
+
+    /// Hashes some data at once.
+    fn hash(data: &[u8]) -> [u8; 64] {
+        let mut state = Self::new();
+
+        Self::update(&mut state, data);
+
+        Self::finalize(state)
+    }
+}
+
+/// The SHA-512 hash function, used by default by the blocks, and to hash the transactions.
+/// 
+/// # Example
+/// ```
+/// # use blockchain::hasher::{Hasher, Sha512Hasher};
+/// # use hex_literal::hex;
+/// assert_eq!(Sha512Hasher::hash(b"abc"), hex!("ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Sha512Hasher;
+
+impl Hasher for Sha512Hasher {
+    type State = Sha512;
+
+    fn new() -> Self::State {
+        Sha512::new()
+    }
+
+    fn update(state: &mut Self::State, data: &[u8]) {
+        state.update(data);
+    }
+
+    fn finalize(state: Self::State) -> [u8; 64] {
+        let mut hash = [0; 64];
+
+        // the output of SHA-512 always has 64 bytes, so the copy can't fail
+        hash.copy_from_slice(&state.finalize());
+
+        hash
+    }
+}
+
+/// The double SHA-512 hash function, i.e. `SHA512(SHA512(data))`, which isn't open to length-extension attacks;
+/// a block is hashed with it when it's generated as a `Block<DoubleSha512Hasher>` with `Block::with_hasher()`.
+/// 
+/// # Example
+/// ```
+/// # use blockchain::hasher::{Hasher, Sha512Hasher, DoubleSha512Hasher};
+/// assert_eq!(DoubleSha512Hasher::hash(b"abc"), Sha512Hasher::hash(&Sha512Hasher::hash(b"abc")));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DoubleSha512Hasher;
+
+impl Hasher for DoubleSha512Hasher {
+    type State = Sha512;
+
+    fn new() -> Self::State {
+        Sha512::new()
+    }
+
+    fn update(state: &mut Self::State, data: &[u8]) {
+        state.update(data);
+    }
+
+    fn finalize(state: Self::State) -> [u8; 64] {
+        Sha512Hasher::hash(&Sha512Hasher::finalize(state))
+    }
+}
+
+/// The cost parameter `N` of `ScryptHasher`, i.e. the number of 128-byte blocks of memory used for every hash.
+pub const SCRYPT_N: usize = 1024;
+
+/// The block size parameter `r` of `ScryptHasher`.
+pub const SCRYPT_R: usize = 1;
+
+/// The parallelization parameter `p` of `ScryptHasher`.
+pub const SCRYPT_P: usize = 1;
+
+/// The memory-hard scrypt function (RFC 7914), with the data as both the password and the salt
+/// and the parameters `SCRYPT_N`, `SCRYPT_R` and `SCRYPT_P`, like the proof of work of Litecoin:
+/// every hash needs `128 * SCRYPT_R * SCRYPT_N` bytes of memory, so the mining is harder to speed up with dedicated hardware.
+/// A block is hashed with it when it's generated as a `Block<ScryptHasher>` with `Block::with_hasher()`,
+/// and `verify()` hashes it with scrypt too, since the hasher is part of the type of the block;
+/// the blocks of a chain are hashed with it when the chain is built with `ProofOfWork::Scrypt`.
+/// 
+/// The data fed to the state is buffered, since scrypt isn't incremental, so the data is hashed only by `finalize()`.
+/// 
+/// # Example
+/// ```
+/// # use blockchain::hasher::{Hasher, Sha512Hasher, ScryptHasher};
+/// # use blockchain::block::Block;
+/// let block = Block::<ScryptHasher>::with_hasher(1, [0; 64], Vec::new(), 4);
+/// 
+/// assert!(block.verify());
+/// assert_eq!(block.hash(), ScryptHasher::hash(&block.header_bytes()));
+/// assert_ne!(block.hash(), Sha512Hasher::hash(&block.header_bytes()));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ScryptHasher;
+
+impl Hasher for ScryptHasher {
+    type State = Vec<u8>;
+
+    fn new() -> Self::State {
+        Vec::new()
+    }
+
+    fn update(state: &mut Self::State, data: &[u8]) {
+        state.extend_from_slice(data);
+    }
+
+    fn finalize(state: Self::State) -> [u8; 64] {
+        let mut hash = [0; 64];
+
+        // `SCRYPT_N` is a power of two, and the output has a valid length
+        let params = scrypt::Params::new(SCRYPT_N.trailing_zeros() as u8, SCRYPT_R as u32, SCRYPT_P as u32, hash.len())
+            .expect("The parameters of scrypt are valid.");
+
+        scrypt::scrypt(&state, &state, &params, &mut hash).expect("The output of scrypt has a valid length.");
+
+        hash
+    }
+}
+
+/// The proof of work function of a `BlockChain`, selected with `BlockChainBuilder::proof_of_work()`:
+/// the blocks of the chain are mined and verified with the `Hasher` of the function, see `Block::verify_with()`.
+/// 
+/// # Example
+/// ```
+/// # use blockchain::blockchain::BlockChain;
+/// # use blockchain::hasher::ProofOfWork;
+/// let mut blockchain = BlockChain::builder().difficulty(2).proof_of_work(ProofOfWork::Scrypt).build().unwrap();
+/// blockchain.add_block(Vec::new()).unwrap();
+/// 
+/// assert_eq!(blockchain.proof_of_work(), ProofOfWork::Scrypt);
+/// assert!(blockchain.tip().verify_with(ProofOfWork::Scrypt));
+/// assert!(blockchain.is_valid());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ProofOfWork {
+    /// The blocks are hashed with `Sha512Hasher`, like the blocks of `Block::new()`.
+    #[default]
+    Sha512,
+    /// The blocks are hashed with the memory-hard `ScryptHasher`.
+    Scrypt,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::Block;
+
+    #[test]
+    fn memory_hard_blocks_verify_with_their_own_hash() {
+        let block = Block::<ScryptHasher>::with_hasher(1, [0; 64], Vec::new(), 6);
+
+        assert!(block.verify());
+        assert_eq!(block.hash(), ScryptHasher::hash(&block.header_bytes()));
+        assert_ne!(block.hash(), Sha512Hasher::hash(&block.header_bytes()));
+
+        // the same header mined with SHA-512 has another hash
+        let sha512 = Block::<Sha512Hasher>::from_parts(1, [0; 64], Vec::new(), 6, block.nonce(), block.time(), block.hash());
+
+        assert!(!sha512.verify());
+    }
+}
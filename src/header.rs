@@ -0,0 +1,184 @@
+//! The headers of the blocks, without their transactions, so that a light client can check
+//! the proof of work and the links of a chain without downloading the transactions;
+//! the headers commit to the transactions through the Merkle root of the blocks.
+
+use crate::{block::{Block, DEFAULT_TARGET}, hasher::ProofOfWork};
+use chrono::{DateTime, Utc};
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+/// The header of a `Block`, returned by `Block::header()`, which contains:
+/// - the index of the block
+/// - the hash of the previous block
+/// - the Merkle root of the transactions of the block, see `Block::merkle_root()`
+/// - the time the block was mined
+/// - the difficulty and the target byte the block was mined with
+/// - the nonce of the block
+/// - the hash of the block
+/// - the extra data of the block, see `Block::extra_data()`
+/// - the identifier of the chain of the block, see `Block::chain_id()`
+/// - the version of the format of the block, see `Block::version()`
+/// - the public key of the producer of the block, if any, see `Block::producer()`
+/// 
+/// # Example
+/// ```
+/// # use blockchain::block::Block;
+/// let block = Block::new_with_difficulty(1, [0; 64], Vec::new(), 4);
+/// let header = block.header();
+/// 
+/// assert_eq!(header.hash, block.hash());
+/// assert!(header.verify());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BlockHeader {
+    pub index: usize,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))]
+    pub prev_hash: [u8; 64],
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))]
+    pub merkle_root: [u8; 64],
+    pub time: DateTime<Utc>,
+    pub difficulty: usize,
+    pub target: u8,
+    pub nonce: u128,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))]
+    pub hash: [u8; 64],
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub extra_data: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub chain_id: u32,
+    #[cfg_attr(feature = "serde", serde(default = "crate::block::default_version", deserialize_with = "crate::block::deserialize_version"))]
+    pub version: u16,
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_hex::key::option", skip_serializing_if = "Option::is_none"))]
+    pub producer: Option<[u8; 32]>,
+}
+
+impl BlockHeader {
+    /// This method checks, like `Block::verify()`, that the hash of the header is the hash of its fields,
+    /// and that it satisfies the proof of work; the transactions aren't checked, since the header doesn't have them.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::block::Block;
+    /// let mut header = Block::new_with_difficulty(1, [0; 64], Vec::new(), 4).header();
+    /// 
+    /// header.nonce += 1;
+    /// 
+    /// assert!(!header.verify());
+    /// ```
+    pub fn verify(&self) -> bool {
+        Block::from_header(self).verify()
+    }
+
+    /// This method checks the header like `verify()`, hashing it with the given proof of work function instead of SHA-512,
+    /// see `Block::verify_with()`.
+    pub fn verify_with(&self, proof_of_work: ProofOfWork) -> bool {
+        Block::from_header(self).verify_with(proof_of_work)
+    }
+}
+
+/// A chain of `BlockHeader`s, e.g. returned by `BlockChain::header_chain()`.
+/// 
+/// # Example
+/// ```
+/// # use blockchain::blockchain::BlockChain;
+/// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+/// blockchain.add_block(Vec::new()).unwrap();
+/// 
+/// let headers = blockchain.header_chain();
+/// 
+/// assert_eq!(headers.headers().len(), 2);
+/// assert!(headers.is_valid());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HeaderChain {
+    headers: Vec<BlockHeader>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    proof_of_work: ProofOfWork,
+}
+
+impl HeaderChain {
+    /// Generates a new `HeaderChain` with the given headers, in chain order.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::header::HeaderChain;
+    /// let headers = HeaderChain::new(Vec::new());
+    /// 
+    /// assert!(!headers.is_valid()); // there's no genesis block
+    /// ```
+    pub fn new(headers: Vec<BlockHeader>) -> Self {
+        HeaderChain::with_proof_of_work(headers, ProofOfWork::Sha512)
+    }
+
+    /// Generates a new `HeaderChain` like `new()`, whose headers are verified with the given proof of work function,
+    /// e.g. the headers of a chain built with `BlockChainBuilder::proof_of_work()`.
+    pub fn with_proof_of_work(headers: Vec<BlockHeader>, proof_of_work: ProofOfWork) -> Self {
+        Self { headers, proof_of_work }
+    }
+
+    /// This method returns the headers of the chain, since the `headers` field isn't `pub`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// let blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// 
+    /// assert_eq!(blockchain.header_chain().headers()[0].index, 0);
+    /// ```
+    pub fn headers(&self) -> &[BlockHeader] {
+        &self.headers
+    }
+
+    /// This method checks if the chain of headers is valid: the first header must be the header
+    /// of a genesis block, every header must verify with `DEFAULT_TARGET` and the proof of work function of the chain, and have its position in the chain as index,
+    /// and every header must be linked to the previous header and come after it.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::header::HeaderChain;
+    /// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// blockchain.add_block(Vec::new()).unwrap();
+    /// 
+    /// let mut headers = blockchain.header_chain().headers().to_vec();
+    /// headers.remove(1);
+    /// 
+    /// assert!(!HeaderChain::new(headers).is_valid());
+    /// ```
+    pub fn is_valid(&self) -> bool {
+        let genesis = self.headers.first().is_some_and(|genesis| genesis.index == 0 && genesis.prev_hash == [0; 64]);
+
+        genesis
+            && self.headers.iter().enumerate().all(|(i, header)| header.index == i && header.target == DEFAULT_TARGET && header.verify_with(self.proof_of_work))
+            && self.headers.windows(2).all(|pair| pair[1].prev_hash == pair[0].hash && pair[1].time > pair[0].time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{blockchain::BlockChain, transaction::Transaction, account::Account};
+
+    #[test]
+    fn altered_nonce_breaks_the_header_chain() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(10);
+
+        let mut blockchain = BlockChain::with_genesis_allocations(5, 4, vec![(sender.clone(), 10)]);
+        blockchain.add_block(vec![Transaction::new(sender, receiver, 5, "sender_password").unwrap()]).unwrap();
+        blockchain.add_block(Vec::new()).unwrap();
+
+        let headers = blockchain.header_chain();
+
+        assert!(blockchain.is_valid());
+        assert!(headers.is_valid());
+
+        let mut altered = headers.clone();
+        altered.headers[1].nonce = altered.headers[1].nonce.wrapping_add(1);
+
+        assert!(!altered.is_valid());
+    }
+}
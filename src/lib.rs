@@ -1,8 +1,24 @@
 pub mod account;
+pub mod amount;
 pub mod positive_f64;
 pub mod transaction;
 pub mod block;
+pub mod hasher;
+pub mod header;
+pub mod clock;
+pub mod binary;
 pub mod blockchain;
+pub mod store;
+pub mod shared;
+pub mod wallet;
+pub mod error;
+#[cfg(feature = "serde")]
+pub mod net;
+
+pub use error::Error;
+
+#[cfg(feature = "serde")]
+mod serde_hex;
 
 #[cfg(test)]
 mod tests {
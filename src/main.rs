@@ -1,22 +1,89 @@
+//! A command line tool to mine and inspect a blockchain saved in a file:
+//! - `mine <count> <file>` mines `count` empty blocks at the end of the chain in the file,
+//!   creating the chain if the file doesn't exist
+//! - `validate <file>` checks the chain in the file, and exits with an error if it isn't valid
+//! - `balance <address> <file>` prints the balance of the account with the given address
+//! - `show <index> <file>` prints the block with the given index
+
 use blockchain::{
-    blockchain::BlockChain,
-    account::Account,
+    blockchain::{BlockChain, LoadError},
+    block::hash_to_hex,
 };
+use std::{env, process, path::Path};
+
+const USAGE: &str = "Usage:
+    blockchain mine <count> <file>
+    blockchain validate <file>
+    blockchain balance <address> <file>
+    blockchain show <index> <file>";
 
 fn main() {
-    let mut a0 = Account::new("a", "a", "a");
-    let mut a1 = Account::new("b", "b", "b");
-    let mut a2 = Account::new("c", "c", "c");
-    let mut a3 = Account::new("d", "d", "d");
-    let mut a4 = Account::new("e", "e", "e");
-    
-    a0.add_money(100.0);
-    a2.add_money(100.0);
-    a4.add_money(100.0);
-
-    let mut blockchain = BlockChain::new(2);
-    blockchain.push_transaction(&mut a0, &mut a1, 2.0, "a");
-    blockchain.push_transaction(&mut a2, &mut a3, 1.0, "c");
-
-    println!("{} {} {} {} {}", a0, a1, a2, a3, a4);
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if let Err(e) = run(&args) {
+        eprintln!("{}", e);
+
+        process::exit(1);
+    }
+}
+
+/// Runs the subcommand in the arguments, returning the message to print if it fails.
+fn run(args: &[String]) -> Result<(), String> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    match args[..] {
+        ["mine", count, file] => {
+            let count = count.parse().map_err(|_| format!("Invalid number of blocks: {}", count))?;
+            let path = Path::new(file);
+
+            let mut blockchain = if path.exists() {
+                load(file)?
+            } else {
+                BlockChain::new(1)
+            };
+
+            blockchain.mine_n_empty(count).map_err(|e| format!("Can't mine the blocks: {}", e))?;
+            blockchain.save_to_file(path).map_err(|e| format!("Can't save the blockchain: {}", e))?;
+
+            println!("Mined {} blocks, the height of the chain is {}.", count, blockchain.height());
+        },
+        ["validate", file] => {
+            let blockchain = load(file)?;
+
+            println!("The chain is valid, its height is {}.", blockchain.height());
+        },
+        ["balance", address, file] => {
+            let blockchain = load(file)?;
+            let balance = blockchain.snapshot().balances().get(address).copied().unwrap_or(0);
+
+            println!("{}", balance);
+        },
+        ["show", index, file] => {
+            let index: usize = index.parse().map_err(|_| format!("Invalid block index: {}", index))?;
+            let blockchain = load(file)?;
+
+            let chain = blockchain.chain();
+            let block = chain.get(index).ok_or_else(|| format!("There is no block #{}.", index))?;
+
+            println!("{}", block);
+            println!("hash: {}", hash_to_hex(&block.hash()));
+            println!("previous hash: {}", hash_to_hex(&block.prev_hash()));
+
+            for transaction in block.transactions() {
+                println!("transaction {}: {} units", hash_to_hex(&transaction.hash()), transaction.amount());
+            }
+        },
+        _ => return Err(USAGE.to_string()),
+    }
+
+    Ok(())
+}
+
+/// Loads the blockchain saved in the file, which must be valid.
+fn load(file: &str) -> Result<BlockChain, String> {
+    BlockChain::load_from_file(Path::new(file)).map_err(|e| match e {
+        LoadError::Corrupt => format!("The chain in {} isn't valid.", file),
+        LoadError::Defect(defect) => format!("The chain in {} isn't valid: {}", file, defect),
+        e => format!("Can't load the blockchain from {}: {}", file, e),
+    })
 }
@@ -0,0 +1,399 @@
+//! A basic peer-to-peer network of nodes, exchanging the blocks they mine over TCP.
+//!
+//! The messages are serialized in JSON, one message per line: a node sends a new block
+//! to its peers, and if a peer is behind it asks for the whole chain, to replace its own chain
+//! following the heaviest chain rule of `BlockChain::replace_chain()`.
+//!
+//! The blocks and the chains of the peers aren't trusted: their transactions are checked against the state
+//! of the chain, like the blocks put in the chain with `BlockChain::submit_external_block()`.
+//! A chain is only accepted after the node asked for it, and a message longer than `MAX_MESSAGE_BYTES`
+//! closes the connection.
+
+use crate::{
+    block::Block,
+    blockchain::{BlockChain, AcceptOutcome},
+};
+use std::{io, thread};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex, MutexGuard};
+use serde::{Serialize, Deserialize, de::DeserializeOwned};
+
+/// The maximum length in bytes of a message, including its newline, so that a peer can't exhaust
+/// the memory of a node by sending a line that never ends.
+pub const MAX_MESSAGE_BYTES: u64 = 64 * 1024 * 1024;
+
+/// A node of the network, listening for the blocks sent by the other nodes
+/// and broadcasting its blocks to its peers.
+/// 
+/// The `BlockChain` of the node is shared with the thread handling the connections,
+/// so it's accessed with `blockchain()`.
+#[derive(Debug)]
+pub struct Node {
+    addr: SocketAddr,
+    blockchain: Arc<Mutex<BlockChain>>,
+    peers: Vec<SocketAddr>,
+}
+
+/// The messages sent to a node.
+#[derive(Debug, Serialize, Deserialize)]
+enum Message {
+    Block(Box<Block>),
+    Chain(Vec<Block>),
+}
+
+/// The replies of a node to a `Message`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum Reply {
+    Accepted,
+    Rejected,
+    SendChain,
+}
+
+impl Node {
+    /// Generates a new `Node` with the given `BlockChain`, listening on the given address
+    /// in a background thread; every connection is handled in its own thread.
+    /// 
+    /// Use the port `0` to let the operating system choose a free port, returned by `addr()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::net::Node;
+    /// let node = Node::listen("127.0.0.1:0", BlockChain::new_with_difficulty(2, 4)).unwrap();
+    /// 
+    /// assert_ne!(node.addr().port(), 0);
+    /// ```
+    pub fn listen<A: ToSocketAddrs>(addr: A, blockchain: BlockChain) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let blockchain = Arc::new(Mutex::new(blockchain));
+
+        let shared = Arc::clone(&blockchain);
+
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let blockchain = Arc::clone(&shared);
+
+                // a peer sending invalid messages only loses its connection
+                thread::spawn(move || handle(stream, &blockchain));
+            }
+        });
+
+        Ok(Self {
+            addr,
+            blockchain,
+            peers: Vec::new(),
+        })
+    }
+
+    /// This method returns the address the node is listening on, since the `addr` field isn't `pub`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::net::Node;
+    /// let node = Node::listen("127.0.0.1:0", BlockChain::new_with_difficulty(2, 4)).unwrap();
+    /// 
+    /// assert!(node.addr().ip().is_loopback());
+    /// ```
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// This method adds a peer, which receives the blocks sent with `broadcast_block()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::net::Node;
+    /// let mut node = Node::listen("127.0.0.1:0", BlockChain::new_with_difficulty(2, 4)).unwrap();
+    /// node.add_peer("127.0.0.1:7000".parse().unwrap());
+    /// 
+    /// assert_eq!(node.peers().len(), 1);
+    /// ```
+    pub fn add_peer(&mut self, peer: SocketAddr) {
+        self.peers.push(peer);
+    }
+
+    /// This method returns the peers of the node, since the `peers` field isn't `pub`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::net::Node;
+    /// let node = Node::listen("127.0.0.1:0", BlockChain::new_with_difficulty(2, 4)).unwrap();
+    /// 
+    /// assert!(node.peers().is_empty());
+    /// ```
+    pub fn peers(&self) -> &[SocketAddr] {
+        &self.peers
+    }
+
+    /// This method locks and returns the `BlockChain` of the node; while it's locked,
+    /// the blocks received from the other nodes wait to be put in the chain.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::net::Node;
+    /// let node = Node::listen("127.0.0.1:0", BlockChain::new_with_difficulty(2, 4)).unwrap();
+    /// 
+    /// node.blockchain().add_block(Vec::new()).unwrap();
+    /// 
+    /// assert_eq!(node.blockchain().chain().len(), 2);
+    /// ```
+    pub fn blockchain(&self) -> MutexGuard<'_, BlockChain> {
+        lock(&self.blockchain)
+    }
+
+    /// This method sends the given block to every peer, which accepts it with `BlockChain::accept_block()`:
+    /// if the block is an orphan for the peer, the peer asks for the whole chain of the node,
+    /// and replaces its chain if the chain of the node has more work.
+    /// 
+    /// The method returns the number of peers that accepted the block (or the chain),
+    /// or the first `io::Error` encountered while sending it; the block is sent to every peer anyway.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::net::Node;
+    /// let blockchain = BlockChain::new_with_difficulty(2, 4);
+    /// 
+    /// let mut miner = Node::listen("127.0.0.1:0", blockchain.clone()).unwrap();
+    /// let peer = Node::listen("127.0.0.1:0", blockchain).unwrap();
+    /// miner.add_peer(peer.addr());
+    /// 
+    /// miner.blockchain().add_block(Vec::new()).unwrap();
+    /// let block = miner.blockchain().chain()[1].clone();
+    /// 
+    /// assert_eq!(miner.broadcast_block(&block).unwrap(), 1);
+    /// assert_eq!(peer.blockchain().chain(), miner.blockchain().chain());
+    /// ```
+    pub fn broadcast_block(&self, block: &Block) -> io::Result<usize> {
+        let mut accepted = 0;
+        let mut error = None;
+
+        for peer in &self.peers {
+            match self.send_block(*peer, block) {
+                Ok(true) => accepted += 1,
+                Ok(false) => {},
+                Err(e) => {
+                    error.get_or_insert(e);
+                },
+            }
+        }
+
+        match error {
+            Some(e) => Err(e),
+            None => Ok(accepted),
+        }
+    }
+
+    /// Sends a block to a peer, and then the chain if the peer asks for it;
+    /// returns `true` if the peer accepted the block or the chain.
+    fn send_block(&self, peer: SocketAddr, block: &Block) -> io::Result<bool> {
+        let stream = TcpStream::connect(peer)?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut writer = stream;
+
+        write_line(&mut writer, &Message::Block(Box::new(block.clone())))?;
+
+        let reply = match read_line(&mut reader)? {
+            Some(Reply::SendChain) => {
+                let chain = self.blockchain().chain();
+
+                write_line(&mut writer, &Message::Chain(chain))?;
+
+                read_line(&mut reader)?
+            },
+            reply => reply,
+        };
+
+        Ok(reply == Some(Reply::Accepted))
+    }
+}
+
+/// Handles the messages sent by a peer through a connection, until the peer closes it.
+fn handle(stream: TcpStream, blockchain: &Mutex<BlockChain>) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+    // a peer can only send its chain right after the node asked for it
+    let mut chain_requested = false;
+
+    while let Some(message) = read_line(&mut reader)? {
+        let mut blockchain = lock(blockchain);
+
+        let reply = match message {
+            // an orphan means that the node is missing some blocks of the peer
+            Message::Block(block) => match blockchain.accept_block(*block) {
+                AcceptOutcome::Appended => Reply::Accepted,
+                AcceptOutcome::Orphan => Reply::SendChain,
+                AcceptOutcome::Rejected => Reply::Rejected,
+            },
+            Message::Chain(chain) => if chain_requested && blockchain.replace_chain(chain) {
+                Reply::Accepted
+            } else {
+                Reply::Rejected
+            },
+        };
+
+        chain_requested = reply == Reply::SendChain;
+
+        // the lock isn't held while waiting for the peer
+        drop(blockchain);
+
+        write_line(&mut writer, &reply)?;
+    }
+
+    Ok(())
+}
+
+/// Locks the blockchain, even if another thread panicked while holding the lock.
+fn lock(blockchain: &Mutex<BlockChain>) -> MutexGuard<'_, BlockChain> {
+    blockchain.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Writes a value as a line of JSON.
+fn write_line<T: Serialize>(writer: &mut TcpStream, value: &T) -> io::Result<()> {
+    let mut line = serde_json::to_vec(value)?;
+    line.push(b'\n');
+
+    writer.write_all(&line)?;
+    writer.flush()
+}
+
+/// Reads a value from a line of JSON, returning `None` if the connection was closed;
+/// a line longer than `MAX_MESSAGE_BYTES` is an `io::ErrorKind::InvalidData` error.
+fn read_line<T: DeserializeOwned, R: BufRead>(reader: &mut R) -> io::Result<Option<T>> {
+    let mut line = String::new();
+
+    if reader.by_ref().take(MAX_MESSAGE_BYTES).read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+
+    if line.len() as u64 == MAX_MESSAGE_BYTES && !line.ends_with('\n') {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "The message is too long."));
+    }
+
+    Ok(Some(serde_json::from_str(&line)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{account::Account, transaction::Transaction};
+
+    #[test]
+    fn lagging_peer_replaces_its_chain() {
+        let blockchain = BlockChain::new_with_difficulty(2, 4);
+
+        let mut miner = Node::listen("127.0.0.1:0", blockchain.clone()).unwrap();
+        let peer = Node::listen("127.0.0.1:0", blockchain).unwrap();
+        miner.add_peer(peer.addr());
+
+        miner.blockchain().add_block(Vec::new()).unwrap();
+        miner.blockchain().add_block(Vec::new()).unwrap();
+
+        let block = miner.blockchain().chain()[2].clone();
+
+        // the peer doesn't have the block #1, so it asks for the whole chain
+        assert_eq!(miner.broadcast_block(&block).unwrap(), 1);
+        assert_eq!(peer.blockchain().chain(), miner.blockchain().chain());
+    }
+
+    #[test]
+    fn unreachable_peer_is_an_error() {
+        let blockchain = BlockChain::new_with_difficulty(2, 4);
+
+        let mut node = Node::listen("127.0.0.1:0", blockchain).unwrap();
+
+        // a port nobody is listening on, since the listener is dropped
+        let closed = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        node.add_peer(closed);
+
+        let block = node.blockchain().chain()[0].clone();
+
+        assert!(node.broadcast_block(&block).is_err());
+    }
+
+    #[test]
+    fn unrequested_chains_are_rejected() {
+        let blockchain = BlockChain::new_with_difficulty(2, 4);
+
+        let mut heavier = blockchain.clone();
+        heavier.add_block(Vec::new()).unwrap();
+
+        let peer = Node::listen("127.0.0.1:0", blockchain).unwrap();
+
+        let stream = TcpStream::connect(peer.addr()).unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut writer = stream;
+
+        write_line(&mut writer, &Message::Chain(heavier.chain())).unwrap();
+
+        assert_eq!(read_line(&mut reader).unwrap(), Some(Reply::Rejected));
+        assert_eq!(peer.blockchain().height(), 0);
+    }
+
+    #[test]
+    fn messages_longer_than_the_limit_are_errors() {
+        let mut long = vec![b' '; MAX_MESSAGE_BYTES as usize];
+        long.extend_from_slice(b"[]\n");
+
+        let error = read_line::<Vec<Block>, _>(&mut &long[..]).unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        // a message right at the limit is read
+        let short = format!("{}[]\n", " ".repeat(MAX_MESSAGE_BYTES as usize - 3));
+
+        assert_eq!(read_line::<Vec<Block>, _>(&mut short.as_bytes()).unwrap(), Some(Vec::new()));
+    }
+
+    #[test]
+    fn peers_reject_blocks_printing_money() {
+        let blockchain = BlockChain::new_with_difficulty(2, 4); // the block reward is 0
+        let thief = Account::new("Thief", "Test", "thief_password").unwrap();
+
+        let mut miner = Node::listen("127.0.0.1:0", blockchain.clone()).unwrap();
+        let peer = Node::listen("127.0.0.1:0", blockchain).unwrap();
+        miner.add_peer(peer.addr());
+
+        miner.blockchain().add_block(vec![Transaction::coinbase(thief.clone(), 1_000_000)]).unwrap();
+        let block = miner.blockchain().chain()[1].clone();
+
+        assert_eq!(miner.broadcast_block(&block).unwrap(), 0);
+
+        // the chain with the block is rejected too
+        miner.blockchain().add_block(Vec::new()).unwrap();
+        let block = miner.blockchain().chain()[2].clone();
+
+        assert_eq!(miner.broadcast_block(&block).unwrap(), 0);
+        assert_eq!(peer.blockchain().height(), 0);
+        assert_eq!(peer.blockchain().balance_of(&thief), 0);
+    }
+
+    #[test]
+    fn peers_reject_blocks_overspending() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(1_000); // the balance the sender claims, not its money in the chain
+
+        let blockchain = BlockChain::with_genesis_allocations(2, 4, vec![(sender.clone(), 10)]);
+
+        let mut miner = Node::listen("127.0.0.1:0", blockchain.clone()).unwrap();
+        let peer = Node::listen("127.0.0.1:0", blockchain).unwrap();
+        miner.add_peer(peer.addr());
+
+        let overspending = Transaction::new(sender, receiver.clone(), 500, "sender_password").unwrap();
+        let json = serde_json::to_string(&overspending).unwrap();
+
+        miner.blockchain().add_block(vec![serde_json::from_str(&json).unwrap()]).unwrap();
+        let block = miner.blockchain().chain()[1].clone();
+
+        assert_eq!(miner.broadcast_block(&block).unwrap(), 0);
+        assert_eq!(peer.blockchain().height(), 0);
+        assert_eq!(peer.blockchain().balance_of(&receiver), 0);
+    }
+}
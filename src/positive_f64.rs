@@ -3,16 +3,21 @@ use std::{
     error,
     ops,
 };
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
 
 /// A struct to handle positive `f64` numbers.
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PositiveF64(f64);
 
 impl PositiveF64 {
     /// Generates a new `PositiveF64`.
     /// 
     /// The function returns a `Result<PositiveF64, InvalidNumber>`, because
-    /// if the number is negative, an `InvalidNumber::NegativeValue` error is returned.
+    /// if the number is negative, an `InvalidNumber::NegativeValue` error is returned,
+    /// and if the number is NaN or infinite, an `InvalidNumber::NonFiniteValue` error is returned,
+    /// so that a sum of `PositiveF64`s can't silently become NaN.
     /// 
     /// # Example
     /// ```
@@ -20,9 +25,14 @@ impl PositiveF64 {
     /// let positive_f64 = PositiveF64::new(3.0).unwrap();
     /// 
     /// assert_eq!(positive_f64.value(), 3.0); // this method returns the value
+    /// 
+    /// assert!(PositiveF64::new(f64::NAN).is_err());
+    /// assert!(PositiveF64::new(f64::INFINITY).is_err());
     /// ```
     pub fn new(number: f64) -> Result<Self, InvalidNumber> {
-        if number >= 0.0 {
+        if !number.is_finite() {
+            Err(InvalidNumber::NonFiniteValue)
+        } else if number >= 0.0 {
             Ok(PositiveF64(number))
         } else {
             Err(InvalidNumber::NegativeValue)
@@ -115,12 +125,14 @@ impl ops::SubAssign for PositiveF64 {
 #[derive(Debug)]
 pub enum InvalidNumber {
     NegativeValue,
+    NonFiniteValue,
 }
 
 impl fmt::Display for InvalidNumber {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
             Self::NegativeValue => write!(f, "The number can't be negative."),
+            Self::NonFiniteValue => write!(f, "The number can't be NaN or infinite."),
         }
     }
 }
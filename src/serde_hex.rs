@@ -0,0 +1,158 @@
+//! Serializes `[u8; 64]` arrays (hashes, signatures and keys) as hex strings,
+//! since `serde` doesn't support arrays of this length out of the box.
+//!
+//! The formats which aren't human-readable, like the `bincode` encoding of the `binary` module,
+//! get the bytes of the arrays as they are instead.
+
+use serde::{de, ser::SerializeTuple, Deserialize, Deserializer, Serializer};
+use std::fmt;
+use crate::block::{hash_to_hex, hash_from_hex};
+
+pub fn serialize<S: Serializer>(bytes: &[u8; 64], serializer: S) -> Result<S::Ok, S::Error> {
+    if !serializer.is_human_readable() {
+        return serialize_raw(bytes, serializer);
+    }
+
+    serializer.serialize_str(&hash_to_hex(bytes))
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 64], D::Error> {
+    if !deserializer.is_human_readable() {
+        return deserialize_raw(deserializer);
+    }
+
+    let hex = String::deserialize(deserializer)?;
+
+    hash_from_hex(&hex).map_err(de::Error::custom)
+}
+
+/// Serializes an array as a tuple of bytes, which has no length prefix since the length is known.
+fn serialize_raw<S: Serializer, const N: usize>(bytes: &[u8; N], serializer: S) -> Result<S::Ok, S::Error> {
+    let mut tuple = serializer.serialize_tuple(N)?;
+
+    for byte in bytes {
+        tuple.serialize_element(byte)?;
+    }
+
+    tuple.end()
+}
+
+/// Deserializes an array serialized with `serialize_raw()`.
+fn deserialize_raw<'de, D: Deserializer<'de>, const N: usize>(deserializer: D) -> Result<[u8; N], D::Error> {
+    struct Raw<const N: usize>;
+
+    impl<'de, const N: usize> de::Visitor<'de> for Raw<N> {
+        type Value = [u8; N];
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{} bytes", N)
+        }
+
+        fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<[u8; N], A::Error> {
+            let mut bytes = [0; N];
+
+            for (i, byte) in bytes.iter_mut().enumerate() {
+                *byte = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(i, &self))?;
+            }
+
+            Ok(bytes)
+        }
+    }
+
+    deserializer.deserialize_tuple(N, Raw::<N>)
+}
+
+/// Serializes `Option<[u8; 64]>` values, to be used with `#[serde(default)]`.
+pub mod option {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    struct HexRef<'a>(&'a [u8; 64]);
+
+    impl Serialize for HexRef<'_> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            super::serialize(self.0, serializer)
+        }
+    }
+
+    pub fn serialize<S: Serializer>(bytes: &Option<[u8; 64]>, serializer: S) -> Result<S::Ok, S::Error> {
+        match bytes {
+            Some(bytes) => serializer.serialize_some(&HexRef(bytes)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<[u8; 64]>, D::Error> {
+        #[derive(Deserialize)]
+        struct Hex(#[serde(with = "super")] [u8; 64]);
+
+        Ok(Option::<Hex>::deserialize(deserializer)?.map(|Hex(bytes)| bytes))
+    }
+}
+
+/// Serializes the 32-byte public keys of the accounts as hex strings.
+pub mod key {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8; 32], serializer: S) -> Result<S::Ok, S::Error> {
+        if !serializer.is_human_readable() {
+            return super::serialize_raw(bytes, serializer);
+        }
+
+        let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+
+        serializer.serialize_str(&hex)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 32], D::Error> {
+        if !deserializer.is_human_readable() {
+            return super::deserialize_raw(deserializer);
+        }
+
+        let hex = String::deserialize(deserializer)?;
+
+        if hex.len() != 64 {
+            return Err(de::Error::custom("The key isn't 64 characters long."));
+        }
+
+        let mut key = [0; 32];
+
+        for (byte, digits) in key.iter_mut().zip(hex.as_bytes().chunks(2)) {
+            let high = (digits[0] as char).to_digit(16);
+            let low = (digits[1] as char).to_digit(16);
+
+            match (high, low) {
+                (Some(high), Some(low)) => *byte = (high * 16 + low) as u8,
+                _ => return Err(de::Error::custom("The key contains characters which aren't hex digits.")),
+            }
+        }
+
+        Ok(key)
+    }
+
+    /// Serializes `Option<[u8; 32]>` values, to be used with `#[serde(default)]`.
+    pub mod option {
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+        struct KeyRef<'a>(&'a [u8; 32]);
+
+        impl Serialize for KeyRef<'_> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                super::serialize(self.0, serializer)
+            }
+        }
+
+        pub fn serialize<S: Serializer>(bytes: &Option<[u8; 32]>, serializer: S) -> Result<S::Ok, S::Error> {
+            match bytes {
+                Some(bytes) => serializer.serialize_some(&KeyRef(bytes)),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<[u8; 32]>, D::Error> {
+            #[derive(Deserialize)]
+            struct Key(#[serde(with = "super")] [u8; 32]);
+
+            Ok(Option::<Key>::deserialize(deserializer)?.map(|Key(bytes)| bytes))
+        }
+    }
+}
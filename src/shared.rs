@@ -0,0 +1,181 @@
+//! A `BlockChain` shared between threads, e.g. by a node serving concurrent requests,
+//! behind a read-write lock: any number of threads can read the chain at the same time,
+//! while a thread changing the chain has it to itself.
+
+use crate::{
+    account::Account,
+    amount::Balance,
+    block::Block,
+    blockchain::{BlockChain, RejectReason},
+};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// A structure to share a `BlockChain` between threads: the clones of a `SharedChain` share the same chain.
+/// 
+/// The convenience methods, like `read_tip()` or `submit_block()`, hold the lock only while they run,
+/// so they can be called from any thread without managing the lock. There's a single lock,
+/// so the only rule to avoid deadlocks is not to call a method of the `SharedChain` while holding
+/// a guard returned by `read()` or `write()` in the same thread; for the same reason, the callbacks
+/// registered with `BlockChain::on_block()`, `BlockChain::on_reorg()` and `BlockChain::on_retarget()`, which are called
+/// while the chain is locked for writing, must not use the `SharedChain`.
+/// 
+/// If a thread panics while holding the lock, the chain is still returned to the other threads.
+/// 
+/// # Example
+/// ```
+/// # use blockchain::blockchain::BlockChain;
+/// # use blockchain::shared::SharedChain;
+/// use std::thread;
+/// 
+/// let shared = SharedChain::new(BlockChain::new_with_difficulty(5, 4));
+/// let writer = shared.clone();
+/// 
+/// thread::spawn(move || writer.write().add_block(Vec::new()).unwrap()).join().unwrap();
+/// 
+/// assert_eq!(shared.read_tip().index(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SharedChain {
+    blockchain: Arc<RwLock<BlockChain>>,
+}
+
+impl SharedChain {
+    /// Generates a new `SharedChain` sharing the given `BlockChain`.
+    pub fn new(blockchain: BlockChain) -> Self {
+        Self { blockchain: Arc::new(RwLock::new(blockchain)) }
+    }
+
+    /// This method locks the chain for reading and returns it; the other readers aren't blocked,
+    /// but the writers wait until the guard is dropped.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::shared::SharedChain;
+    /// let shared = SharedChain::new(BlockChain::new_with_difficulty(5, 4));
+    /// 
+    /// assert!(shared.read().is_valid());
+    /// ```
+    pub fn read(&self) -> RwLockReadGuard<'_, BlockChain> {
+        self.blockchain.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// This method locks the chain for writing and returns it; the other threads wait until the guard is dropped.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::shared::SharedChain;
+    /// let shared = SharedChain::new(BlockChain::new_with_difficulty(5, 4));
+    /// 
+    /// shared.write().add_block(Vec::new()).unwrap();
+    /// 
+    /// assert_eq!(shared.read().height(), 1);
+    /// ```
+    pub fn write(&self) -> RwLockWriteGuard<'_, BlockChain> {
+        self.blockchain.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// This method returns a copy of the last block of the chain, see `BlockChain::tip()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::shared::SharedChain;
+    /// let shared = SharedChain::new(BlockChain::new_with_difficulty(5, 4));
+    /// 
+    /// assert_eq!(shared.read_tip().index(), 0);
+    /// ```
+    pub fn read_tip(&self) -> Block {
+        self.read().tip().clone()
+    }
+
+    /// This method puts a block mined somewhere else at the end of the chain, see `BlockChain::submit_external_block()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::shared::SharedChain;
+    /// let blockchain = BlockChain::new_with_difficulty(5, 4);
+    /// let mut peer = blockchain.clone();
+    /// peer.add_block(Vec::new()).unwrap();
+    /// 
+    /// let shared = SharedChain::new(blockchain);
+    /// 
+    /// assert_eq!(shared.submit_block(peer.tip().clone()), Ok(()));
+    /// assert_eq!(shared.read_tip(), *peer.tip());
+    /// ```
+    pub fn submit_block(&self, block: Block) -> Result<(), RejectReason> {
+        self.write().submit_external_block(block)
+    }
+
+    /// This method returns the balance of the given account, see `BlockChain::balance_of()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::blockchain::BlockChain;
+    /// # use blockchain::shared::SharedChain;
+    /// # use blockchain::account::Account;
+    /// let nina = Account::new("Nina", "Holt", "n1n4_h0lt").unwrap();
+    /// 
+    /// let shared = SharedChain::new(BlockChain::with_genesis_allocations(5, 4, vec![(nina.clone(), 30)]));
+    /// 
+    /// assert_eq!(shared.query_balance(&nina), 30);
+    /// ```
+    pub fn query_balance(&self, account: &Account) -> Balance {
+        self.read().balance_of(account)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn concurrent_readers_and_a_writer_agree() {
+        let miner = Account::new("Miner", "Test", "miner_password").unwrap();
+
+        let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+        blockchain.set_miner(miner.clone());
+        blockchain.set_block_reward(10);
+
+        let shared = SharedChain::new(blockchain);
+
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let shared = shared.clone();
+                let miner = miner.clone();
+
+                thread::spawn(move || {
+                    for _ in 0..50 {
+                        let blockchain = shared.read();
+
+                        // every block pays the miner, so the balance follows the height
+                        assert_eq!(blockchain.balance_of(&miner), 10 * blockchain.height() as Balance);
+                        assert_eq!(blockchain.tip().index(), blockchain.height());
+                    }
+                })
+            })
+            .collect();
+
+        let writer = {
+            let shared = shared.clone();
+
+            thread::spawn(move || {
+                for _ in 0..5 {
+                    shared.write().mine_n_empty(1).unwrap();
+                }
+            })
+        };
+
+        writer.join().unwrap();
+
+        for reader in readers {
+            reader.join().unwrap();
+        }
+
+        assert_eq!(shared.read_tip().index(), 5);
+        assert_eq!(shared.query_balance(&miner), 50);
+    }
+}
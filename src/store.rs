@@ -0,0 +1,575 @@
+//! The storage backends of the blocks, behind the `ChainStore` trait, so that a `BlockChain`
+//! can persist its blocks one by one as they are added, see `BlockChain::set_store()`.
+
+use crate::block::Block;
+use std::io;
+#[cfg(feature = "serde")]
+use std::{fs::{self, File, OpenOptions}, io::{BufRead, BufReader, Write}, path::{Path, PathBuf}};
+#[cfg(feature = "bincode")]
+use std::io::{Read, Seek, SeekFrom};
+#[cfg(all(feature = "mmap", unix))]
+use std::{convert::TryFrom, os::unix::io::AsRawFd};
+
+/// A storage of the blocks of a chain, in chain order.
+/// 
+/// The blocks are appended one at a time with `append_block()`; when the chain is replaced,
+/// e.g. by `BlockChain::replace_chain()`, the blocks after the fork are removed with `truncate()`.
+/// 
+/// # Example
+/// ```
+/// # use blockchain::store::{ChainStore, InMemoryChainStore};
+/// # use blockchain::block::Block;
+/// let mut store = InMemoryChainStore::new();
+/// 
+/// store.append_block(&Block::new_with_difficulty(0, [0; 64], Vec::new(), 4)).unwrap();
+/// 
+/// assert_eq!(store.len(), 1);
+/// assert_eq!(store.load_blocks().unwrap()[0].index(), 0);
+/// ```
+pub trait ChainStore {
+    /// Returns all the blocks of the store, in the order they were appended.
+    fn load_blocks(&self) -> io::Result<Vec<Block>>;
+
+    /// Appends a block after the blocks of the store.
+    fn append_block(&mut self, block: &Block) -> io::Result<()>;
+
+    /// Removes the blocks after the first `len` blocks of the store.
+    fn truncate(&mut self, len: usize) -> io::Result<()>;
+
+    /// Returns the number of blocks of the store.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the store has no blocks.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the block with the given position in the store, or `None` if the store has no such block;
+    /// by default all the blocks are loaded, but a store can read only the requested block.
+    fn block_by_index(&self, index: usize) -> io::Result<Option<Block>> {
+        Ok(self.load_blocks()?.into_iter().nth(index))
+    }
+}
+
+/// A `ChainStore` keeping the blocks in memory, e.g. for testing.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InMemoryChainStore {
+    blocks: Vec<Block>,
+}
+
+impl InMemoryChainStore {
+    /// Generates a new `InMemoryChainStore`, without blocks.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::store::{ChainStore, InMemoryChainStore};
+    /// assert!(InMemoryChainStore::new().is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ChainStore for InMemoryChainStore {
+    fn load_blocks(&self) -> io::Result<Vec<Block>> {
+        Ok(self.blocks.clone())
+    }
+
+    fn append_block(&mut self, block: &Block) -> io::Result<()> {
+        self.blocks.push(block.clone());
+
+        Ok(())
+    }
+
+    fn truncate(&mut self, len: usize) -> io::Result<()> {
+        self.blocks.truncate(len);
+
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.blocks.len()
+    }
+}
+
+/// A `ChainStore` keeping the blocks in a file as JSON Lines, i.e. one block per line,
+/// like `BlockChain::export_jsonl()`: appending a block writes only its line at the end of the file.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileChainStore {
+    path: PathBuf,
+    len: usize,
+}
+
+#[cfg(feature = "serde")]
+impl FileChainStore {
+    /// Opens the store in the file at the given path, creating the file if it doesn't exist.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::store::{ChainStore, FileChainStore};
+    /// let path = std::env::temp_dir().join("blockchain_file_chain_store_open.jsonl");
+    /// # std::fs::remove_file(&path).ok();
+    /// 
+    /// let store = FileChainStore::open(&path).unwrap();
+    /// 
+    /// assert!(store.is_empty());
+    /// ```
+    pub fn open(path: &Path) -> io::Result<Self> {
+        OpenOptions::new().create(true).append(true).open(path)?;
+
+        let mut store = Self { path: path.to_path_buf(), len: 0 };
+        store.len = store.load_blocks()?.len();
+
+        Ok(store)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ChainStore for FileChainStore {
+    fn load_blocks(&self) -> io::Result<Vec<Block>> {
+        let mut blocks = Vec::new();
+
+        for line in BufReader::new(File::open(&self.path)?).lines() {
+            let line = line?;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            blocks.push(serde_json::from_str(&line)?);
+        }
+
+        Ok(blocks)
+    }
+
+    fn append_block(&mut self, block: &Block) -> io::Result<()> {
+        let mut line = serde_json::to_vec(block)?;
+        line.push(b'\n');
+
+        OpenOptions::new().append(true).open(&self.path)?.write_all(&line)?;
+        self.len += 1;
+
+        Ok(())
+    }
+
+    fn truncate(&mut self, len: usize) -> io::Result<()> {
+        if len >= self.len {
+            return Ok(());
+        }
+
+        let mut contents = Vec::new();
+
+        for block in self.load_blocks()?.iter().take(len) {
+            serde_json::to_writer(&mut contents, block)?;
+            contents.push(b'\n');
+        }
+
+        fs::write(&self.path, contents)?;
+        self.len = len;
+
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+/// A `ChainStore` keeping the blocks in a file in the binary format of `Block::to_bytes()`,
+/// each block preceded by the length of its encoding as a little-endian `u64`.
+/// 
+/// The store keeps the position of every block in the file, so `block_by_index()` reads and decodes
+/// only the requested block, and `truncate()` only cuts the file, without reading the whole chain.
+/// 
+/// The private fields of the struct are the path of the file and the offsets of the blocks in the file,
+/// followed by the length of the file. The store is available with the `bincode` feature.
+/// 
+/// # Example
+/// ```
+/// # use blockchain::store::{ChainStore, BinaryChainStore};
+/// # use blockchain::blockchain::BlockChain;
+/// let path = std::env::temp_dir().join("blockchain_binary_chain_store.bin");
+/// # std::fs::remove_file(&path).ok();
+/// 
+/// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+/// blockchain.set_store(Box::new(BinaryChainStore::open(&path).unwrap())).unwrap();
+/// blockchain.add_block(Vec::new()).unwrap();
+/// 
+/// let store = BinaryChainStore::open(&path).unwrap();
+/// 
+/// assert_eq!(store.len(), 2);
+/// assert_eq!(store.block_by_index(1).unwrap().as_ref(), blockchain.block_by_index(1));
+/// ```
+#[cfg(feature = "bincode")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinaryChainStore {
+    path: PathBuf,
+    offsets: Vec<u64>,
+}
+
+#[cfg(feature = "bincode")]
+impl BinaryChainStore {
+    /// Opens the store in the file at the given path, creating the file if it doesn't exist;
+    /// only the lengths of the records are read, to find where the blocks are, and the blocks aren't decoded.
+    /// 
+    /// If the file ends in the middle of a record, e.g. because a write was interrupted,
+    /// an `io::ErrorKind::UnexpectedEof` error is returned, and if the length of a record is too large
+    /// to be an offset in the file, an `io::ErrorKind::InvalidData` error is returned.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let mut file = OpenOptions::new().read(true).append(true).create(true).open(path)?;
+        let file_len = file.metadata()?.len();
+
+        let mut offsets = vec![0];
+
+        while offsets[offsets.len() - 1] < file_len {
+            let offset = offsets[offsets.len() - 1];
+
+            file.seek(SeekFrom::Start(offset))?;
+
+            let next = read_len(&mut file)?
+                .checked_add(offset + 8)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "The length of a block of the file is corrupt."))?;
+
+            if next > file_len {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "The last block of the file is incomplete."));
+            }
+
+            offsets.push(next);
+        }
+
+        Ok(Self { path: path.to_path_buf(), offsets })
+    }
+
+    /// Reads and decodes the block of the record at the current position of the file;
+    /// the length of the record is checked against the rest of the file before the block is read.
+    fn read_block(file: &mut File) -> io::Result<Block> {
+        let len = read_len(file)?;
+        let left = file.metadata()?.len().saturating_sub(file.stream_position()?);
+
+        if len > left {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "The block is longer than the rest of the file."));
+        }
+
+        let mut bytes = vec![0; len as usize];
+        file.read_exact(&mut bytes)?;
+
+        Block::from_bytes(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// Reads the length of a record of a `BinaryChainStore`.
+#[cfg(feature = "bincode")]
+fn read_len(file: &mut File) -> io::Result<u64> {
+    let mut len = [0; 8];
+    file.read_exact(&mut len)?;
+
+    Ok(u64::from_le_bytes(len))
+}
+
+#[cfg(feature = "bincode")]
+impl ChainStore for BinaryChainStore {
+    fn load_blocks(&self) -> io::Result<Vec<Block>> {
+        let mut file = File::open(&self.path)?;
+
+        (0..self.len()).map(|_| BinaryChainStore::read_block(&mut file)).collect()
+    }
+
+    fn append_block(&mut self, block: &Block) -> io::Result<()> {
+        let bytes = block.to_bytes();
+
+        let mut record = Vec::with_capacity(8 + bytes.len());
+        record.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+        record.extend_from_slice(&bytes);
+
+        OpenOptions::new().append(true).open(&self.path)?.write_all(&record)?;
+
+        let end = self.offsets[self.offsets.len() - 1];
+        self.offsets.push(end + record.len() as u64);
+
+        Ok(())
+    }
+
+    fn truncate(&mut self, len: usize) -> io::Result<()> {
+        if len >= self.len() {
+            return Ok(());
+        }
+
+        OpenOptions::new().write(true).open(&self.path)?.set_len(self.offsets[len])?;
+        self.offsets.truncate(len + 1);
+
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        // the last offset is the end of the file
+        self.offsets.len() - 1
+    }
+
+    fn block_by_index(&self, index: usize) -> io::Result<Option<Block>> {
+        if index >= self.len() {
+            return Ok(None);
+        }
+
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(self.offsets[index]))?;
+
+        BinaryChainStore::read_block(&mut file).map(Some)
+    }
+}
+
+/// A `ChainStore` keeping the blocks in a file like a `BinaryChainStore`, which is memory-mapped,
+/// so that `block_by_index()` decodes the requested block straight from the mapped file, without reading it.
+/// 
+/// The file is mapped again after every change made through the store: the file mustn't be shortened
+/// by another process while it's mapped, since reading the pages past its end would crash the process.
+/// The store is available on Unix with the `mmap` feature, which enables the `bincode` feature.
+/// 
+/// # Example
+/// ```
+/// # use blockchain::store::{ChainStore, MmapChainStore};
+/// # use blockchain::blockchain::BlockChain;
+/// let path = std::env::temp_dir().join("blockchain_mmap_chain_store.bin");
+/// # std::fs::remove_file(&path).ok();
+/// 
+/// let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+/// blockchain.set_store(Box::new(MmapChainStore::open(&path).unwrap())).unwrap();
+/// blockchain.add_block(Vec::new()).unwrap();
+/// 
+/// let store = MmapChainStore::open(&path).unwrap();
+/// 
+/// assert_eq!(store.len(), 2);
+/// assert_eq!(store.block_by_index(1).unwrap().as_ref(), blockchain.block_by_index(1));
+/// ```
+#[cfg(all(feature = "mmap", unix))]
+#[derive(Debug)]
+pub struct MmapChainStore {
+    store: BinaryChainStore,
+    map: Option<Mmap>,
+}
+
+#[cfg(all(feature = "mmap", unix))]
+impl MmapChainStore {
+    /// Opens the store in the file at the given path, creating the file if it doesn't exist, and maps the file;
+    /// the errors are the errors of `BinaryChainStore::open()`, and of the mapping.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let store = BinaryChainStore::open(path)?;
+        let map = Mmap::map(path)?;
+
+        Ok(Self { store, map })
+    }
+
+    /// Returns the bytes of the record of the block with the given position in the store, without its length.
+    fn record(&self, index: usize) -> io::Result<&[u8]> {
+        let bytes = self.map.as_ref().map_or(&[][..], Mmap::bytes);
+
+        let start = self.store.offsets[index] as usize + 8;
+        let end = self.store.offsets[index + 1] as usize;
+
+        bytes.get(start..end).ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "The block isn't in the mapped file."))
+    }
+}
+
+#[cfg(all(feature = "mmap", unix))]
+impl ChainStore for MmapChainStore {
+    fn load_blocks(&self) -> io::Result<Vec<Block>> {
+        (0..self.len()).map(|index| self.block_by_index(index)?.ok_or_else(|| io::ErrorKind::UnexpectedEof.into())).collect()
+    }
+
+    fn append_block(&mut self, block: &Block) -> io::Result<()> {
+        self.store.append_block(block)?;
+        self.map = Mmap::map(&self.store.path)?;
+
+        Ok(())
+    }
+
+    fn truncate(&mut self, len: usize) -> io::Result<()> {
+        // the file is unmapped before it's cut, so the pages past the new end are never read
+        self.map = None;
+        self.store.truncate(len)?;
+        self.map = Mmap::map(&self.store.path)?;
+
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.store.len()
+    }
+
+    fn block_by_index(&self, index: usize) -> io::Result<Option<Block>> {
+        if index >= self.len() {
+            return Ok(None);
+        }
+
+        Block::from_bytes(self.record(index)?).map(Some).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// A read-only mapping of a whole file in memory, which is unmapped when it's dropped.
+#[cfg(all(feature = "mmap", unix))]
+#[derive(Debug)]
+struct Mmap {
+    ptr: *mut libc::c_void,
+    len: usize,
+}
+
+// the mapping is read-only, so it can be shared between threads like a `&[u8]`
+#[cfg(all(feature = "mmap", unix))]
+unsafe impl Send for Mmap {}
+#[cfg(all(feature = "mmap", unix))]
+unsafe impl Sync for Mmap {}
+
+#[cfg(all(feature = "mmap", unix))]
+impl Mmap {
+    /// Maps the file at the given path, or returns `None` if the file is empty, since an empty file can't be mapped.
+    fn map(path: &Path) -> io::Result<Option<Self>> {
+        let file = File::open(path)?;
+        let len = usize::try_from(file.metadata()?.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "The file is too large to be mapped."))?;
+
+        if len == 0 {
+            return Ok(None);
+        }
+
+        // SAFETY: a new private read-only mapping of `len` bytes is requested for an open file,
+        // and the result is checked before it's used; the mapping outlives the file descriptor
+        let ptr = unsafe { libc::mmap(std::ptr::null_mut(), len, libc::PROT_READ, libc::MAP_PRIVATE, file.as_raw_fd(), 0) };
+
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Some(Self { ptr, len }))
+    }
+
+    /// Returns the bytes of the mapped file.
+    fn bytes(&self) -> &[u8] {
+        // SAFETY: the mapping is readable for `len` bytes until it's dropped, and it's never written
+        unsafe { std::slice::from_raw_parts(self.ptr as *const u8, self.len) }
+    }
+}
+
+#[cfg(all(feature = "mmap", unix))]
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        // SAFETY: the mapping was created by `map()` with the same address and length, and it's unmapped only once
+        unsafe {
+            libc::munmap(self.ptr, self.len);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use crate::blockchain::BlockChain;
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn single_blocks_are_read_from_the_binary_store() {
+        let path = std::env::temp_dir().join("blockchain_single_blocks_are_read_from_the_binary_store.bin");
+        std::fs::remove_file(&path).ok();
+
+        let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+        blockchain.set_store(Box::new(BinaryChainStore::open(&path).unwrap())).unwrap();
+        blockchain.mine_n_empty(3).unwrap();
+
+        let store = BinaryChainStore::open(&path).unwrap();
+
+        assert_eq!(store.len(), 4);
+        assert_eq!(store.load_blocks().unwrap(), blockchain.chain());
+
+        // the last block is corrupted, but the other blocks are read without decoding it
+        let mut file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.seek(SeekFrom::Start(store.offsets[3] + 8)).unwrap();
+        file.write_all(&[0xff; 4]).unwrap();
+
+        assert_eq!(store.block_by_index(1).unwrap().as_ref(), blockchain.block_by_index(1));
+        assert_eq!(store.block_by_index(2).unwrap().as_ref(), blockchain.block_by_index(2));
+        assert!(store.block_by_index(3).is_err());
+        assert!(store.block_by_index(4).unwrap().is_none());
+        assert!(store.load_blocks().is_err());
+
+        let mut store = store;
+        store.truncate(3).unwrap();
+
+        assert_eq!(BinaryChainStore::open(&path).unwrap().load_blocks().unwrap(), blockchain.chain()[..3]);
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn corrupt_lengths_are_errors() {
+        let path = std::env::temp_dir().join("blockchain_corrupt_lengths_are_errors.bin");
+        std::fs::remove_file(&path).ok();
+
+        let mut store = BinaryChainStore::open(&path).unwrap();
+        store.append_block(&Block::new_with_difficulty(0, [0; 64], Vec::new(), 4)).unwrap();
+
+        // a length overflowing the offset of the next record
+        let mut file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.write_all(&u64::MAX.to_le_bytes()).unwrap();
+
+        assert_eq!(BinaryChainStore::open(&path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        // a length past the end of the file isn't allocated
+        file.seek(SeekFrom::Start(0)).unwrap();
+        file.write_all(&(u64::MAX / 2).to_le_bytes()).unwrap();
+
+        assert_eq!(BinaryChainStore::open(&path).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(store.block_by_index(0).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn appended_blocks_survive_reload() {
+        let path = std::env::temp_dir().join("blockchain_appended_blocks_survive_reload.jsonl");
+        fs::remove_file(&path).ok();
+
+        let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+        blockchain.set_store(Box::new(FileChainStore::open(&path).unwrap())).unwrap();
+
+        blockchain.add_block(Vec::new()).unwrap();
+        blockchain.add_block(Vec::new()).unwrap();
+
+        let store = FileChainStore::open(&path).unwrap();
+
+        assert_eq!(store.len(), 3);
+        assert_eq!(store.load_blocks().unwrap(), blockchain.chain());
+
+        let reloaded = BlockChain::from_store(5, 4, Box::new(store)).unwrap();
+
+        assert_eq!(reloaded.chain(), blockchain.chain());
+        assert_eq!(reloaded.height(), 2);
+    }
+
+    #[cfg(all(feature = "mmap", unix))]
+    #[test]
+    fn single_blocks_are_read_from_the_mapped_file() {
+        let path = std::env::temp_dir().join("blockchain_single_blocks_are_read_from_the_mapped_file.bin");
+        std::fs::remove_file(&path).ok();
+
+        let mut blockchain = BlockChain::new_with_difficulty(5, 4);
+        blockchain.set_store(Box::new(MmapChainStore::open(&path).unwrap())).unwrap();
+        blockchain.mine_n_empty(3).unwrap();
+
+        // the last block is corrupted before the file is mapped, but the other blocks are decoded without it
+        let offsets = BinaryChainStore::open(&path).unwrap().offsets;
+        let mut file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.seek(SeekFrom::Start(offsets[3] + 8)).unwrap();
+        file.write_all(&[0xff; 4]).unwrap();
+
+        let mut store = MmapChainStore::open(&path).unwrap();
+
+        assert_eq!(store.len(), 4);
+        assert_eq!(store.block_by_index(1).unwrap().as_ref(), blockchain.block_by_index(1));
+        assert_eq!(store.block_by_index(2).unwrap().as_ref(), blockchain.block_by_index(2));
+        assert!(store.block_by_index(3).is_err());
+        assert!(store.block_by_index(4).unwrap().is_none());
+        assert!(store.load_blocks().is_err());
+
+        store.truncate(3).unwrap();
+        store.append_block(&blockchain.chain()[3]).unwrap();
+
+        assert_eq!(store.load_blocks().unwrap(), blockchain.chain());
+    }
+}
@@ -1,230 +1,1192 @@
-use std::{fmt, error};
-use std::convert::{TryInto, TryFrom};
-use sha2::{Sha512, Digest};
-use chrono::{DateTime, Utc};
-use crate::{
-    account::Account,
-    positive_f64::PositiveF64
-};
-use ed25519_dalek::{
-    Keypair,
-    Signature,
-    Signer,
-};
-
-/// A structure to handle the transactions of the blockchain.
-/// 
-/// Every transaction contains:
-/// - the sender's `Account`
-/// - the receiver's `Account`
-/// - the amount of the transaction
-/// - the `DateTime<Utc>` time when the block was generated
-/// - the SHA-512 hash of the sender's password
-/// - the message to be signed
-/// - the digital signature of the message
-/// - the hash of the transaction
-/// 
-/// Note: this structure **does not** handle the actual transfer of money,
-/// `.push_transaction()` from `blockchain::BlockChain` handles that.
-#[derive(Debug, Clone, PartialEq)]
-pub struct Transaction {
-    pub sender: Account,
-    pub receiver: Account,
-    amount: f64,
-    time: DateTime<Utc>,
-    hash_sender_password: [u8; 64],
-    message: String,
-    signature: [u8; 64],
-    hash: [u8; 64],
-}
-
-impl Transaction {
-    /// Generates a new `Transaction`.
-    /// 
-    /// In order to perform a new transaction, the sender must specify his account, his password,
-    /// the amount to transfer and the receiver's account.
-    /// 
-    /// # Example
-    /// ```
-    /// # use blockchain::transaction::Transaction;
-    /// # use blockchain::account::Account;
-    /// let mut alvin = Account::new("Alvin", "Wilton", "alvin_wilton_1990#");
-    /// let egbert = Account::new("Egbert", "Tucker", "egby_tucky_PASS5000");
-    /// alvin.add_money(400.0);
-    /// 
-    /// let transaction = Transaction::new(alvin, egbert, 30.0, "alvin_wilton_1990#");
-    /// 
-    /// assert_eq!(transaction.amount(), 30.0);
-    /// ```
-    pub fn new(sender: Account, receiver: Account, amount: f64, sender_password: &str) -> Self {
-        let mut hasher = Sha512::new();
-
-        hasher.update(sender_password.as_bytes());
-
-        let hash_sender_password = hasher
-            .finalize()[..]
-            .try_into()
-            .expect("Error generating the SHA-512 hash of the password.");
-
-        let mut transaction = Self {
-            sender,
-            receiver,
-            amount,
-            time: Utc::now(),
-            hash_sender_password,
-            message: String::new(),
-            signature: [0; 64],
-            hash: [0; 64],
-        };
-
-        transaction.sign();
-
-        transaction.calculate_hash();
-
-        transaction
-    }
-
-    /// This method returns the amount of the transaction, since the `amount` field isn't `pub`.
-    /// 
-    /// # Example
-    /// ```
-    /// # use blockchain::transaction::Transaction;
-    /// # use blockchain::account::Account;
-    /// let mut chloe = Account::new("Chloe", "Savage", "KloeeSavageTrue1234");
-    /// let zoey = Account::new("Zoey", "Jacobson", "ZoomingPassword000#");
-    /// chloe.add_money(300.0);
-    /// 
-    /// let transaction = Transaction::new(chloe, zoey, 20.0, "KloeeSavageTrue1234");
-    /// 
-    /// assert_eq!(transaction.amount(), 20.0);
-    /// ```
-    pub fn amount(&self) -> f64 {
-        self.amount
-    }
-
-    /// This method returns the hash of the transaction, since the `hash` field isn't `pub`.
-    /// 
-    /// # Example
-    /// ```
-    /// # use blockchain::transaction::Transaction;
-    /// # use blockchain::account::Account;
-    /// let mut luke = Account::new("Luke", "Steffen", "Luke_Steffen999");
-    /// let jerold = Account::new("Jerold", "Butcher", "Jer0ld_Butcher1000##");
-    /// luke.add_money(70.0);
-    /// 
-    /// let transaction = Transaction::new(luke, jerold, 10.0, "Luke_Steffen999");
-    /// 
-    /// assert_eq!(transaction.hash().len(), 64);
-    /// ```
-    pub fn hash(&self) -> [u8; 64] {
-        self.hash
-    }
-
-    /// This method is called when a new transaction is generated,
-    /// and it is used to perform the digital signature of the new transaction.
-    /// 
-    /// The digital signature is generated using the `Keypair` from the sender's account,
-    /// using the `ed25519_dalek` crate.
-    /// 
-    /// The signature is performed on the `message`, generated by using:
-    /// - the sender's `Account`
-    /// - the receiver's `Account`
-    /// - the amount of the transaction
-    /// - the `DateTime<Utc>` time when the block was generated
-    fn sign(&mut self) {
-        let keypair = Keypair::from_bytes(&self.sender.keypair()).expect("Error generating the Keypair while signing the transaction.");
-        
-        self.message = format!("{}{}{}{:?}", self.sender, self.receiver, self.amount, self.time);
-
-        self.signature = keypair.sign(self.message.as_bytes()).to_bytes();
-    }
-
-    /// This method is called when a new transacion is generated,
-    /// and is is used to calculate the SHA-512 hash of the new transaction.
-    ///
-    /// The hash is calculated by using the `message` and the `signature`,
-    /// both fields generated in the `sign()` method.
-    fn calculate_hash(&mut self) {
-        let mut hasher = Sha512::new();
-
-        let message = format!("{:?}{:?}", self.message, self.signature);
-
-        hasher.update(message.as_bytes());
-
-        self.hash = hasher
-            .finalize()[..]
-            .try_into()
-            .expect("Error generating the SHA-512 hash of the transaction.");
-    }
-
-    /// This method checks if the transaction is valid,
-    /// and returns a `Err(ValidationError)` if the transaction isn't valid.
-    /// 
-    /// - If the hash in the input doesn't match with the `hash` of the transaction,
-    /// a `ValidationError::Tempered` error is returned.
-    /// - If the hash of the sender's password doesn't match with the `hash_sender_password` field,
-    /// a `ValidationError::WrongPassword` error is returned.
-    /// - If the signature verification doesn't succeed,
-    /// a `ValidationError::InvalidSign` error is returned.
-    /// - If the amount is zero or negative, or if the amount of the transaction is more than the sender's balance,
-    /// a `ValidationError::InvalidAmount` error is returned.
-    /// 
-    /// # Example
-    /// ```
-    /// # use blockchain::transaction::{Transaction, ValidationError};
-    /// # use blockchain::account::Account;
-    /// let mut odin = Account::new("Odin", "Dennell", "OdinDennellPass102938");
-    /// let kenya = Account::new("Kenya", "Dawson", "kenyyyya_dawwwwson69");
-    /// odin.add_money(10.0);
-    /// 
-    /// // cloning accounts because `Account` doesn't implement the `Copy` trait
-    /// let valid_transaction = Transaction::new(odin.clone(), kenya.clone(), 5.0, "OdinDennellPass102938");
-    /// 
-    /// assert_eq!(valid_transaction.validate(valid_transaction.hash()).unwrap(), ()); // if the transaction is valid `()` is returned
-    /// 
-    /// assert_eq!(kenya.balance(), 0.0); // as you can see Kenya still has no money (check the definition of the struct for reference)
-    /// 
-    /// let invalid_transaction = Transaction::new(kenya, odin, 80.0, "kenyyyya_dawwwwson69");
-    /// 
-    /// assert_eq!(invalid_transaction.validate(invalid_transaction.hash()), Err(ValidationError::InvalidAmount));
-    /// ```
-    pub fn validate(&self, hash: [u8; 64]) -> Result<(), ValidationError> {
-        let signature = Signature::try_from(self.signature).expect("Error generating the Signature while validating the transaction.");
-
-        let keypair = Keypair::from_bytes(&self.sender.keypair()).expect("Error generating the Keypair while validating the transaction.");
-
-        if hash != self.hash {
-            Err(ValidationError::Tempered)
-        } else if self.hash_sender_password != self.sender.hash_password() {
-            Err(ValidationError::WrongPassword)
-        } else if keypair.verify(self.message.as_bytes(), &signature).is_err() {
-            Err(ValidationError::InvalidSignature)
-        } else if PositiveF64::new(self.amount).is_err() || self.amount == 0.0 || self.amount > self.sender.balance() {
-            Err(ValidationError::InvalidAmount)
-        } else {
-            Ok(())
-        }
-    }
-}
-
-/// An enum to handle errors generated while validating `Transaction`s.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum ValidationError {
-    Tempered,
-    WrongPassword,
-    InvalidSignature,
-    InvalidAmount,
-}
-
-impl fmt::Display for ValidationError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match *self {
-            Self::Tempered =>  write!(f, "Tempered transaction."),
-            Self::WrongPassword => write!(f, "Wrong password."),
-            Self::InvalidSignature => write!(f, "Invalid signature."),
-            Self::InvalidAmount => write!(f, "Invalid amount.")
-        }
-    }
-}
-
-impl error::Error for ValidationError {}
+use std::{fmt, error};
+use chrono::{DateTime, Utc};
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+#[cfg(feature = "serde")]
+use std::{collections::HashMap, fs, io, path::Path};
+use crate::{
+    account::Account,
+    amount::Amount,
+    hasher::{Hasher, Sha512Hasher},
+};
+use crate::binary::Binary;
+#[cfg(feature = "bincode")]
+use crate::binary::{self, DecodeError};
+use ed25519_dalek::{
+    Keypair,
+    PublicKey,
+    Signature,
+    Signer,
+};
+use rayon::prelude::*;
+
+/// A structure to handle the transactions of the blockchain.
+/// 
+/// Every transaction contains:
+/// - the sender's `Account`, which is `None` for the coinbase transactions rewarding the miners
+/// - the outputs of the transaction, i.e. the receivers' `Account`s with the amount each one receives
+/// - the fee paid by the sender to the miner of the block
+/// - the nonce of the sender, which must increase with every transaction of the sender to prevent replays
+/// - the identifier of the chain the transaction is made for, so that it can't be replayed on another chain
+/// - an optional memo, i.e. a short note for the receivers, which is signed with the transaction
+/// - the `DateTime<Utc>` time when the transaction was generated
+/// - the digital signature of the transaction
+/// - the hash of the transaction
+/// 
+/// The sender's password is never kept in the transaction, not even hashed, so it can't leak
+/// when the transaction is serialized or encoded: only the signature proves that the sender authorized it.
+/// 
+/// Note: this structure **does not** handle the actual transfer of money,
+/// `.push_transaction()` from `blockchain::BlockChain` handles that.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Transaction {
+    pub sender: Option<Account>,
+    outputs: Vec<(Account, Amount)>,
+    fee: Amount,
+    nonce: u64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    chain_id: u32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    memo: Option<String>,
+    time: DateTime<Utc>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))]
+    signature: [u8; 64],
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))]
+    hash: [u8; 64],
+}
+
+impl Transaction {
+    /// Generates a new `Transaction`.
+    /// 
+    /// In order to perform a new transaction, the sender must specify his account, his password,
+    /// the amount to transfer and the receiver's account.
+    /// 
+    /// The function returns a `Result<Transaction, TxError>`: if the password isn't the password
+    /// of the sender, a `TxError::Unauthorized` error is returned, so a transaction can't be made on behalf of another account.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::transaction::{Transaction, TxError};
+    /// # use blockchain::account::Account;
+    /// let mut alvin = Account::new("Alvin", "Wilton", "alvin_wilton_1990#").unwrap();
+    /// let egbert = Account::new("Egbert", "Tucker", "egby_tucky_PASS5000").unwrap();
+    /// alvin.add_money(400);
+    /// 
+    /// let transaction = Transaction::new(alvin.clone(), egbert.clone(), 30, "alvin_wilton_1990#").unwrap();
+    /// 
+    /// assert_eq!(transaction.amount(), 30);
+    /// 
+    /// let forged = Transaction::new(alvin, egbert, 30, "egby_tucky_PASS5000"); // egbert can't spend alvin's money
+    /// 
+    /// assert_eq!(forged, Err(TxError::Unauthorized));
+    /// ```
+    pub fn new(sender: Account, receiver: Account, amount: Amount, sender_password: &str) -> Result<Self, TxError> {
+        Transaction::new_with_fee(sender, receiver, amount, 0, sender_password)
+    }
+
+    /// Generates a new `Transaction`, with a fee paid by the sender to the miner of the block
+    /// that includes the transaction; when there are more pending transactions than a block can hold,
+    /// the transactions with the highest fees are mined first.
+    /// 
+    /// Like `new()`, the function returns a `TxError::Unauthorized` error if the password isn't the password of the sender.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::transaction::{Transaction, TxError};
+    /// # use blockchain::account::Account;
+    /// let mut nora = Account::new("Nora", "Blake", "n0r4_bl4k3").unwrap();
+    /// let omar = Account::new("Omar", "Haddad", "Omar_Haddad_22").unwrap();
+    /// nora.add_money(50);
+    /// 
+    /// let transaction = Transaction::new_with_fee(nora.clone(), omar.clone(), 30, 1, "n0r4_bl4k3").unwrap();
+    /// 
+    /// assert_eq!(transaction.fee(), 1);
+    /// assert_eq!(Transaction::new_with_fee(nora, omar, 30, 1, "Omar_Haddad_22"), Err(TxError::Unauthorized));
+    /// ```
+    pub fn new_with_fee(sender: Account, receiver: Account, amount: Amount, fee: Amount, sender_password: &str) -> Result<Self, TxError> {
+        Transaction::new_with_outputs(sender, vec![(receiver, amount)], fee, sender_password)
+    }
+
+    /// Generates a new `Transaction` paying several receivers at once: every output
+    /// is a receiver's `Account` with the amount it receives, and the sender pays the sum of the amounts,
+    /// plus the fee.
+    /// 
+    /// Like `new()`, the function returns a `TxError::Unauthorized` error if the password isn't the password of the sender.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let mut fay = Account::new("Fay", "Lowe", "f4y_l0w3").unwrap();
+    /// let gus = Account::new("Gus", "Hart", "gus_hart_pw").unwrap();
+    /// let hal = Account::new("Hal", "Ives", "h4l.1v3s").unwrap();
+    /// fay.add_money(10);
+    /// 
+    /// let transaction = Transaction::new_with_outputs(fay, vec![(gus, 2), (hal, 3)], 1, "f4y_l0w3").unwrap();
+    /// 
+    /// assert_eq!(transaction.outputs().len(), 2);
+    /// assert_eq!(transaction.amount(), 5);
+    /// assert_eq!(transaction.validate(transaction.hash()), Ok(()));
+    /// ```
+    pub fn new_with_outputs(sender: Account, outputs: Vec<(Account, Amount)>, fee: Amount, sender_password: &str) -> Result<Self, TxError> {
+        if !sender.verify_password(sender_password) {
+            return Err(TxError::Unauthorized);
+        }
+
+        let mut transaction = Self {
+            sender: Some(sender),
+            outputs,
+            fee,
+            nonce: 0,
+            chain_id: 0,
+            memo: None,
+            time: Utc::now(),
+            signature: [0; 64],
+            hash: [0; 64],
+        };
+
+        transaction.sign();
+
+        transaction.calculate_hash();
+
+        Ok(transaction)
+    }
+
+    /// Generates a new coinbase `Transaction`, which creates the given amount of money
+    /// and gives it to the receiver, usually the miner of the block as a reward.
+    /// 
+    /// A coinbase transaction has no sender, so it has no password and no signature.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let miner = Account::new("Mina", "Rowe", "m1n3r_r0w3").unwrap();
+    /// 
+    /// let coinbase = Transaction::coinbase(miner, 50);
+    /// 
+    /// assert!(coinbase.is_coinbase());
+    /// assert_eq!(coinbase.validate(coinbase.hash()), Ok(()));
+    /// ```
+    pub fn coinbase(receiver: Account, amount: Amount) -> Self {
+        let mut transaction = Self {
+            sender: None,
+            outputs: vec![(receiver, amount)],
+            fee: 0,
+            nonce: 0,
+            chain_id: 0,
+            memo: None,
+            time: Utc::now(),
+            signature: [0; 64],
+            hash: [0; 64],
+        };
+
+        transaction.sign();
+
+        transaction.calculate_hash();
+
+        transaction
+    }
+
+    /// This method sets the nonce of the transaction and signs it again; the transactions created
+    /// with the other functions have a nonce of `0`.
+    /// 
+    /// Every transaction of a sender must have a different nonce, and a transaction can't be put in the
+    /// pending transactions of a `BlockChain` if its nonce isn't greater than the nonces already mined for the sender,
+    /// so the same transaction can't be put in the chain twice.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let mut yves = Account::new("Yves", "Martin", "yv3s_m4rt1n").unwrap();
+    /// let zoe = Account::new("Zoe", "Clark", "zoe_clark_pw").unwrap();
+    /// yves.add_money(5);
+    /// 
+    /// let transaction = Transaction::new(yves, zoe, 1, "yv3s_m4rt1n").unwrap().with_nonce(3);
+    /// 
+    /// assert_eq!(transaction.nonce(), 3);
+    /// assert!(transaction.verify_signature()); // the new nonce is signed too
+    /// ```
+    pub fn with_nonce(mut self, nonce: u64) -> Self {
+        self.nonce = nonce;
+
+        self.sign();
+
+        self.calculate_hash();
+
+        self
+    }
+
+    /// This method sets the identifier of the chain the transaction is made for and signs it again;
+    /// the transactions created with the other functions have a chain identifier of `0`.
+    /// 
+    /// A `BlockChain` accepts only the transactions with its own identifier, see `BlockChain::new_with_chain_id()`,
+    /// so a transaction signed for a chain can't be replayed on another chain.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let mut abel = Account::new("Abel", "Rios", "4b3l_r10s").unwrap();
+    /// let bess = Account::new("Bess", "Caro", "bess.caro.pw").unwrap();
+    /// abel.add_money(5);
+    /// 
+    /// let transaction = Transaction::new(abel, bess, 1, "4b3l_r10s").unwrap();
+    /// let bound = transaction.clone().with_chain_id(7);
+    /// 
+    /// assert_eq!(bound.chain_id(), 7);
+    /// assert!(bound.verify_signature()); // the chain identifier is signed too
+    /// assert_ne!(bound.hash(), transaction.hash());
+    /// ```
+    pub fn with_chain_id(mut self, chain_id: u32) -> Self {
+        self.chain_id = chain_id;
+
+        self.sign();
+
+        self.calculate_hash();
+
+        self
+    }
+
+    /// This method attaches a memo to the transaction, i.e. a short note for the receivers like the memo
+    /// of a bank transfer, and signs the transaction again: the memo is part of the hash of the transaction.
+    /// 
+    /// A `BlockChain` rejects the memos longer than its maximum length, see `BlockChain::set_max_memo_len()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let mut cora = Account::new("Cora", "Lind", "c0r4_l1nd").unwrap();
+    /// let dale = Account::new("Dale", "Moss", "dale.moss.pw").unwrap();
+    /// cora.add_money(5);
+    /// 
+    /// let transaction = Transaction::new(cora, dale, 2, "c0r4_l1nd").unwrap();
+    /// let with_memo = transaction.clone().with_memo("Rent, March");
+    /// 
+    /// assert_eq!(with_memo.memo(), Some("Rent, March"));
+    /// assert!(with_memo.verify_signature());
+    /// assert_ne!(with_memo.hash(), transaction.hash());
+    /// ```
+    pub fn with_memo(mut self, memo: &str) -> Self {
+        self.memo = Some(memo.to_string());
+
+        self.sign();
+
+        self.calculate_hash();
+
+        self
+    }
+
+    /// Generates a new `Transaction`, checking the amount first.
+    /// 
+    /// The function returns a `Result<Transaction, TxError>`:
+    /// - if the amount is zero, a `TxError::InvalidAmount` error is returned
+    /// - if the amount is more than the sender's balance, a `TxError::InsufficientFunds` error is returned
+    /// - if the password isn't the password of the sender, a `TxError::Unauthorized` error is returned
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::transaction::{Transaction, TxError};
+    /// # use blockchain::account::Account;
+    /// let mut iris = Account::new("Iris", "West", "iris_WEST_2014").unwrap();
+    /// let barry = Account::new("Barry", "Allen", "fastest_man_alive").unwrap();
+    /// iris.add_money(20);
+    /// 
+    /// let transaction = Transaction::try_new(iris.clone(), barry.clone(), 20, "iris_WEST_2014"); // the whole balance can be spent
+    /// 
+    /// assert!(transaction.is_ok());
+    /// 
+    /// let overspending = Transaction::try_new(iris.clone(), barry.clone(), 21, "iris_WEST_2014");
+    /// 
+    /// assert_eq!(overspending, Err(TxError::InsufficientFunds));
+    /// 
+    /// let empty = Transaction::try_new(iris, barry, 0, "iris_WEST_2014");
+    /// 
+    /// assert_eq!(empty, Err(TxError::InvalidAmount));
+    /// ```
+    pub fn try_new(sender: Account, receiver: Account, amount: Amount, sender_password: &str) -> Result<Self, TxError> {
+        if amount == 0 {
+            Err(TxError::InvalidAmount)
+        } else if amount > sender.balance() {
+            Err(TxError::InsufficientFunds)
+        } else {
+            Transaction::new(sender, receiver, amount, sender_password)
+        }
+    }
+
+    /// Loads a batch of `Transaction`s from the JSON file at the given path, e.g. to seed a chain for testing.
+    /// 
+    /// The file contains a list of entries like `{"from": "...", "to": "...", "amount": 150, "fee": 10}`,
+    /// where `from` and `to` are addresses returned by `Account::address()` and the `fee` can be omitted;
+    /// the addresses are resolved with the given accounts, each one with its password, used to sign
+    /// the transactions it sends. The transactions of every sender get the nonces `0`, `1`, `2` and so on,
+    /// in the order of the file.
+    /// 
+    /// The function returns a `Result<Vec<Transaction>, BatchError>`:
+    /// - if the file can't be read, a `BatchError::Io` error is returned
+    /// - if the file doesn't contain a list of entries, a `BatchError::Parse` error is returned
+    /// - if an address isn't one of the given accounts, a `BatchError::UnknownAccount` error is returned
+    /// - if the password given with a sender isn't its password, a `BatchError::Unauthorized` error is returned
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::transaction::{Transaction, BatchError};
+    /// # use blockchain::account::Account;
+    /// let mut kim = Account::new("Kim", "Lee", "k1m_l33").unwrap();
+    /// let max = Account::new("Max", "Ford", "max_ford_pw").unwrap();
+    /// kim.add_money(10);
+    /// 
+    /// let path = std::env::temp_dir().join("blockchain_load_batch.json");
+    /// 
+    /// let batch = format!(
+    ///     r#"[{{"from": "{0}", "to": "{1}", "amount": 2, "fee": 1}}, {{"from": "{0}", "to": "{1}", "amount": 3}}]"#,
+    ///     kim.address(),
+    ///     max.address(),
+    /// );
+    /// std::fs::write(&path, batch).unwrap();
+    /// 
+    /// let accounts = [(kim, "k1m_l33"), (max.clone(), "max_ford_pw")];
+    /// let transactions = Transaction::load_batch(&path, &accounts).unwrap();
+    /// 
+    /// assert_eq!(transactions.iter().map(|t| t.amount()).collect::<Vec<_>>(), vec![2, 3]);
+    /// assert_eq!(transactions[0].fee(), 1);
+    /// assert_eq!(transactions[1].nonce(), 1);
+    /// 
+    /// let unknown = Transaction::load_batch(&path, &[(max, "max_ford_pw")]);
+    /// 
+    /// assert!(matches!(unknown, Err(BatchError::UnknownAccount(_))));
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn load_batch(path: &Path, accounts: &[(Account, &str)]) -> Result<Vec<Self>, BatchError> {
+        #[derive(Deserialize)]
+        struct Entry {
+            from: String,
+            to: String,
+            amount: Amount,
+            #[serde(default)]
+            fee: Amount,
+        }
+
+        let json = fs::read_to_string(path).map_err(BatchError::Io)?;
+
+        let entries: Vec<Entry> = serde_json::from_str(&json).map_err(BatchError::Parse)?;
+
+        let resolve = |address: &str| {
+            accounts
+                .iter()
+                .find(|(account, _)| account.address() == address)
+                .ok_or_else(|| BatchError::UnknownAccount(address.to_string()))
+        };
+
+        let mut nonces: HashMap<&str, u64> = HashMap::new();
+
+        entries
+            .iter()
+            .map(|entry| {
+                let (sender, password) = resolve(&entry.from)?;
+                let (receiver, _) = resolve(&entry.to)?;
+
+                let nonce = nonces.entry(&entry.from).or_insert(0);
+
+                let transaction = Transaction::new_with_fee(sender.clone(), receiver.clone(), entry.amount, entry.fee, password)
+                    .map_err(|_| BatchError::Unauthorized(entry.from.clone()))?
+                    .with_nonce(*nonce);
+
+                *nonce += 1;
+
+                Ok(transaction)
+            })
+            .collect()
+    }
+
+    /// This method returns the amount of the transaction, which is the sum of the amounts of its outputs;
+    /// if the sum overflows, the amount is `Amount::MAX`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let mut chloe = Account::new("Chloe", "Savage", "KloeeSavageTrue1234").unwrap();
+    /// let zoey = Account::new("Zoey", "Jacobson", "ZoomingPassword000#").unwrap();
+    /// chloe.add_money(300);
+    /// 
+    /// let transaction = Transaction::new(chloe, zoey, 20, "KloeeSavageTrue1234").unwrap();
+    /// 
+    /// assert_eq!(transaction.amount(), 20);
+    /// ```
+    pub fn amount(&self) -> Amount {
+        self.outputs.iter().fold(0, |total: Amount, (_, amount)| total.saturating_add(*amount))
+    }
+
+    /// This method returns a reference to the sender's `Account`, or `None` for a coinbase transaction,
+    /// without cloning the account.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let mut gia = Account::new("Gia", "Lowe", "g14_l0w3").unwrap();
+    /// let hal = Account::new("Hal", "Moss", "hal.moss.pw").unwrap();
+    /// gia.add_money(5);
+    /// 
+    /// let transaction = Transaction::new(gia.clone(), hal.clone(), 2, "g14_l0w3").unwrap();
+    /// 
+    /// assert_eq!(transaction.sender(), Some(&gia));
+    /// assert_eq!(Transaction::coinbase(hal, 5).sender(), None);
+    /// ```
+    pub fn sender(&self) -> Option<&Account> {
+        self.sender.as_ref()
+    }
+
+    /// This method returns a reference to the receiver's `Account` of the first output, without cloning the account,
+    /// or `None` if the transaction has no outputs; the receivers of all the outputs are returned by `outputs()`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let mut gia = Account::new("Gia", "Lowe", "g14_l0w3").unwrap();
+    /// let hal = Account::new("Hal", "Moss", "hal.moss.pw").unwrap();
+    /// gia.add_money(5);
+    /// 
+    /// let transaction = Transaction::new(gia, hal.clone(), 2, "g14_l0w3").unwrap();
+    /// 
+    /// assert_eq!(transaction.receiver(), Some(&hal));
+    /// ```
+    pub fn receiver(&self) -> Option<&Account> {
+        self.outputs.first().map(|(receiver, _)| receiver)
+    }
+
+    /// This method returns the outputs of the transaction, since the `outputs` field isn't `pub`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let mut ivy = Account::new("Ivy", "Jones", "1vy_j0n3s").unwrap();
+    /// let jay = Account::new("Jay", "Kent", "jay.kent.pw").unwrap();
+    /// ivy.add_money(5);
+    /// 
+    /// let transaction = Transaction::new(ivy, jay.clone(), 2, "1vy_j0n3s").unwrap();
+    /// 
+    /// assert_eq!(transaction.outputs(), &[(jay, 2)]);
+    /// ```
+    pub fn outputs(&self) -> &[(Account, Amount)] {
+        &self.outputs
+    }
+
+    /// This method returns the fee of the transaction, since the `fee` field isn't `pub`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let mut pia = Account::new("Pia", "Kurz", "p14_kurz").unwrap();
+    /// let quentin = Account::new("Quentin", "Roy", "quentin_roy_pw").unwrap();
+    /// pia.add_money(5);
+    /// 
+    /// let transaction = Transaction::new(pia, quentin, 5, "p14_kurz").unwrap();
+    /// 
+    /// assert_eq!(transaction.fee(), 0); // `new()` creates transactions without fees
+    /// ```
+    pub fn fee(&self) -> Amount {
+        self.fee
+    }
+
+    /// This method returns the nonce of the transaction, since the `nonce` field isn't `pub`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let miner = Account::new("Abel", "Cruz", "4b3l_cruz").unwrap();
+    /// 
+    /// let coinbase = Transaction::coinbase(miner, 10);
+    /// 
+    /// assert_eq!(coinbase.nonce(), 0);
+    /// ```
+    pub fn nonce(&self) -> u64 {
+        self.nonce
+    }
+
+    /// This method returns the identifier of the chain the transaction is made for,
+    /// since the `chain_id` field isn't `pub`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let miner = Account::new("Abel", "Cruz", "4b3l_cruz").unwrap();
+    /// 
+    /// let coinbase = Transaction::coinbase(miner, 10);
+    /// 
+    /// assert_eq!(coinbase.chain_id(), 0);
+    /// ```
+    pub fn chain_id(&self) -> u32 {
+        self.chain_id
+    }
+
+    /// This method returns the memo of the transaction, if any, since the `memo` field isn't `pub`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let miner = Account::new("Abel", "Cruz", "4b3l_cruz").unwrap();
+    /// 
+    /// let coinbase = Transaction::coinbase(miner, 10);
+    /// 
+    /// assert_eq!(coinbase.memo(), None);
+    /// ```
+    pub fn memo(&self) -> Option<&str> {
+        self.memo.as_deref()
+    }
+
+    /// This method returns the `DateTime<Utc>` time when the transaction was generated, since the `time` field isn't `pub`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let mut wes = Account::new("Wes", "Young", "w3s_y0ung").unwrap();
+    /// let xena = Account::new("Xena", "Brooks", "xena.brooks!").unwrap();
+    /// wes.add_money(5);
+    /// 
+    /// let first = Transaction::new(wes.clone(), xena.clone(), 1, "w3s_y0ung").unwrap();
+    /// let second = Transaction::new(wes, xena, 1, "w3s_y0ung").unwrap();
+    /// 
+    /// assert!(first.time() <= second.time());
+    /// ```
+    pub fn time(&self) -> DateTime<Utc> {
+        self.time
+    }
+
+    /// This method returns `true` if the transaction is a coinbase transaction, i.e. if it has no sender.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let mut kai = Account::new("Kai", "Moana", "k41_m04n4").unwrap();
+    /// let lea = Account::new("Lea", "Roux", "lea.roux.pass").unwrap();
+    /// kai.add_money(5);
+    /// 
+    /// let transaction = Transaction::new(kai, lea, 5, "k41_m04n4").unwrap();
+    /// 
+    /// assert!(!transaction.is_coinbase());
+    /// ```
+    pub fn is_coinbase(&self) -> bool {
+        self.sender.is_none()
+    }
+
+    /// This method returns the hash of the transaction, since the `hash` field isn't `pub`.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let mut luke = Account::new("Luke", "Steffen", "Luke_Steffen999").unwrap();
+    /// let jerold = Account::new("Jerold", "Butcher", "Jer0ld_Butcher1000##").unwrap();
+    /// luke.add_money(70);
+    /// 
+    /// let transaction = Transaction::new(luke, jerold, 10, "Luke_Steffen999").unwrap();
+    /// 
+    /// assert_eq!(transaction.hash().len(), 64);
+    /// ```
+    pub fn hash(&self) -> [u8; 64] {
+        self.hash
+    }
+
+    /// This method returns the size of the transaction in bytes, which is the size of the signed bytes,
+    /// which grows with the number of outputs, plus the size of the signature and of the hash.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let mut rory = Account::new("Rory", "Shaw", "r0ry_sh4w").unwrap();
+    /// let sid = Account::new("Sid", "Tate", "sid.tate.pw").unwrap();
+    /// rory.add_money(5);
+    /// 
+    /// let single = Transaction::new(rory.clone(), sid.clone(), 2, "r0ry_sh4w").unwrap();
+    /// let double = Transaction::new_with_outputs(rory, vec![(sid.clone(), 1), (sid, 1)], 0, "r0ry_sh4w").unwrap();
+    /// 
+    /// assert!(double.size_bytes() > single.size_bytes());
+    /// ```
+    pub fn size_bytes(&self) -> usize {
+        self.signed_message().len() + self.signature.len() + self.hash.len()
+    }
+
+    /// This method encodes the transaction with `bincode`, in the compact binary format of the `binary` module,
+    /// like `Block::to_bytes()`: the signature and the hash are kept as they are, so the transaction
+    /// decoded with `from_bytes()` is the same transaction, and it doesn't need to be signed again.
+    /// The method is available with the `bincode` feature.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let mut yara = Account::new("Yara", "Zell", "y4r4_z3ll").unwrap();
+    /// let abe = Account::new("Abe", "Bishop", "abe.bishop.pw").unwrap();
+    /// yara.add_money(8);
+    /// 
+    /// let transaction = Transaction::new(yara, abe, 3, "y4r4_z3ll").unwrap();
+    /// let decoded = Transaction::from_bytes(&transaction.to_bytes()).unwrap();
+    /// 
+    /// assert_eq!(decoded, transaction);
+    /// assert!(decoded.verify_hash() && decoded.verify_signature());
+    /// ```
+    #[cfg(feature = "bincode")]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        binary::encode(self)
+    }
+
+    /// Decodes a transaction encoded with `to_bytes()`; the transaction isn't verified,
+    /// see `verify_hash()` and `verify_signature()`.
+    /// 
+    /// The function returns a `Result<Transaction, DecodeError>`:
+    /// - if the bytes end before the transaction, a `DecodeError::UnexpectedEnd` error is returned
+    /// - if there are bytes left after the transaction, a `DecodeError::TrailingBytes` error is returned
+    /// - if a field has an invalid value, a `DecodeError::Invalid` error is returned
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// # use blockchain::binary::DecodeError;
+    /// let miner = Account::new("Cass", "Dunn", "c4ss_dunn").unwrap();
+    /// let bytes = Transaction::coinbase(miner, 5).to_bytes();
+    /// 
+    /// assert_eq!(Transaction::from_bytes(&bytes[..bytes.len() - 1]), Err(DecodeError::UnexpectedEnd));
+    /// ```
+    #[cfg(feature = "bincode")]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        binary::decode(bytes)
+    }
+
+    /// This method checks the digital signature of the transaction, using only the public key
+    /// of the sender's account, so anyone can confirm that the sender authorized the transaction.
+    /// 
+    /// Coinbase transactions aren't signed, so their verification is skipped and `true` is returned.
+    /// The signature is checked with `verify_strict()` of `ed25519_dalek`, which also rejects the public keys
+    /// and the points `R` of small order, so that every node agrees on the signatures it accepts.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let mut nadia = Account::new("Nadia", "Petrova", "nadia_PETROVA_77").unwrap();
+    /// let hugo = Account::new("Hugo", "Lambert", "Hugo.Lambert.1999").unwrap();
+    /// nadia.add_money(40);
+    /// 
+    /// let transaction = Transaction::new(nadia, hugo.clone(), 12, "nadia_PETROVA_77").unwrap();
+    /// 
+    /// assert!(transaction.verify_signature());
+    /// 
+    /// let mut forged_transaction = transaction.clone();
+    /// forged_transaction.sender = Some(hugo); // hugo didn't sign this transaction
+    /// 
+    /// assert!(!forged_transaction.verify_signature());
+    /// ```
+    pub fn verify_signature(&self) -> bool {
+        let sender = match &self.sender {
+            Some(sender) => sender,
+            None => return true,
+        };
+
+        // a signature whose scalar isn't reduced can't even be decoded
+        match (PublicKey::from_bytes(&sender.public_key()), Signature::from_bytes(&self.signature)) {
+            (Ok(public_key), Ok(signature)) => public_key.verify_strict(&self.signed_message(), &signature).is_ok(),
+            _ => false,
+        }
+    }
+
+    /// This method returns the identifier of the transaction, which is its hash.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let mut bea = Account::new("Bea", "Dorn", "b34_d0rn").unwrap();
+    /// let cyrus = Account::new("Cyrus", "Eld", "cyrus.eld.pw").unwrap();
+    /// bea.add_money(5);
+    /// 
+    /// let transaction = Transaction::new(bea, cyrus, 1, "b34_d0rn").unwrap();
+    /// 
+    /// assert_eq!(transaction.id(), transaction.hash());
+    /// ```
+    pub fn id(&self) -> [u8; 64] {
+        self.hash
+    }
+
+    /// This method returns the bytes the sender signs, generated from the fields of the transaction, see `sign()`:
+    /// if the transaction wasn't tampered with, they're the bytes the signature was made on.
+    /// 
+    /// The accounts are written as their public keys, and every field has a fixed size or is prefixed
+    /// by its length, like in the `binary` module, so two different transactions never have the same signed bytes.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let mut ada = Account::new("Ada", "Finch", "4d4_f1nch").unwrap();
+    /// let bo = Account::new("Bo", "Gray", "bo.gray.pw").unwrap();
+    /// ada.add_money(5);
+    /// 
+    /// let transaction = Transaction::new(ada, bo, 1, "4d4_f1nch").unwrap();
+    /// 
+    /// assert_ne!(transaction.signing_bytes(), transaction.clone().with_nonce(1).signing_bytes());
+    /// ```
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        self.signed_message()
+    }
+
+    /// This method is called when a new transaction is generated,
+    /// and it is used to perform the digital signature of the new transaction.
+    /// 
+    /// The digital signature is generated using the `Keypair` from the sender's account,
+    /// using the `ed25519_dalek` crate.
+    /// 
+    /// The signature is performed on the bytes returned by `signing_bytes()`, generated by using:
+    /// - the public key of the sender's `Account`
+    /// - the public key of the receiver's `Account` and the amount of every output
+    /// - the fee of the transaction
+    /// - the nonce of the transaction
+    /// - the identifier of the chain
+    /// - the memo of the transaction
+    /// - the `DateTime<Utc>` time when the transaction was generated
+    /// 
+    /// Coinbase transactions have no sender, so they aren't signed.
+    fn sign(&mut self) {
+        if let Some(sender) = &self.sender {
+            let keypair = Keypair::from_bytes(&sender.keypair()).expect("Error generating the Keypair while signing the transaction.");
+
+            self.signature = keypair.sign(&self.signed_message()).to_bytes();
+        }
+    }
+
+    /// Generates the bytes signed by the sender from the fields of the transaction, see `sign()`.
+    /// 
+    /// The names and the balances of the accounts aren't signed, since they don't identify the accounts:
+    /// a receiver is bound by its public key, so the money can't be redirected to another account.
+    fn signed_message(&self) -> Vec<u8> {
+        let mut message = Vec::new();
+
+        self.sender.as_ref().map(Account::public_key).encode(&mut message);
+
+        self.outputs
+            .iter()
+            .map(|(receiver, amount)| (receiver.public_key(), *amount))
+            .collect::<Vec<_>>()
+            .encode(&mut message);
+
+        self.fee.encode(&mut message);
+        self.nonce.encode(&mut message);
+        self.chain_id.encode(&mut message);
+        self.memo.encode(&mut message);
+        self.time.encode(&mut message);
+
+        message
+    }
+
+    /// This method is called when a new transacion is generated,
+    /// and is is used to calculate the SHA-512 hash of the new transaction.
+    /// 
+    /// The hash is calculated by using the signed bytes and the `signature`, generated in the `sign()` method.
+    fn calculate_hash(&mut self) {
+        self.hash = self.digest();
+    }
+
+    /// Calculates the SHA-512 hash of the signed bytes followed by the `signature`, see `calculate_hash()`:
+    /// the signature has a fixed size, so two different transactions never hash the same bytes.
+    fn digest(&self) -> [u8; 64] {
+        let mut message = self.signed_message();
+        message.extend_from_slice(&self.signature);
+
+        Sha512Hasher::hash(&message)
+    }
+
+    /// This method checks that the hash of the transaction matches its contents, e.g. after
+    /// the transaction was deserialized: the signed bytes are generated again from the fields
+    /// of the transaction, and the hash is calculated again from them and the `signature`.
+    /// 
+    /// Unlike `validate()`, the password and the balance of the sender aren't checked.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::transaction::Transaction;
+    /// # use blockchain::account::Account;
+    /// let mut una = Account::new("Una", "Boyd", "un4_b0yd").unwrap();
+    /// let vic = Account::new("Vic", "Cole", "vic.cole.pw").unwrap();
+    /// una.add_money(10);
+    /// 
+    /// let transaction = Transaction::new(una, vic.clone(), 3, "un4_b0yd").unwrap();
+    /// 
+    /// assert!(transaction.verify_hash());
+    /// 
+    /// let mut tampered = transaction.clone();
+    /// tampered.sender = Some(vic); // the hash doesn't cover this sender
+    /// 
+    /// assert!(!tampered.verify_hash());
+    /// ```
+    pub fn verify_hash(&self) -> bool {
+        self.digest() == self.hash
+    }
+
+    /// This method checks if the transaction is valid,
+    /// and returns a `Err(ValidationError)` if the transaction isn't valid.
+    /// 
+    /// - If the hash in the input doesn't match with the `hash` of the transaction,
+    ///   a `ValidationError::Tempered` error is returned.
+    /// - If the signature verification doesn't succeed,
+    ///   a `ValidationError::InvalidSign` error is returned.
+    /// - If the transaction has no outputs, if the amount of an output is zero,
+    ///   or if the amount of the transaction (plus the fee) is more than the sender's balance,
+    ///   a `ValidationError::InvalidAmount` error is returned.
+    /// 
+    /// The password of the sender isn't checked: the signature already proves that the sender authorized
+    /// the transaction, and a deserialized sender has no password, so its transactions can still be validated.
+    /// Coinbase transactions have no sender, so only their hash is checked.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::transaction::{Transaction, ValidationError};
+    /// # use blockchain::account::Account;
+    /// let mut odin = Account::new("Odin", "Dennell", "OdinDennellPass102938").unwrap();
+    /// let kenya = Account::new("Kenya", "Dawson", "kenyyyya_dawwwwson69").unwrap();
+    /// odin.add_money(10);
+    /// 
+    /// // cloning accounts because `Account` doesn't implement the `Copy` trait
+    /// let valid_transaction = Transaction::new(odin.clone(), kenya.clone(), 5, "OdinDennellPass102938").unwrap();
+    /// 
+    /// assert_eq!(valid_transaction.validate(valid_transaction.hash()).unwrap(), ()); // if the transaction is valid `()` is returned
+    /// 
+    /// assert_eq!(kenya.balance(), 0); // as you can see Kenya still has no money (check the definition of the struct for reference)
+    /// 
+    /// let invalid_transaction = Transaction::new(kenya, odin, 80, "kenyyyya_dawwwwson69").unwrap();
+    /// 
+    /// assert_eq!(invalid_transaction.validate(invalid_transaction.hash()), Err(ValidationError::InvalidAmount));
+    /// ```
+    pub fn validate(&self, hash: [u8; 64]) -> Result<(), ValidationError> {
+        if hash != self.hash {
+            Err(ValidationError::Tempered)
+        } else if let Some(sender) = &self.sender {
+            if !self.verify_signature() {
+                Err(ValidationError::InvalidSignature)
+            } else {
+                // the total is `None` if it overflows
+                let total = self.outputs.iter().try_fold(self.fee, |total, (_, amount)| total.checked_add(*amount));
+
+                if self.outputs.is_empty()
+                    || self.outputs.iter().any(|(_, amount)| *amount == 0)
+                    || total.is_none_or(|total| total > sender.balance()) {
+                    Err(ValidationError::InvalidAmount)
+                } else {
+                    Ok(())
+                }
+            }
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Checks the signatures of all the given transactions at once, like calling `Transaction::verify_signature()`
+/// on every transaction, and returns `true` if they're all valid; the coinbase transactions aren't signed, so they're skipped.
+/// 
+/// The signatures are checked one by one with `verify_strict()`, on all the threads of the machine, instead of
+/// the batch verification of `ed25519_dalek`: the batch equation is weighted by random coefficients, so a signature
+/// whose `R` has a component of small order passes it only for some coefficients, while it always fails the single verification,
+/// and two nodes could disagree on the validity of the same block. The single verifications always agree.
+pub(crate) fn verify_signatures_batch(transactions: &[Transaction]) -> bool {
+    transactions.par_iter().all(|transaction| transaction.verify_signature())
+}
+
+/// An enum to handle errors generated while validating `Transaction`s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationError {
+    Tempered,
+    WrongPassword,
+    InvalidSignature,
+    InvalidAmount,
+    InvalidNonce,
+    ImmatureCoinbase,
+    AmountTooLarge,
+    WrongChain,
+    FeeTooLow,
+    MemoTooLong,
+    UnknownSender,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Tempered =>  write!(f, "Tempered transaction."),
+            Self::WrongPassword => write!(f, "Wrong password."),
+            Self::InvalidSignature => write!(f, "Invalid signature."),
+            Self::InvalidAmount => write!(f, "Invalid amount."),
+            Self::InvalidNonce => write!(f, "Invalid nonce."),
+            Self::ImmatureCoinbase => write!(f, "Immature coinbase."),
+            Self::AmountTooLarge => write!(f, "Amount too large."),
+            Self::WrongChain => write!(f, "Wrong chain."),
+            Self::FeeTooLow => write!(f, "Fee too low."),
+            Self::MemoTooLong => write!(f, "Memo too long."),
+            Self::UnknownSender => write!(f, "Unknown sender."),
+        }
+    }
+}
+
+impl error::Error for ValidationError {}
+
+/// An enum to handle errors generated while creating `Transaction`s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxError {
+    InvalidAmount,
+    InsufficientFunds,
+    Unauthorized,
+}
+
+impl fmt::Display for TxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::InvalidAmount => write!(f, "Invalid amount."),
+            Self::InsufficientFunds => write!(f, "Insufficient funds."),
+            Self::Unauthorized => write!(f, "Unauthorized."),
+        }
+    }
+}
+
+impl error::Error for TxError {}
+
+/// An enum to handle errors generated while loading a batch of `Transaction`s from a file.
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+pub enum BatchError {
+    Io(io::Error),
+    Parse(serde_json::Error),
+    UnknownAccount(String),
+    Unauthorized(String),
+}
+
+#[cfg(feature = "serde")]
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Can't read the file: {}", e),
+            Self::Parse(e) => write!(f, "Can't parse the transactions: {}", e),
+            Self::UnknownAccount(address) => write!(f, "Unknown account: {}", address),
+            Self::Unauthorized(address) => write!(f, "Wrong password for the account: {}", address),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl error::Error for BatchError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn getters_borrow_the_accounts_of_the_transaction() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(10);
+
+        let transaction = Transaction::new(sender, receiver, 5, "sender_password").unwrap();
+
+        assert!(std::ptr::eq(transaction.sender().unwrap(), transaction.sender.as_ref().unwrap()));
+        assert!(std::ptr::eq(transaction.receiver().unwrap(), &transaction.outputs[0].0));
+
+        let keypair = Keypair::from_bytes(&transaction.sender().unwrap().keypair()).unwrap();
+        let signature = keypair.sign(&transaction.signing_bytes()).to_bytes();
+
+        assert_eq!(signature, transaction.signature);
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn transaction_round_trips_through_bytes() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(10);
+
+        let transaction = Transaction::new_with_fee(sender, receiver, 4, 1, "sender_password").unwrap()
+            .with_nonce(3)
+            .with_chain_id(7);
+
+        let bytes = transaction.to_bytes();
+        let decoded = Transaction::from_bytes(&bytes).unwrap();
+
+        assert_eq!(decoded.hash(), transaction.hash());
+        assert_eq!(decoded.signature, transaction.signature);
+        assert!(decoded.verify_hash());
+        assert!(decoded.verify_signature());
+        assert_eq!(decoded.to_bytes(), bytes);
+
+        assert_eq!(Transaction::from_bytes(&[&bytes[..], &[0]].concat()), Err(DecodeError::TrailingBytes));
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn memos_and_outputs_round_trip_through_bytes() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let first = Account::new("First", "Test", "first_password").unwrap();
+        let second = Account::new("Second", "Test", "second_password").unwrap();
+        sender.add_money(10);
+
+        let transaction = Transaction::new_with_outputs(sender, vec![(first, 2), (second, 3)], 1, "sender_password").unwrap()
+            .with_memo("Rent, March");
+
+        let decoded = Transaction::from_bytes(&transaction.to_bytes()).unwrap();
+
+        assert_eq!(decoded, transaction);
+        assert_eq!(decoded.memo(), Some("Rent, March"));
+        assert_eq!(decoded.outputs, transaction.outputs);
+        assert!(decoded.verify_hash() && decoded.verify_signature());
+
+        // the keys and the signature are written as they are, not as hex strings
+        let bytes = transaction.to_bytes();
+
+        assert!(bytes.windows(64).any(|window| window == transaction.signature));
+        assert!(bytes.windows(64).any(|window| window == transaction.hash));
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn decoded_transactions_can_be_validated() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(10);
+
+        let transaction = Transaction::new(sender, receiver, 4, "sender_password").unwrap();
+        let decoded = Transaction::from_bytes(&transaction.to_bytes()).unwrap();
+
+        assert!(!decoded.sender().unwrap().verify_password("sender_password")); // the password isn't encoded
+        assert_eq!(decoded.validate(decoded.hash()), Ok(()));
+    }
+
+    #[test]
+    fn transactions_with_outputs_need_the_password_of_the_sender() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(10);
+
+        let outputs = vec![(receiver.clone(), 2), (receiver, 3)];
+
+        assert!(Transaction::new_with_outputs(sender.clone(), outputs.clone(), 1, "sender_password").is_ok());
+        assert_eq!(Transaction::new_with_outputs(sender, outputs, 1, "receiver_password"), Err(TxError::Unauthorized));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialized_transaction_has_no_password_hash() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(10);
+
+        let transaction = Transaction::new(sender.clone(), receiver, 4, "sender_password").unwrap();
+        let json = serde_json::to_string(&transaction).unwrap();
+
+        assert!(!json.contains("password"));
+        assert!(!json.contains(sender.hash_password()));
+    }
+
+    #[test]
+    fn receivers_are_signed_by_public_key() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        let impostor = Account::new("Receiver", "Test", "impostor_password").unwrap(); // same name and balance
+        sender.add_money(10);
+
+        let transaction = Transaction::new(sender, receiver, 4, "sender_password").unwrap();
+
+        let mut redirected = transaction.clone();
+        redirected.outputs[0].0 = impostor;
+
+        assert_ne!(redirected.signing_bytes(), transaction.signing_bytes());
+        assert!(!redirected.verify_signature());
+        assert!(!redirected.verify_hash());
+    }
+
+    #[test]
+    fn hash_covers_the_signed_bytes_and_the_signature() {
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        let namesake = Account::new("Receiver", "Test", "namesake_password").unwrap();
+        sender.add_money(10);
+
+        let first = Transaction::new(sender.clone(), receiver, 4, "sender_password").unwrap();
+
+        let mut second = first.clone();
+        second.outputs[0].0 = namesake; // the Display of the receiver is the same
+        second.sign();
+        second.calculate_hash();
+
+        assert_ne!(first.hash(), second.hash());
+        assert_eq!(first.hash(), Sha512Hasher::hash(&[first.signing_bytes(), first.signature.to_vec()].concat()));
+    }
+
+    #[test]
+    fn batched_signatures_match_the_single_signatures() {
+        let senders: Vec<Account> = (0..3)
+            .map(|i| {
+                let mut sender = Account::new("Sender", &i.to_string(), "sender_password").unwrap();
+                sender.add_money(10);
+                sender
+            })
+            .collect();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+
+        let mut transactions: Vec<Transaction> = senders
+            .iter()
+            .map(|sender| Transaction::new(sender.clone(), receiver.clone(), 1, "sender_password").unwrap())
+            .chain(std::iter::once(Transaction::coinbase(receiver.clone(), 5)))
+            .collect();
+
+        assert!(verify_signatures_batch(&transactions));
+        assert!(verify_signatures_batch(&[]));
+
+        // the signature of another transaction
+        transactions[1].signature = transactions[0].signature;
+
+        assert!(!transactions[1].verify_signature());
+        assert!(!verify_signatures_batch(&transactions));
+
+        // a signature which can't be decoded, since `R` isn't a point
+        transactions[1].signature = [0xff; 64];
+
+        assert!(!verify_signatures_batch(&transactions));
+        assert!(verify_signatures_batch(&[transactions[0].clone(), transactions[2].clone()]));
+    }
+
+    #[test]
+    fn signatures_with_a_torsion_component_are_always_rejected() {
+        use curve25519_dalek::edwards::CompressedEdwardsY;
+
+        let mut sender = Account::new("Sender", "Test", "sender_password").unwrap();
+        let receiver = Account::new("Receiver", "Test", "receiver_password").unwrap();
+        sender.add_money(10);
+
+        let mut transaction = Transaction::new(sender, receiver, 1, "sender_password").unwrap();
+
+        // the point `(0, -1)` of order 2 is added to `R`, which a batch with random coefficients accepts half of the times
+        let mut minus_one = [0xff; 32];
+        minus_one[0] = 0xec;
+        minus_one[31] = 0x7f;
+        let torsion = CompressedEdwardsY(minus_one).decompress().unwrap();
+
+        let mut r = [0; 32];
+        r.copy_from_slice(&transaction.signature[..32]);
+        let tainted = CompressedEdwardsY(r).decompress().unwrap() + torsion;
+        transaction.signature[..32].copy_from_slice(tainted.compress().as_bytes());
+
+        for _ in 0..64 {
+            assert!(!transaction.verify_signature());
+            assert!(!verify_signatures_batch(std::slice::from_ref(&transaction)));
+        }
+    }
+}
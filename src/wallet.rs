@@ -0,0 +1,244 @@
+//! A registry of accounts, indexed by their addresses, so that the transactions
+//! can be made and the balances can be queried knowing only the addresses.
+
+use crate::{
+    account::{Account, AccountError},
+    amount::{Amount, Balance},
+    transaction::Transaction,
+    blockchain::BlockChain,
+};
+use std::{collections::HashMap, fmt, error};
+
+/// A structure to handle the `Account`s of a user, indexed by their addresses,
+/// returned by `Account::address()`.
+/// 
+/// # Example
+/// ```
+/// # use blockchain::wallet::Wallet;
+/// let mut wallet = Wallet::new();
+/// 
+/// let address = wallet.create_account("Lena", "Voss", "l3n4_v0ss").unwrap();
+/// 
+/// assert_eq!(wallet.get(&address).unwrap().address(), address);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Wallet {
+    accounts: HashMap<String, Account>,
+}
+
+impl Wallet {
+    /// Generates a new `Wallet`, without accounts.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::wallet::Wallet;
+    /// let wallet = Wallet::new();
+    /// 
+    /// assert!(wallet.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// This method generates a new `Account` in the wallet, and returns its address;
+    /// if the names of the account aren't valid, see `Account::new()`, the `AccountError` is returned.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::wallet::Wallet;
+    /// let mut wallet = Wallet::new();
+    /// 
+    /// let address = wallet.create_account("Otto", "Brandt", "0tt0_br4ndt").unwrap();
+    /// 
+    /// assert_eq!(wallet.len(), 1);
+    /// assert!(wallet.get(&address).unwrap().verify_password("0tt0_br4ndt"));
+    /// ```
+    pub fn create_account(&mut self, first_name: &str, last_name: &str, password: &str) -> Result<String, AccountError> {
+        Ok(self.insert(Account::new(first_name, last_name, password)?))
+    }
+
+    /// This method puts an existing `Account` in the wallet, and returns its address;
+    /// an account with the same address is replaced.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::wallet::Wallet;
+    /// # use blockchain::account::Account;
+    /// let mut wallet = Wallet::new();
+    /// let pia = Account::new("Pia", "Kern", "p1a_k3rn").unwrap();
+    /// 
+    /// let address = wallet.insert(pia.clone());
+    /// 
+    /// assert_eq!(wallet.get(&address), Some(&pia));
+    /// ```
+    pub fn insert(&mut self, account: Account) -> String {
+        let address = account.address();
+
+        self.accounts.insert(address.clone(), account);
+
+        address
+    }
+
+    /// This method returns the account with the given address, if it's in the wallet.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::wallet::Wallet;
+    /// let mut wallet = Wallet::new();
+    /// wallet.create_account("Rudi", "Falk", "rud1.f4lk").unwrap();
+    /// 
+    /// assert_eq!(wallet.get("not an address"), None);
+    /// ```
+    pub fn get(&self, address: &str) -> Option<&Account> {
+        self.accounts.get(address)
+    }
+
+    /// This method returns a mutable reference to the account with the given address, if it's in the wallet.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::wallet::Wallet;
+    /// let mut wallet = Wallet::new();
+    /// let address = wallet.create_account("Sven", "Ahl", "sv3n_4hl").unwrap();
+    /// 
+    /// wallet.get_mut(&address).unwrap().add_money(5);
+    /// 
+    /// assert_eq!(wallet.get(&address).unwrap().balance(), 5);
+    /// ```
+    pub fn get_mut(&mut self, address: &str) -> Option<&mut Account> {
+        self.accounts.get_mut(address)
+    }
+
+    /// This method returns the addresses of the accounts of the wallet, in no particular order.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::wallet::Wallet;
+    /// let mut wallet = Wallet::new();
+    /// let address = wallet.create_account("Tess", "Moor", "t3ss_m00r").unwrap();
+    /// 
+    /// assert_eq!(wallet.addresses().collect::<Vec<_>>(), vec![&address]);
+    /// ```
+    pub fn addresses(&self) -> impl Iterator<Item = &String> {
+        self.accounts.keys()
+    }
+
+    /// This method returns the number of accounts of the wallet.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::wallet::Wallet;
+    /// let mut wallet = Wallet::new();
+    /// wallet.create_account("Ugo", "Neri", "ug0_n3r1").unwrap();
+    /// 
+    /// assert_eq!(wallet.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    /// This method returns `true` if the wallet has no accounts.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::wallet::Wallet;
+    /// assert!(Wallet::new().is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    /// This method generates a new `Transaction`, like `Transaction::new_with_fee()`,
+    /// resolving the sender and the receiver from their addresses.
+    /// 
+    /// The method returns a `Result<Transaction, WalletError>`:
+    /// - if an address isn't in the wallet, a `WalletError::UnknownAccount` error is returned
+    /// - if the password isn't the password of the sender, a `WalletError::WrongPassword` error is returned
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::wallet::{Wallet, WalletError};
+    /// let mut wallet = Wallet::new();
+    /// let vera = wallet.create_account("Vera", "Holm", "v3r4_h0lm").unwrap();
+    /// let will = wallet.create_account("Will", "Dunn", "w1ll_dunn").unwrap();
+    /// 
+    /// wallet.get_mut(&vera).unwrap().add_money(10);
+    /// 
+    /// let transaction = wallet.transaction(&vera, &will, 4, 1, "v3r4_h0lm").unwrap();
+    /// 
+    /// assert_eq!(transaction.amount(), 4);
+    /// assert_eq!(transaction.outputs()[0].0.address(), will);
+    /// 
+    /// assert_eq!(wallet.transaction(&vera, &will, 4, 1, "wrong"), Err(WalletError::WrongPassword));
+    /// assert_eq!(wallet.transaction(&vera, "nobody", 4, 1, "v3r4_h0lm"), Err(WalletError::UnknownAccount("nobody".to_string())));
+    /// ```
+    pub fn transaction(&self, from: &str, to: &str, amount: Amount, fee: Amount, sender_password: &str) -> Result<Transaction, WalletError> {
+        let sender = self.resolve(from)?;
+        let receiver = self.resolve(to)?;
+
+        Transaction::new_with_fee(sender.clone(), receiver.clone(), amount, fee, sender_password).map_err(|_| WalletError::WrongPassword)
+    }
+
+    /// This method returns the balance of the account with the given address in the given `BlockChain`,
+    /// computed by `BlockChain::balance_of()`, or `None` if the address isn't in the wallet.
+    /// 
+    /// # Example
+    /// ```
+    /// # use blockchain::wallet::Wallet;
+    /// # use blockchain::blockchain::BlockChain;
+    /// let mut wallet = Wallet::new();
+    /// let yara = wallet.create_account("Yara", "Benn", "y4r4_b3nn").unwrap();
+    /// 
+    /// let mut blockchain = BlockChain::new_with_difficulty(2, 4);
+    /// blockchain.set_miner(wallet.get(&yara).unwrap().clone());
+    /// blockchain.set_block_reward(50);
+    /// blockchain.mine_pending().unwrap();
+    /// 
+    /// assert_eq!(wallet.balance_of(&yara, &blockchain), Some(50));
+    /// assert_eq!(wallet.balance_of("nobody", &blockchain), None);
+    /// ```
+    pub fn balance_of(&self, address: &str, blockchain: &BlockChain) -> Option<Balance> {
+        self.get(address).map(|account| blockchain.balance_of(account))
+    }
+
+    /// Returns the account with the given address, or a `WalletError::UnknownAccount` error.
+    fn resolve(&self, address: &str) -> Result<&Account, WalletError> {
+        self.get(address).ok_or_else(|| WalletError::UnknownAccount(address.to_string()))
+    }
+}
+
+/// An enum to handle errors generated while making `Transaction`s with a `Wallet`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WalletError {
+    UnknownAccount(String),
+    WrongPassword,
+}
+
+impl fmt::Display for WalletError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownAccount(address) => write!(f, "Unknown account: {}", address),
+            Self::WrongPassword => write!(f, "Wrong password."),
+        }
+    }
+}
+
+impl error::Error for WalletError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accounts_are_found_by_address() {
+        let mut wallet = Wallet::new();
+
+        let ines = wallet.create_account("Ines", "Roth", "1n3s_r0th").unwrap();
+        let jon = wallet.create_account("Jon", "Kay", "j0n_k4y").unwrap();
+
+        assert_ne!(ines, jon);
+        assert_eq!(wallet.len(), 2);
+        assert!(wallet.get(&ines).unwrap().verify_password("1n3s_r0th"));
+        assert!(wallet.get(&jon).unwrap().verify_password("j0n_k4y"));
+    }
+}